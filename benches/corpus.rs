@@ -0,0 +1,277 @@
+// Ratio and throughput of every algorithm over the test/files corpus, with
+// flate2 deflate as a baseline. Prints a table and writes JSON so runs can be
+// compared across commits:
+//
+//     cargo bench --bench corpus -- [--json PATH] [--min-time MS] [FILTER]...
+//
+// Each FILTER keeps the files whose path contains it and the algorithm it
+// names; with no FILTER everything runs. The JSON defaults to target/bench/corpus.json.
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use goud_compressor::{
+    compress_with_options, decompress_with_options, Algorithm, CompressOptions, DecompressOptions,
+};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const CORPUS_DIR: &str = "test/files";
+const DEFAULT_JSON: &str = "target/bench/corpus.json";
+const DEFAULT_MIN_TIME_MS: u64 = 200;
+
+const BASELINE: &str = "flate2-deflate";
+
+struct Config {
+    json: PathBuf,
+    min_time: Duration,
+    filters: Vec<String>,
+}
+
+struct Measurement {
+    file: String,
+    algorithm: String,
+    original_size: usize,
+    compressed_size: usize,
+    compress_mbps: f64,
+    decompress_mbps: f64,
+    lossless: bool,
+}
+
+fn main() {
+    let config = parse_args();
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let files = corpus_files(&root.join(CORPUS_DIR));
+
+    let algorithms: Vec<&str> = Algorithm::ALL.map(Algorithm::name).into_iter().chain([BASELINE]).collect();
+
+    println!(
+        "{:<45} {:<15} {:>10} {:>10} {:>8} {:>10} {:>10}",
+        "file", "algorithm", "original", "compressed", "ratio", "comp MB/s", "dec MB/s"
+    );
+
+    let mut results = Vec::new();
+    for path in &files {
+        let name = path
+            .strip_prefix(root.join(CORPUS_DIR))
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let data = fs::read(path).expect("failed to read corpus file");
+
+        for &algorithm in &algorithms {
+            if !config.matches(&name, algorithm) {
+                continue;
+            }
+            let measurement = measure(&name, algorithm, &data, config.min_time);
+            println!(
+                "{:<45} {:<15} {:>10} {:>10} {:>7.2}% {:>10.2} {:>10.2}{}",
+                measurement.file,
+                measurement.algorithm,
+                measurement.original_size,
+                measurement.compressed_size,
+                ratio(&measurement) * 100.0,
+                measurement.compress_mbps,
+                measurement.decompress_mbps,
+                if measurement.lossless { "" } else { "  LOSSY" }
+            );
+            results.push(measurement);
+        }
+    }
+
+    if let Some(parent) = config.json.parent() {
+        fs::create_dir_all(parent).expect("failed to create JSON output directory");
+    }
+    fs::write(&config.json, to_json(&results, &config)).expect("failed to write JSON results");
+    println!(
+        "\nWrote {} results to {}",
+        results.len(),
+        config.json.display()
+    );
+
+    if results.iter().any(|m| !m.lossless) {
+        eprintln!("Some round trips were not lossless");
+        std::process::exit(1);
+    }
+}
+
+fn parse_args() -> Config {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut config = Config {
+        json: root.join(DEFAULT_JSON),
+        min_time: Duration::from_millis(DEFAULT_MIN_TIME_MS),
+        filters: Vec::new(),
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // Passed by `cargo bench` to every harness
+            "--bench" => {}
+            "--json" => config.json = PathBuf::from(args.next().expect("--json requires a path")),
+            "--min-time" => {
+                let ms = args
+                    .next()
+                    .and_then(|ms| ms.parse().ok())
+                    .expect("--min-time requires milliseconds");
+                config.min_time = Duration::from_millis(ms);
+            }
+            _ => config.filters.push(arg),
+        }
+    }
+    config
+}
+
+impl Config {
+    fn matches(&self, file: &str, algorithm: &str) -> bool {
+        self.filters.is_empty()
+            || self.filters.iter().any(|filter| {
+                file.contains(filter.as_str()) || algorithm.eq_ignore_ascii_case(filter)
+            })
+    }
+}
+
+fn corpus_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).expect("failed to read corpus directory") {
+            let path = entry.expect("failed to read corpus entry").path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+fn measure(file: &str, algorithm: &str, data: &[u8], min_time: Duration) -> Measurement {
+    let (compressed, compress_time) = time_repeated(min_time, || compress(algorithm, data));
+    let (decompressed, decompress_time) =
+        time_repeated(min_time, || decompress(algorithm, &compressed));
+
+    Measurement {
+        file: file.to_string(),
+        algorithm: algorithm.to_string(),
+        original_size: data.len(),
+        compressed_size: compressed.len(),
+        compress_mbps: throughput(data.len(), compress_time),
+        decompress_mbps: throughput(data.len(), decompress_time),
+        lossless: decompressed == data,
+    }
+}
+
+/// Runs `f` until `min_time` has elapsed (at least once) and returns its last
+/// result with the mean time per call.
+fn time_repeated(min_time: Duration, mut f: impl FnMut() -> Vec<u8>) -> (Vec<u8>, Duration) {
+    let start = Instant::now();
+    let mut runs = 0u32;
+    loop {
+        let output = f();
+        runs += 1;
+        let elapsed = start.elapsed();
+        if elapsed >= min_time {
+            return (output, elapsed / runs);
+        }
+    }
+}
+
+fn compress(algorithm: &str, data: &[u8]) -> Vec<u8> {
+    if algorithm == BASELINE {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).expect("deflate failed");
+        return encoder.finish().expect("deflate failed");
+    }
+    let options = CompressOptions {
+        algorithm: Algorithm::parse(algorithm).expect("algorithm names parse"),
+        ..CompressOptions::default()
+    };
+    compress_with_options(data, &options)
+}
+
+fn decompress(algorithm: &str, data: &[u8]) -> Vec<u8> {
+    if algorithm == BASELINE {
+        let mut output = Vec::new();
+        DeflateDecoder::new(data)
+            .read_to_end(&mut output)
+            .expect("inflate failed");
+        return output;
+    }
+    decompress_with_options(data, &DecompressOptions::default()).expect("decompress failed")
+}
+
+fn throughput(bytes: usize, time: Duration) -> f64 {
+    bytes as f64 / 1_000_000.0 / time.as_secs_f64().max(f64::EPSILON)
+}
+
+fn ratio(measurement: &Measurement) -> f64 {
+    if measurement.original_size == 0 {
+        return 1.0;
+    }
+    measurement.compressed_size as f64 / measurement.original_size as f64
+}
+
+fn git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn to_json(results: &[Measurement], config: &Config) -> String {
+    let mut json = String::from("{\n");
+    let commit = git_commit().map_or("null".to_string(), |commit| json_string(&commit));
+    writeln!(json, "  \"commit\": {},", commit).unwrap();
+    writeln!(json, "  \"min_time_ms\": {},", config.min_time.as_millis()).unwrap();
+    json.push_str("  \"results\": [\n");
+    for (index, m) in results.iter().enumerate() {
+        write!(
+            json,
+            "    {{\"file\": {}, \"algorithm\": {}, \"original_size\": {}, \"compressed_size\": {}, \
+             \"ratio\": {:.6}, \"compress_mbps\": {:.3}, \"decompress_mbps\": {:.3}, \"lossless\": {}}}",
+            json_string(&m.file),
+            json_string(&m.algorithm),
+            m.original_size,
+            m.compressed_size,
+            ratio(m),
+            m.compress_mbps,
+            m.decompress_mbps,
+            m.lossless
+        )
+        .unwrap();
+        json.push_str(if index + 1 < results.len() {
+            ",\n"
+        } else {
+            "\n"
+        });
+    }
+    json.push_str("  ]\n}\n");
+    json
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
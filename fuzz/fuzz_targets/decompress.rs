@@ -0,0 +1,9 @@
+#![no_main]
+
+// Any byte string must decompress without panicking; exceeding the output cap is an
+// error, not a crash.
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = goud_compressor::fuzzing::decompress(data);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+// compress -> decompress must reproduce the input for every algorithm.
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    goud_compressor::fuzzing::round_trip(data);
+});
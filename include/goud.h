@@ -0,0 +1,141 @@
+#ifndef GOUD_H
+#define GOUD_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// A required pointer argument was NULL.
+#define GOUD_ERROR_NULL_POINTER -1
+
+// A `GoudOptions` field is out of range.
+#define GOUD_ERROR_INVALID_OPTIONS -2
+
+// `out_cap` is smaller than the compressed size; see `goud_compress_bound`.
+#define GOUD_ERROR_BUFFER_TOO_SMALL -3
+
+// Decompressed output would exceed `max_output_size`.
+#define GOUD_ERROR_OUTPUT_TOO_LARGE -4
+
+// Decompressed output would exceed `max_ratio` times the input size.
+#define GOUD_ERROR_RATIO_EXCEEDED -5
+
+// The operation was cancelled.
+#define GOUD_ERROR_CANCELLED -6
+
+// A bug in the library; the message has details.
+#define GOUD_ERROR_INTERNAL -7
+
+// Values for `GoudOptions.algorithm`, matching the `Algorithm` discriminants.
+#define GOUD_ALGORITHM_BEST 0
+
+#define GOUD_ALGORITHM_LZ_HUFFMAN 1
+
+#define GOUD_ALGORITHM_RLE 2
+
+#define GOUD_ALGORITHM_DELTA 3
+
+#define GOUD_ALGORITHM_BWT 4
+
+#define GOUD_ALGORITHM_NUMPACK 5
+
+#define GOUD_ALGORITHM_RECORD 6
+
+#define GOUD_ALGORITHM_LZ4 7
+
+#define GOUD_ALGORITHM_SNAPPY 8
+
+#define GOUD_ALGORITHM_FSE 9
+
+#define GOUD_ALGORITHM_CM 10
+
+// Values for `GoudOptions.chunk_mode`.
+#define GOUD_CHUNK_MODE_INDEPENDENT 0
+
+#define GOUD_CHUNK_MODE_DEPENDENT 1
+
+// Settings for `goud_compress` and `goud_decompress`. Set `struct_size` to
+// `sizeof(GoudOptions)` and zero the other fields for their defaults, or
+// pass a NULL pointer; each function ignores the fields meant for the other.
+typedef struct GoudOptions {
+  // `sizeof(GoudOptions)` in the caller's header. Fields are only ever
+  // appended, so the library reads this many bytes and gives any fields
+  // beyond them their defaults, and callers built against an older header
+  // keep working.
+  uint32_t struct_size;
+  // One of GOUD_ALGORITHM_*.
+  uint32_t algorithm;
+  // 1 (fastest) to 9 (smallest); 0 for the default.
+  uint32_t level;
+  // Bytes per chunk for GOUD_ALGORITHM_BEST; 0 for the default.
+  uint32_t chunk_size;
+  // Record width for GOUD_ALGORITHM_RECORD, at most 65535; 0 to detect it.
+  uint32_t record_width;
+  // Largest decompressed size accepted, in bytes; 0 for the default (256 MiB).
+  uint64_t max_output_size;
+  // Largest accepted ratio of decompressed to compressed size; 0 for unlimited.
+  double max_ratio;
+  // Nonzero to let LZ copy from the built-in dictionary of web and JSON
+  // tokens.
+  uint32_t dictionary;
+  // Nonzero to replace far repeats across the whole input before
+  // chunking, for GOUD_ALGORITHM_BEST and GOUD_ALGORITHM_CM.
+  uint32_t long_range;
+  // One of GOUD_CHUNK_MODE_*.
+  uint32_t chunk_mode;
+} GoudOptions;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Largest output `goud_compress` can produce for `len` input bytes. LZ
+// tokens, BWT runs and NumPack numbers cost at most twice their input, plus
+// a few header bytes; an LZ4 frame adds 23 bytes plus 4 per 4 MiB block
+// to its input, and a Snappy stream 10 bytes plus 8 per 64 KiB.
+size_t goud_compress_bound(size_t len);
+
+// Compresses `len` bytes at `src` into `out`, returning the number of bytes
+// written or a negative GOUD_ERROR_* code.
+//
+// # Safety
+//
+// `src` must be valid for reading `len` bytes (it may be NULL if `len` is
+// 0), `out` valid for writing `out_cap` bytes, and `opts` NULL or valid.
+int64_t goud_compress(const uint8_t *src,
+                      size_t len,
+                      const struct GoudOptions *opts,
+                      uint8_t *out,
+                      size_t out_cap);
+
+// Decompresses `len` bytes at `src` into a new buffer stored in `*out`,
+// returning its length or a negative GOUD_ERROR_* code. Release the buffer
+// with `goud_free(*out, length)`. On error `*out` is set to NULL.
+//
+// # Safety
+//
+// `src` must be valid for reading `len` bytes (it may be NULL if `len` is
+// 0), `out` valid for writing one pointer, and `opts` NULL or valid.
+int64_t goud_decompress(const uint8_t *src,
+                        size_t len,
+                        const struct GoudOptions *opts,
+                        uint8_t **out);
+
+// Frees a buffer returned by `goud_decompress`. NULL is ignored.
+//
+// # Safety
+//
+// `ptr` must be NULL or a buffer from `goud_decompress` with its returned
+// length `len`, not already freed.
+void goud_free(uint8_t *ptr, size_t len);
+
+// Message for the last error on this thread, or NULL if the last call
+// succeeded. Valid until the next goud_* call on the same thread.
+const char *goud_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GOUD_H */
@@ -0,0 +1,391 @@
+// Command-line front end for compressing and decompressing files natively,
+// without Node or wasm-pack. Usage mirrors gzip: files are replaced by
+// `<name>.goud` unless -k or -c is given, and `-` or no file means stdin.
+
+use goud_compressor::{
+    compress_with_report, decompress_with_options, inspect, method_name, stream_name, Algorithm,
+    ChunkMode, CompressOptions, CompressionReport, DecompressOptions, LogLevel, StreamInfo, MAX_LEVEL,
+    MIN_LEVEL,
+};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+const SUFFIX: &str = ".goud";
+// Short flags without a value, which may be combined as in `-dk`
+const BUNDLED_FLAGS: &str = "dckftvh123456789";
+// Short options with a value, which may be attached as in `-l9`
+const VALUE_FLAGS: [&str; 2] = ["-a", "-l"];
+
+const USAGE: &str = "\
+Usage: goud [OPTIONS] [FILE]...
+
+Compresses each FILE to FILE.goud, or decompresses FILE.goud back to FILE.
+With no FILE, or when FILE is -, reads standard input and writes standard output.
+
+Options take values as -l 9, -l9, --level 9 or --level=9.
+
+Options:
+  -d, --decompress        Decompress instead of compressing
+  -c, --stdout            Write to standard output and keep input files
+  -k, --keep              Keep input files
+  -f, --force             Overwrite existing output files
+  -t, --test              Check integrity; with -d only decodes, otherwise also
+                          verifies that the compressed output round-trips
+  -a, --algorithm NAME    best (default), LZ+Huffman (lz), RLE, Delta, bwt, NumPack,
+                          Record, LZ4, Snappy, FSE, CM
+  -l, --level N           Effort from 1 (fastest) to 9 (smallest), default 6
+  -1 .. -9                Same as --level N
+      --chunk-size SIZE   Bytes per chunk for best, e.g. 65536, 64K or 1M
+      --chunk-mode MODE   independent (default), or dependent to let LZ copy
+                          from the end of the previous chunk
+      --record-width N    Record width for the Record transform
+      --dictionary        Let LZ copy from the built-in web/JSON dictionary
+      --long-range        Replace repeats of 64+ bytes anywhere earlier in the
+                          input before chunking (best and CM)
+      --max-output-size SIZE
+                          Refuse to decompress more than SIZE bytes (default 256M)
+      --max-ratio N       Refuse output larger than N times the compressed input
+      --log LEVEL         none, error, info, debug or performance
+  -v, --verbose           Include performance log messages
+      --stats             Print sizes, ratio and per-chunk methods to stderr
+  -h, --help              Print this help";
+
+#[derive(Debug, Default)]
+struct Args {
+    decompress: bool,
+    stdout: bool,
+    keep: bool,
+    force: bool,
+    test: bool,
+    stats: bool,
+    compress: CompressOptions,
+    max_output_size: Option<usize>,
+    max_ratio: Option<f64>,
+    files: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(expand_short_flags(std::env::args().skip(1))) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            let _ = writeln!(io::stdout(), "{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("goud: {}", message);
+            eprintln!("Try 'goud --help' for more information.");
+            return ExitCode::from(2);
+        }
+    };
+
+    let files = if args.files.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        args.files.clone()
+    };
+
+    let mut status = ExitCode::SUCCESS;
+    for file in &files {
+        if let Err(message) = process(&args, file) {
+            eprintln!("goud: {}: {}", display_name(file), message);
+            status = ExitCode::FAILURE;
+        }
+    }
+    status
+}
+
+fn parse_args(argv: Vec<String>) -> Result<Option<Args>, String> {
+    let mut args = Args::default();
+    let mut argv = argv.into_iter();
+    let mut only_files = false;
+
+    while let Some(arg) = argv.next() {
+        if only_files || arg == "-" || !arg.starts_with('-') {
+            args.files.push(arg);
+            continue;
+        }
+
+        // Accept `--name value`, `--name=value`, `-n value` and `-nvalue`
+        let short = arg
+            .get(..2)
+            .filter(|short| arg.len() > 2 && VALUE_FLAGS.contains(short));
+        let (name, inline_value) = match (arg.split_once('='), short) {
+            (Some((name, value)), _) if arg.starts_with("--") => {
+                (name.to_string(), Some(value.to_string()))
+            }
+            (_, Some(short)) => (short.to_string(), Some(arg[2..].to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| -> Result<String, String> {
+            inline_value
+                .clone()
+                .or_else(|| argv.next())
+                .ok_or_else(|| format!("option '{}' requires a value", name))
+        };
+
+        match name.as_str() {
+            "--" => only_files = true,
+            "-h" | "--help" => return Ok(None),
+            "-d" | "--decompress" => args.decompress = true,
+            "-c" | "--stdout" => args.stdout = true,
+            "-k" | "--keep" => args.keep = true,
+            "-f" | "--force" => args.force = true,
+            "-t" | "--test" => args.test = true,
+            "-v" | "--verbose" => args.compress.verbose = true,
+            "--stats" => args.stats = true,
+            "-a" | "--algorithm" => args.compress.algorithm = parse_algorithm(&value(&name)?)?,
+            "-l" | "--level" => args.compress.level = parse_level(&value(&name)?)?,
+            "--chunk-size" => args.compress.chunk_size = parse_size(&value(&name)?)?,
+            "--chunk-mode" => {
+                let mode = value(&name)?;
+                args.compress.chunk_mode = ChunkMode::parse(&mode).ok_or_else(|| {
+                    format!("unknown chunk mode '{}' (expected independent or dependent)", mode)
+                })?;
+            }
+            "--record-width" => {
+                let width = parse_size(&value(&name)?)?;
+                if width > u16::MAX as usize {
+                    return Err(format!("record width {} exceeds {}", width, u16::MAX));
+                }
+                args.compress.record_width = Some(width);
+            }
+            "--dictionary" => args.compress.dictionary = true,
+            "--long-range" => args.compress.long_range = true,
+            "--max-output-size" => args.max_output_size = Some(parse_size(&value(&name)?)?),
+            "--max-ratio" => {
+                let ratio = value(&name)?;
+                let parsed = ratio
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|r| r.is_finite() && *r > 0.0);
+                args.max_ratio = Some(parsed.ok_or_else(|| format!("invalid ratio '{}'", ratio))?);
+            }
+            "--log" => {
+                let level = value(&name)?;
+                args.compress.log_level =
+                    LogLevel::parse(&level).ok_or_else(|| format!("unknown log level '{}'", level))?;
+            }
+            _ if name.len() == 2 && name.as_bytes()[1].is_ascii_digit() => {
+                args.compress.level = parse_level(&name[1..])?;
+            }
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    Ok(Some(args))
+}
+
+/// Splits bundled short flags such as `-dk` or `-9c` into separate arguments.
+fn expand_short_flags(argv: impl Iterator<Item = String>) -> Vec<String> {
+    let mut expanded = Vec::new();
+    let mut only_files = false;
+    for arg in argv {
+        let bundled = !only_files
+            && arg.len() > 2
+            && arg.starts_with('-')
+            && !arg.starts_with("--")
+            && arg[1..].chars().all(|c| BUNDLED_FLAGS.contains(c));
+        if bundled {
+            expanded.extend(arg[1..].chars().map(|c| format!("-{}", c)));
+        } else {
+            only_files |= arg == "--";
+            expanded.push(arg);
+        }
+    }
+    expanded
+}
+
+fn parse_algorithm(value: &str) -> Result<Algorithm, String> {
+    Algorithm::parse(value).ok_or_else(|| {
+        format!(
+            "unknown algorithm '{}' (expected one of {})",
+            value,
+            Algorithm::ALL.map(Algorithm::name).join(", ")
+        )
+    })
+}
+
+fn parse_level(value: &str) -> Result<u8, String> {
+    value
+        .parse::<u8>()
+        .ok()
+        .filter(|level| (MIN_LEVEL..=MAX_LEVEL).contains(level))
+        .ok_or_else(|| {
+            format!(
+                "level must be between {} and {}, got '{}'",
+                MIN_LEVEL, MAX_LEVEL, value
+            )
+        })
+}
+
+/// Parses a positive byte count with an optional K or M suffix.
+fn parse_size(value: &str) -> Result<usize, String> {
+    let (digits, multiplier) = match value.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&value[..value.len() - 1], 1024),
+        Some('M') => (&value[..value.len() - 1], 1024 * 1024),
+        _ => (value, 1),
+    };
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|size| size.checked_mul(multiplier))
+        .filter(|&size| size > 0 && size <= u32::MAX as usize)
+        .ok_or_else(|| format!("invalid size '{}'", value))
+}
+
+fn process(args: &Args, file: &str) -> Result<(), String> {
+    let from_stdin = file == "-";
+    let input = if from_stdin {
+        let mut buffer = Vec::new();
+        io::stdin()
+            .read_to_end(&mut buffer)
+            .map_err(|err| err.to_string())?;
+        buffer
+    } else {
+        fs::read(file).map_err(|err| err.to_string())?
+    };
+
+    let defaults = DecompressOptions::default();
+    let decompress_options = DecompressOptions {
+        log_level: args.compress.log_level,
+        verbose: args.compress.verbose,
+        max_output_size: args.max_output_size.unwrap_or(defaults.max_output_size),
+        max_ratio: args.max_ratio,
+    };
+
+    let mut report = None;
+    let (compressed, output) = if args.decompress {
+        let info = inspect(&input).ok_or("not a valid goud stream")?;
+        let output =
+            decompress_with_options(&input, &decompress_options).map_err(|err| err.to_string())?;
+        if let Some(expected) = info.original_size {
+            if output.len() != expected {
+                return Err(format!(
+                    "corrupt stream: decoded {} bytes, header records {}",
+                    output.len(),
+                    expected
+                ));
+            }
+        }
+        (input.clone(), output)
+    } else {
+        let (output, compress_report) = compress_with_report(&input, &args.compress);
+        report = Some(compress_report);
+        if args.test {
+            let decoded = decompress_with_options(&output, &decompress_options)
+                .map_err(|err| err.to_string())?;
+            if decoded != input {
+                return Err("round trip check failed".to_string());
+            }
+        }
+        (output.clone(), output)
+    };
+
+    if args.stats {
+        let original = if args.decompress {
+            output.len()
+        } else {
+            input.len()
+        };
+        print_stats(file, original, &compressed, report.as_ref());
+    }
+
+    if args.test {
+        if !args.stats {
+            eprintln!("{}: OK", display_name(file));
+        }
+        return Ok(());
+    }
+
+    if from_stdin || args.stdout {
+        return io::stdout()
+            .lock()
+            .write_all(&output)
+            .map_err(|err| err.to_string());
+    }
+
+    let target = output_path(file, args.decompress)?;
+    if !args.force && fs::metadata(&target).is_ok() {
+        return Err(format!("{} already exists (use -f to overwrite)", target));
+    }
+    fs::write(&target, &output).map_err(|err| format!("{}: {}", target, err))?;
+    if !args.keep {
+        fs::remove_file(file).map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+fn output_path(file: &str, decompress: bool) -> Result<String, String> {
+    if !decompress {
+        if file.ends_with(SUFFIX) {
+            return Err(format!("already has {} suffix", SUFFIX));
+        }
+        return Ok(format!("{}{}", file, SUFFIX));
+    }
+    file.strip_suffix(SUFFIX)
+        .filter(|stem| !stem.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| format!("unknown suffix, expected {}", SUFFIX))
+}
+
+/// Prints sizes and per-chunk chains; when compressing, `report` adds each
+/// chunk's entropy and time.
+fn print_stats(file: &str, original: usize, compressed: &[u8], report: Option<&CompressionReport>) {
+    let ratio = if original == 0 {
+        0.0
+    } else {
+        compressed.len() as f64 / original as f64 * 100.0
+    };
+    eprintln!(
+        "{}: {} -> {} bytes ({:.2}%)",
+        display_name(file),
+        original,
+        compressed.len(),
+        ratio
+    );
+
+    let Some(StreamInfo { flag, chunks, .. }) = inspect(compressed) else {
+        return;
+    };
+    let method = stream_name(compressed).map_or_else(|| describe(flag), str::to_string);
+    eprintln!("  method: {}", method);
+    for (index, chunk) in chunks.iter().enumerate() {
+        let methods: Vec<String> = chunk.methods.iter().map(|&m| describe(m)).collect();
+        let mut chain = if methods.is_empty() {
+            "stored".to_string()
+        } else {
+            methods.join(" -> ")
+        };
+        if chunk.depends_on_previous {
+            chain.push_str(" (after the previous chunk)");
+        }
+        let details =
+            report
+                .and_then(|report| report.chunks.get(index))
+                .map_or(String::new(), |chunk| {
+                    format!(
+                        ", entropy {:.2} bits/byte, {:.2} ms",
+                        chunk.entropy, chunk.time_ms
+                    )
+                });
+        eprintln!(
+            "  chunk {}: {} bytes, {}{}",
+            index, chunk.compressed_size, chain, details
+        );
+    }
+}
+
+fn describe(flag: u8) -> String {
+    method_name(flag)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("unknown ({:02X})", flag))
+}
+
+fn display_name(file: &str) -> &str {
+    if file == "-" {
+        "(stdin)"
+    } else {
+        file
+    }
+}
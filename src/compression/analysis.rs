@@ -0,0 +1,68 @@
+// Lightweight statistics used by strategies to pick their parameters.
+
+use alloc::vec::Vec;
+
+/// Zero-order Shannon entropy of `data`, in bits per byte.
+pub fn shannon_entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let mut freqs = [0u32; 256];
+    for &byte in data {
+        freqs[byte as usize] += 1;
+    }
+
+    let mut entropy = 0.0;
+    let len = data.len() as f64;
+    for &freq in freqs.iter() {
+        if freq > 0 {
+            let p = freq as f64 / len;
+            entropy -= p * log2(p);
+        }
+    }
+    entropy
+}
+
+#[cfg(feature = "std")]
+fn log2(x: f64) -> f64 {
+    x.log2()
+}
+
+// `core` has no float math; libm supplies it without std
+#[cfg(not(feature = "std"))]
+fn log2(x: f64) -> f64 {
+    libm::log2(x)
+}
+
+/// Finds the record width in `min_width..=max_width` at which bytes of the
+/// sample most often repeat, i.e. the peak of the byte autocorrelation.
+/// Returns `None` if no width shows a meaningful correlation.
+pub fn detect_record_width(sample: &[u8], min_width: usize, max_width: usize) -> Option<usize> {
+    let max_width = max_width.min(sample.len() / 4);
+    if min_width == 0 || min_width > max_width {
+        return None;
+    }
+
+    let scores: Vec<(usize, f64)> = (min_width..=max_width)
+        .map(|width| {
+            let matches = sample[width..]
+                .iter()
+                .zip(sample.iter())
+                .filter(|(a, b)| a == b)
+                .count();
+            (width, matches as f64 / (sample.len() - width) as f64)
+        })
+        .collect();
+
+    let best = scores.iter().map(|&(_, score)| score).fold(0.0, f64::max);
+    if best < 0.25 {
+        return None;
+    }
+
+    // Multiples of the true width correlate just as well; prefer the smallest.
+    scores
+        .iter()
+        .find(|&&(_, score)| score >= best * 0.95)
+        .map(|&(width, _)| width)
+}
@@ -0,0 +1,163 @@
+use crate::compression::matcher::{Candidate, MatchFinder};
+use crate::constants::{MAX_MATCH_LEN, MIN_MATCH_LEN, WINDOW_SIZE};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+
+// Binary-tree match finder, as LZMA's BT4. Positions whose next 4 bytes hash
+// alike form a binary search tree, ordered by the bytes from each position
+// on, with newer positions nearer the root. A new position becomes the root:
+// the walk down from the old root splits the tree into the positions that
+// sort before it and those after, and passes every position sharing a
+// longer prefix with it than any seen so far. So one walk both indexes the
+// position and yields its matches in increasing length, and the longest
+// match is found without trying every candidate as hash chains do.
+//
+// A 4-byte hash can't find 3-byte matches; the newest position with the same
+// 3-byte hash supplies those. Each position's two child links sit in a
+// cyclic buffer that holds the window; a link to a position that has left
+// the window ends the walk, as everything below it is older still.
+
+// Twice the window, so no position in the window shares a slot
+const MAX_CYCLIC_SIZE: usize = 2 * WINDOW_SIZE;
+const MIN_HASH_LOG: u32 = 10;
+const MAX_HASH_LOG: u32 = 16;
+const HASH_PRIME: u32 = 0x9E37_79B1;
+const NONE: u32 = u32::MAX;
+
+pub struct BinaryTreeFinder {
+    hash_log: u32,
+    /// Positions the child links have room for, a power of two.
+    cyclic_size: usize,
+    /// Most nodes visited per position.
+    depth: usize,
+    /// Newest position per 3-byte hash.
+    head3: Vec<u32>,
+    /// Root of each 4-byte hash's tree.
+    head4: Vec<u32>,
+    /// The children of position p, sorting before and after it, at
+    /// 2 * (p % cyclic_size) and the slot after.
+    children: Vec<u32>,
+}
+
+impl BinaryTreeFinder {
+    /// A finder for `len` bytes, with tables sized to match, since short
+    /// chunks are searched many times over.
+    pub fn new(len: usize, depth: usize) -> Self {
+        let cyclic_size = len.next_power_of_two().min(MAX_CYCLIC_SIZE);
+        let hash_log = len
+            .next_power_of_two()
+            .trailing_zeros()
+            .clamp(MIN_HASH_LOG, MAX_HASH_LOG);
+        BinaryTreeFinder {
+            hash_log,
+            cyclic_size,
+            depth,
+            head3: vec![NONE; 1 << hash_log],
+            head4: vec![NONE; 1 << hash_log],
+            children: vec![NONE; 2 * cyclic_size],
+        }
+    }
+
+    fn hash(&self, key: u32) -> usize {
+        (key.wrapping_mul(HASH_PRIME) >> (32 - self.hash_log)) as usize
+    }
+
+    /// Inserts `pos` as the root of its tree, appending the matches passed
+    /// on the way to `out` if given.
+    fn insert(&mut self, data: &[u8], pos: usize, mut out: Option<&mut Vec<Candidate>>) {
+        let max_len = MAX_MATCH_LEN.min(data.len() - pos);
+        if max_len < MIN_MATCH_LEN {
+            return;
+        }
+        let mut best_len = MIN_MATCH_LEN - 1;
+
+        let key3 = u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], 0]);
+        let slot = self.hash(key3);
+        let newest = mem::replace(&mut self.head3[slot], pos as u32);
+        if let Some(out) = out.as_deref_mut() {
+            if newest != NONE && pos - newest as usize <= WINDOW_SIZE {
+                let length = common_len(data, newest as usize, pos, 0, max_len);
+                if length > best_len {
+                    best_len = length;
+                    out.push(Candidate {
+                        offset: pos - newest as usize,
+                        length,
+                    });
+                }
+            }
+        }
+        if max_len < 4 {
+            return;
+        }
+
+        let key4 = u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]);
+        let slot = self.hash(key4);
+        let mut current = mem::replace(&mut self.head4[slot], pos as u32);
+        // Where the next nodes sorting before and after `pos` get linked,
+        // and how many bytes every node below each link shares with it
+        let node = 2 * (pos & (self.cyclic_size - 1));
+        let (mut before, mut after) = (node, node + 1);
+        let (mut before_len, mut after_len) = (0, 0);
+        let mut depth = self.depth;
+        loop {
+            if current == NONE || pos - current as usize > WINDOW_SIZE || depth == 0 {
+                self.children[before] = NONE;
+                self.children[after] = NONE;
+                return;
+            }
+            depth -= 1;
+            let start = current as usize;
+            let pair = 2 * (start & (self.cyclic_size - 1));
+            let length = common_len(data, start, pos, before_len.min(after_len), max_len);
+            if length > best_len {
+                best_len = length;
+                if let Some(out) = out.as_deref_mut() {
+                    out.push(Candidate {
+                        offset: pos - start,
+                        length,
+                    });
+                }
+            }
+            if length == max_len {
+                // As far as can be told the two sort alike; `pos` takes the
+                // older position's place in the tree
+                self.children[before] = self.children[pair];
+                self.children[after] = self.children[pair + 1];
+                return;
+            }
+            if data[start + length] < data[pos + length] {
+                self.children[before] = current;
+                before = pair + 1;
+                before_len = length;
+                current = self.children[before];
+            } else {
+                self.children[after] = current;
+                after = pair;
+                after_len = length;
+                current = self.children[after];
+            }
+        }
+    }
+}
+
+impl MatchFinder for BinaryTreeFinder {
+    fn find(&mut self, data: &[u8], pos: usize, out: &mut Vec<Candidate>) {
+        self.insert(data, pos, Some(out));
+    }
+
+    fn skip(&mut self, data: &[u8], pos: usize) {
+        self.insert(data, pos, None);
+    }
+}
+
+/// How many bytes from `start` and `pos` agree, up to `max_len`, given that
+/// the first `known` do.
+fn common_len(data: &[u8], start: usize, pos: usize, known: usize, max_len: usize) -> usize {
+    known
+        + data[start + known..]
+            .iter()
+            .zip(&data[pos + known..pos + max_len])
+            .take_while(|(a, b)| a == b)
+            .count()
+}
@@ -0,0 +1,173 @@
+use crate::compression::levels::level_params;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+// Match finder for the byte-oriented LZ formats (LZ4, Snappy), which code
+// literals and matches directly instead of through Huffman tokens.
+//
+// Matches are found as in `matcher`, with hash chains over 4-byte prefixes,
+// but the chains live in tables sized to the block, at most the 64 KiB
+// window, and the level only sets how many candidates are tried.
+
+pub const MIN_MATCH: usize = 4;
+pub const MAX_OFFSET: usize = 65535;
+
+const MIN_HASH_LOG: u32 = 10; // Tables shrink for small inputs, which are often hot paths
+const MAX_HASH_LOG: u32 = 16;
+
+// Candidates tried per position, indexed by level - 1. Level 1 and 2 also
+// skip ahead faster through data that doesn't match.
+const CHAIN_DEPTH: [usize; 9] = [1, 1, 2, 2, 4, 4, 16, 64, 256];
+
+/// A match and the literals between it and the previous one.
+pub struct Sequence {
+    pub literals: Range<usize>,
+    pub offset: usize,
+    pub length: usize,
+}
+
+pub struct BlockMatcher {
+    depth: usize,
+    lazy: bool,
+    hash_log: u32,
+    /// Latest position + 1 for each hash, 0 for none.
+    head: Vec<u32>,
+    /// Distance to the previous position with the same hash, indexed by
+    /// position modulo the table size; 0 ends the chain.
+    chain: Vec<u16>,
+}
+
+impl BlockMatcher {
+    /// A matcher for blocks of up to `block_size` bytes at `level`.
+    pub fn new(block_size: usize, level: u8) -> Self {
+        let level = level.clamp(1, CHAIN_DEPTH.len() as u8);
+        let hash_log = block_size
+            .next_power_of_two()
+            .trailing_zeros()
+            .clamp(MIN_HASH_LOG, MAX_HASH_LOG);
+        BlockMatcher {
+            depth: CHAIN_DEPTH[level as usize - 1],
+            lazy: level_params(level).lazy > 0,
+            hash_log,
+            head: vec![0; 1 << hash_log],
+            chain: vec![0; 1 << hash_log],
+        }
+    }
+
+    /// Calls `emit` for each match in `src` that starts by `last_start` and
+    /// ends by `match_end`, returning where the trailing literals begin.
+    /// `last_start + MIN_MATCH` must not exceed `src.len()`.
+    pub fn parse(
+        &mut self,
+        src: &[u8],
+        last_start: usize,
+        match_end: usize,
+        mut emit: impl FnMut(Sequence),
+    ) -> usize {
+        self.head.fill(0);
+        let mut anchor = 0;
+        let mut pos = 0;
+
+        while pos <= last_start {
+            let (mut offset, mut length) = self.find_match(src, pos, match_end);
+            self.insert(src, pos);
+
+            if length < MIN_MATCH {
+                // Without chains, skip faster the longer nothing has matched
+                pos += if self.depth == 1 {
+                    1 + ((pos - anchor) >> 6)
+                } else {
+                    1
+                };
+                continue;
+            }
+
+            // Lazy matching: if the next position has a longer match, emit a literal instead
+            while self.lazy && pos < last_start {
+                let (next_offset, next_length) = self.find_match(src, pos + 1, match_end);
+                if next_length <= length {
+                    break;
+                }
+                pos += 1;
+                self.insert(src, pos);
+                (offset, length) = (next_offset, next_length);
+            }
+
+            emit(Sequence {
+                literals: anchor..pos,
+                offset,
+                length,
+            });
+            for inside in pos + 1..(pos + length).min(last_start) {
+                self.insert(src, inside);
+            }
+            pos += length;
+            anchor = pos;
+        }
+        anchor
+    }
+
+    fn find_match(&self, src: &[u8], pos: usize, match_end: usize) -> (usize, usize) {
+        let mut best = (0, 0);
+        let mut candidate = self.head[self.hash(src, pos)] as usize;
+        for _ in 0..self.depth {
+            let Some(start) = candidate.checked_sub(1) else {
+                break;
+            };
+            if pos - start > MAX_OFFSET.min(self.chain.len() - 1) {
+                break;
+            }
+            let length = common_length(src, start, pos, match_end);
+            if length > best.1 {
+                best = (pos - start, length);
+                if pos + length == match_end {
+                    break;
+                }
+            }
+            let step = self.chain[start & (self.chain.len() - 1)] as usize;
+            if step == 0 {
+                break;
+            }
+            candidate = start + 1 - step;
+        }
+        best
+    }
+
+    #[inline]
+    fn hash(&self, src: &[u8], pos: usize) -> usize {
+        let word = u32::from_le_bytes(src[pos..pos + 4].try_into().unwrap());
+        (word.wrapping_mul(2_654_435_761) >> (32 - self.hash_log)) as usize
+    }
+
+    fn insert(&mut self, src: &[u8], pos: usize) {
+        let mask = self.chain.len() - 1;
+        let hash = self.hash(src, pos);
+        let slot = &mut self.head[hash];
+        let distance = match *slot as usize {
+            0 => 0,
+            previous => pos + 1 - previous,
+        };
+        self.chain[pos & mask] = if distance > mask { 0 } else { distance as u16 };
+        *slot = pos as u32 + 1;
+    }
+}
+
+/// Length of the match between `start` and `pos`, ending by `end`.
+#[inline]
+fn common_length(src: &[u8], start: usize, pos: usize, end: usize) -> usize {
+    let mut length = 0;
+    while pos + length + 8 <= end {
+        let a = u64::from_le_bytes(src[start + length..start + length + 8].try_into().unwrap());
+        let b = u64::from_le_bytes(src[pos + length..pos + length + 8].try_into().unwrap());
+        let diff = a ^ b;
+        if diff != 0 {
+            return length + (diff.trailing_zeros() / 8) as usize;
+        }
+        length += 8;
+    }
+    while pos + length < end && src[start + length] == src[pos + length] {
+        length += 1;
+    }
+    length
+}
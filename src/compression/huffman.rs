@@ -1,69 +1,117 @@
-use crate::shared::token::Token;
-use std::collections::HashMap;
+use crate::constants::MIN_MATCH_LEN;
+use crate::shared::bits::BitWriter;
+use crate::shared::huffman::{
+    dictionary_bucket, length_bucket, offset_bucket, Bucket, END_OF_STREAM, LITERAL_LENGTH_SYMBOLS,
+    MAX_CODE_LEN, OFFSET_SYMBOLS,
+};
+use crate::shared::token::{RecentOffsets, Token};
+use alloc::vec;
+use alloc::vec::Vec;
 
+// Huffman coding of LZ tokens over the alphabets in `shared::huffman`.
+//
+// Format: [literal/length code lengths][offset code lengths][tokens...]
+// as a bit stream read LSB first. Each code length takes 4 bits; a zero is
+// followed by the gamma code of 1 + how many more symbols are unused. Codes
+// are canonical, so the lengths are all the decoder needs, and each is
+// written from its highest bit down. Every token is its literal/length
+// symbol; a match follows it with the length's extra bits, then its offset
+// symbol and the offset's extra bits. A window match whose offset is one of
+// the `RecentOffsets` takes that entry's symbol instead of its bucket.
+// END_OF_STREAM closes the stream.
+
+/// Canonical Huffman code of each symbol in an alphabet. Unused symbols have
+/// length 0.
 #[derive(Debug)]
-pub struct HuffmanTree {
-    pub root: HuffmanNode,
+pub struct CodeTable {
+    pub lengths: Vec<u8>,
+    /// Codes bit-reversed, ready to write LSB first.
+    codes: Vec<u32>,
 }
 
-#[derive(Debug, Clone)]
-pub enum HuffmanNode {
-    Leaf(Token),
-    Internal(Box<HuffmanNode>, Box<HuffmanNode>),
+/// The two code tables of a token stream.
+#[derive(Debug)]
+pub struct HuffmanCodes {
+    pub literal_lengths: CodeTable,
+    pub offsets: CodeTable,
 }
 
-impl HuffmanTree {
-    pub fn serialize(&self) -> Vec<u8> {
-        vec![0xFF]  // Placeholder implementation
+/// Buckets of a match's length and offset, or `None` for a literal.
+fn match_buckets(token: &Token, recent: &mut RecentOffsets) -> Option<(Bucket, Bucket)> {
+    match *token {
+        Token::Literal(_) => None,
+        Token::Match(offset, length) => {
+            let offset_bucket = match recent.position(offset) {
+                Some(index) => Bucket {
+                    symbol: index,
+                    extra_bits: 0,
+                    extra: 0,
+                },
+                None => offset_bucket(offset as u32),
+            };
+            recent.push(offset);
+            Some((length_bucket(length as u32 - MIN_MATCH_LEN as u32), offset_bucket))
+        }
+        Token::DictMatch(position, length) => Some((
+            length_bucket(length as u32 - MIN_MATCH_LEN as u32),
+            dictionary_bucket(position as u32),
+        )),
     }
 }
 
-// Build frequencies of each token and create a tree
-pub fn build_huffman_tree(tokens: &[Token]) -> HuffmanTree {
-    if tokens.is_empty() {
-        // Edge case: no tokens
-        return HuffmanTree {
-            root: HuffmanNode::Leaf(Token::Literal(0)),
-        };
-    }
-
-    // Count frequencies
-    let mut freq_map = HashMap::new();
+/// Counts how often each symbol of both alphabets occurs in `tokens` and
+/// builds a code for each.
+pub fn build_huffman_codes(tokens: &[Token]) -> HuffmanCodes {
+    let mut literal_lengths = vec![0u32; LITERAL_LENGTH_SYMBOLS];
+    let mut offsets = vec![0u32; OFFSET_SYMBOLS];
+    literal_lengths[END_OF_STREAM] = 1;
+    let mut recent = RecentOffsets::default();
     for token in tokens {
-        *freq_map.entry(token.clone()).or_insert(0) += 1;
+        match (token, match_buckets(token, &mut recent)) {
+            (_, Some((length, offset))) => {
+                literal_lengths[length.symbol] += 1;
+                offsets[offset.symbol] += 1;
+            }
+            (&Token::Literal(byte), None) => literal_lengths[byte as usize] += 1,
+            _ => unreachable!(),
+        }
     }
-
-    // Build a simple priority queue
-    let mut pq: Vec<(usize, HuffmanNode)> = freq_map
-        .into_iter()
-        .map(|(token, freq)| (freq, HuffmanNode::Leaf(token)))
-        .collect();
-    pq.sort_by_key(|(freq, _)| *freq);
-
-    // Merge nodes until single tree
-    while pq.len() > 1 {
-        let (freq1, node1) = pq.remove(0);
-        let (freq2, node2) = pq.remove(0);
-        let new_node = HuffmanNode::Internal(Box::new(node1), Box::new(node2));
-        let new_freq = freq1 + freq2;
-
-        // Insert back, keeping sorted
-        let idx = pq
-            .binary_search_by_key(&new_freq, |(f, _)| *f)
-            .unwrap_or_else(|e| e);
-        pq.insert(idx, (new_freq, new_node));
+    HuffmanCodes {
+        literal_lengths: CodeTable::new(code_lengths(&literal_lengths)),
+        offsets: CodeTable::new(code_lengths(&offsets)),
     }
+}
 
-    HuffmanTree {
-        root: pq.remove(0).1,
+pub fn encode_huffman(tokens: &[Token], codes: &HuffmanCodes) -> Vec<u8> {
+    let mut output = Vec::with_capacity(tokens.len() / 2 + 64);
+    let mut writer = BitWriter::new(&mut output);
+    codes.literal_lengths.write_lengths(&mut writer);
+    codes.offsets.write_lengths(&mut writer);
+
+    let mut recent = RecentOffsets::default();
+    for token in tokens {
+        match (token, match_buckets(token, &mut recent)) {
+            (_, Some((length, offset))) => {
+                codes.literal_lengths.write(&mut writer, length.symbol);
+                writer.write(length.extra, length.extra_bits);
+                codes.offsets.write(&mut writer, offset.symbol);
+                writer.write(offset.extra, offset.extra_bits);
+            }
+            (&Token::Literal(byte), None) => {
+                codes.literal_lengths.write(&mut writer, byte as usize)
+            }
+            _ => unreachable!(),
+        }
     }
+    codes.literal_lengths.write(&mut writer, END_OF_STREAM);
+    writer.finish();
+    output
 }
 
-// In a real implementation, you'd traverse the Huffman tree to assign bit patterns
-// and then write out bits. For brevity, we'll just store the tokens with minimal encoding.
-pub fn encode_tokens(tokens: &[Token], _tree: &HuffmanTree) -> Vec<u8> {
-    // Pseudo-encoding: just store (tag byte + offset/length or literal).
-    // This is obviously *not* a real bit-compressed output.
+/// Lays tokens out a byte-aligned tag at a time, for a byte-oriented coder
+/// such as FSE: a literal is [0][byte], a match [1][offset: u16][length: u16]
+/// and a dictionary match [2][position: u16][length: u16].
+pub fn encode_tokens(tokens: &[Token]) -> Vec<u8> {
     let mut output = Vec::new();
 
     for token in tokens {
@@ -74,36 +122,131 @@ pub fn encode_tokens(tokens: &[Token], _tree: &HuffmanTree) -> Vec<u8> {
             }
             Token::Match(offset, length) => {
                 output.push(1); // Tag for match
-                // write offset (2 bytes) + length (2 bytes)
+                                // write offset (2 bytes) + length (2 bytes)
                 output.extend_from_slice(&offset.to_le_bytes());
                 output.extend_from_slice(&length.to_le_bytes());
             }
+            Token::DictMatch(position, length) => {
+                output.push(2); // Tag for dictionary match, laid out as a match
+                output.extend_from_slice(&position.to_le_bytes());
+                output.extend_from_slice(&length.to_le_bytes());
+            }
         }
     }
 
     output
 }
 
-// Serialize the Huffman tree into bytes (placeholder).
-// In practice, you'd do a depth-first traversal and record structure + tokens.
-impl HuffmanTree {
-    /// A simple traversal method that reads the tree structure,
-    /// thereby removing the 'fields never read' warnings.
-    #[allow(dead_code)]
-    pub fn walk(&self) {
-        fn recurse(node: &HuffmanNode) {
-            match node {
-                HuffmanNode::Leaf(token) => {
-                    // Just debug-print the leaf's Token
-                    println!("Leaf: {:?}", token);
-                }
-                HuffmanNode::Internal(left, right) => {
-                    // Recursively walk left and right children
-                    recurse(left);
-                    recurse(right);
+impl CodeTable {
+    /// Assigns canonical codes: shorter codes first, and in symbol order
+    /// within a length.
+    fn new(lengths: Vec<u8>) -> Self {
+        let mut counts = [0u32; MAX_CODE_LEN as usize + 1];
+        for &length in &lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut next = [0u32; MAX_CODE_LEN as usize + 1];
+        for length in 1..=MAX_CODE_LEN as usize {
+            next[length] = (next[length - 1] + counts[length - 1]) << 1;
+        }
+        let codes = lengths
+            .iter()
+            .map(|&length| match length {
+                0 => 0,
+                length => {
+                    let code = next[length as usize];
+                    next[length as usize] += 1;
+                    code.reverse_bits() >> (32 - length as u32)
                 }
+            })
+            .collect();
+        CodeTable { lengths, codes }
+    }
+
+    fn write(&self, writer: &mut BitWriter, symbol: usize) {
+        writer.write(self.codes[symbol], self.lengths[symbol] as u32);
+    }
+
+    fn write_lengths(&self, writer: &mut BitWriter) {
+        let mut symbol = 0;
+        while symbol < self.lengths.len() {
+            let length = self.lengths[symbol];
+            writer.write(length as u32, 4);
+            symbol += 1;
+            if length == 0 {
+                let zeros = self.lengths[symbol..]
+                    .iter()
+                    .take_while(|&&length| length == 0)
+                    .count();
+                writer.write_gamma(zeros as u32 + 1);
+                symbol += zeros;
             }
         }
-        recurse(&self.root);
     }
-}
\ No newline at end of file
+}
+
+/// Huffman code lengths for `freqs`, at most MAX_CODE_LEN. If the optimal
+/// code is deeper, counts are halved until it fits, which flattens the tree
+/// at a small cost in ratio.
+fn code_lengths(freqs: &[u32]) -> Vec<u8> {
+    let mut lengths = vec![0u8; freqs.len()];
+    let mut used: Vec<(u32, usize)> = freqs
+        .iter()
+        .enumerate()
+        .filter(|&(_, &freq)| freq > 0)
+        .map(|(symbol, &freq)| (freq, symbol))
+        .collect();
+    match used[..] {
+        [] => return lengths,
+        [(_, symbol)] => {
+            lengths[symbol] = 1;
+            return lengths;
+        }
+        _ => {}
+    }
+    used.sort_unstable();
+
+    loop {
+        // Leaves come first in `weights`, then internal nodes in the order
+        // they are made, whose weights never decrease; so the two lightest
+        // nodes are always at the front of one of the two runs
+        let leaves = used.len();
+        let mut weights: Vec<u64> = used.iter().map(|&(freq, _)| freq as u64).collect();
+        let mut parents = vec![0usize; 2 * leaves];
+        let (mut next_leaf, mut next_node) = (0, leaves);
+        for _ in 1..leaves {
+            let mut lightest = || {
+                let take_leaf = next_leaf < leaves
+                    && (next_node == weights.len() || weights[next_leaf] <= weights[next_node]);
+                let node = if take_leaf {
+                    &mut next_leaf
+                } else {
+                    &mut next_node
+                };
+                *node += 1;
+                *node - 1
+            };
+            let (a, b) = (lightest(), lightest());
+            parents[a] = weights.len();
+            parents[b] = weights.len();
+            weights.push(weights[a] + weights[b]);
+        }
+
+        // The root is last; every node sits one below its parent
+        let mut depths = vec![0u32; weights.len()];
+        for node in (0..weights.len() - 1).rev() {
+            depths[node] = depths[parents[node]] + 1;
+        }
+        if depths[..leaves].iter().all(|&depth| depth <= MAX_CODE_LEN) {
+            for (&(_, symbol), &depth) in used.iter().zip(&depths) {
+                lengths[symbol] = depth as u8;
+            }
+            return lengths;
+        }
+        for (freq, _) in &mut used {
+            *freq = (*freq).div_ceil(2);
+        }
+        used.sort_unstable();
+    }
+}
@@ -0,0 +1,82 @@
+use crate::compression::matcher::Finder;
+use crate::constants::{MAX_LEVEL, MIN_LEVEL, WINDOW_SIZE};
+
+/// Effort settings derived from a compression level.
+#[derive(Debug, Clone, Copy)]
+pub struct LevelParams {
+    /// Where LZ matches come from.
+    pub finder: Finder,
+    /// Most candidates examined per position: hash-chain entries, or
+    /// binary-tree nodes.
+    pub max_chain: usize,
+    /// How many positions after a match are searched for a longer one,
+    /// which the match is then deferred to.
+    pub lazy: usize,
+    /// Most methods a chunk's chain may apply.
+    pub max_methods: usize,
+}
+
+// Indexed by level - 1. Level 6 is the default and searches the whole window;
+// levels 8 and 9 search it with binary trees, which find longer matches
+// faster there, and level 9 looks two positions ahead for a longer match.
+const LEVELS: [LevelParams; 9] = [
+    LevelParams {
+        finder: Finder::HashChain,
+        max_chain: 4,
+        lazy: 0,
+        max_methods: 1,
+    },
+    LevelParams {
+        finder: Finder::HashChain,
+        max_chain: 8,
+        lazy: 0,
+        max_methods: 1,
+    },
+    LevelParams {
+        finder: Finder::HashChain,
+        max_chain: 16,
+        lazy: 0,
+        max_methods: 2,
+    },
+    LevelParams {
+        finder: Finder::HashChain,
+        max_chain: 64,
+        lazy: 0,
+        max_methods: 2,
+    },
+    LevelParams {
+        finder: Finder::HashChain,
+        max_chain: 256,
+        lazy: 0,
+        max_methods: 3,
+    },
+    LevelParams {
+        finder: Finder::HashChain,
+        max_chain: WINDOW_SIZE,
+        lazy: 0,
+        max_methods: 3,
+    },
+    LevelParams {
+        finder: Finder::HashChain,
+        max_chain: WINDOW_SIZE,
+        lazy: 1,
+        max_methods: 3,
+    },
+    LevelParams {
+        finder: Finder::BinaryTree,
+        max_chain: WINDOW_SIZE,
+        lazy: 1,
+        max_methods: 3,
+    },
+    LevelParams {
+        finder: Finder::BinaryTree,
+        max_chain: WINDOW_SIZE,
+        lazy: 2,
+        max_methods: 3,
+    },
+];
+
+/// Looks up the parameters for `level`, clamped to `MIN_LEVEL..=MAX_LEVEL`.
+pub fn level_params(level: u8) -> LevelParams {
+    LEVELS[(level.clamp(MIN_LEVEL, MAX_LEVEL) - MIN_LEVEL) as usize]
+}
@@ -0,0 +1,178 @@
+use super::compress_into;
+use crate::compression::report::ChunkReport;
+use crate::constants::{LONG_RANGE_FLAG, LONG_RANGE_MIN_MATCH, WINDOW_SIZE};
+use crate::shared::error::Error;
+use crate::shared::options::CompressOptions;
+use crate::shared::progress::Progress;
+use crate::shared::varint::write_varint;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec;
+use alloc::vec::Vec;
+
+// Long-range pre-pass over the whole input, as in rzip or zstd's long
+// distance mode. Chunks are compressed on their own with a 4 KB window, so a
+// block that repeats megabytes later would be stored twice; this replaces
+// such repeats with references back into the input and leaves the rest to
+// the chunked algorithms.
+//
+// Repeats are found with a rolling hash over HASH_WINDOW bytes. Only windows
+// whose hash has its top SAMPLE_BITS clear are remembered and looked up,
+// about one in 2^SAMPLE_BITS; a repeat of LONG_RANGE_MIN_MATCH bytes spans
+// enough windows that one of them is nearly always sampled. A candidate is
+// compared byte by byte, then extended both ways.
+//
+// Format: [len: varint][count: varint]
+//         count x [literals: varint][offset: varint][length: varint]
+//         [residual stream...]
+// Each match comes after `literals` bytes of the residual, which holds every
+// byte not covered by a match and is a complete stream with its own flag.
+
+const HASH_WINDOW: usize = 32;
+const SAMPLE_BITS: u32 = 3;
+const MIN_HASH_LOG: u32 = 10;
+const MAX_HASH_LOG: u32 = 20;
+const HASH_PRIME: u64 = 0x100_0000_01B3; // FNV-1a's 64-bit prime
+const HASH_MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// A repeat and the residual bytes before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LongMatch {
+    pub literals: usize,
+    pub offset: usize,
+    pub length: usize,
+}
+
+/// Repeats of at least LONG_RANGE_MIN_MATCH bytes further back than the LZ
+/// window reaches, and the bytes they leave.
+pub fn find_long_matches(input: &[u8]) -> (Vec<LongMatch>, Vec<u8>) {
+    let mut matches = Vec::new();
+    if input.len() < WINDOW_SIZE + LONG_RANGE_MIN_MATCH {
+        return (matches, input.to_vec());
+    }
+
+    let hash_log = ((input.len() >> SAMPLE_BITS) as u32)
+        .next_power_of_two()
+        .trailing_zeros()
+        .clamp(MIN_HASH_LOG, MAX_HASH_LOG);
+    // Latest sampled position + 1 for each hash, 0 for none
+    let mut table = vec![0u32; 1 << hash_log];
+    // Weight of the byte leaving the window
+    let outgoing = (1..HASH_WINDOW).fold(1u64, |power, _| power.wrapping_mul(HASH_PRIME));
+
+    let mut residual = Vec::with_capacity(input.len());
+    let mut anchor = 0;
+    let mut pos = 0;
+    let mut hash = window_hash(&input[..HASH_WINDOW]);
+    while pos + HASH_WINDOW <= input.len() {
+        let mixed = hash.wrapping_mul(HASH_MIX);
+        if mixed >> (64 - SAMPLE_BITS) == 0 {
+            let slot = ((mixed >> (64 - SAMPLE_BITS - hash_log)) as usize) & (table.len() - 1);
+            let candidate = table[slot] as usize;
+            table[slot] = pos as u32 + 1;
+            if let Some(found) = candidate
+                .checked_sub(1)
+                .and_then(|candidate| extend(input, candidate, pos, anchor))
+            {
+                residual.extend_from_slice(&input[anchor..found.literals]);
+                matches.push(LongMatch {
+                    literals: found.literals - anchor,
+                    ..found
+                });
+                anchor = found.literals + found.length;
+                pos = anchor;
+                if pos + HASH_WINDOW > input.len() {
+                    break;
+                }
+                hash = window_hash(&input[pos..pos + HASH_WINDOW]);
+                continue;
+            }
+        }
+        if pos + HASH_WINDOW == input.len() {
+            break;
+        }
+        hash = hash
+            .wrapping_sub((input[pos] as u64).wrapping_mul(outgoing))
+            .wrapping_mul(HASH_PRIME)
+            .wrapping_add(input[pos + HASH_WINDOW] as u64);
+        pos += 1;
+    }
+    residual.extend_from_slice(&input[anchor..]);
+    (matches, residual)
+}
+
+fn window_hash(window: &[u8]) -> u64 {
+    window.iter().fold(0u64, |hash, &byte| {
+        hash.wrapping_mul(HASH_PRIME).wrapping_add(byte as u64)
+    })
+}
+
+/// The repeat of the window at `pos` found at `candidate`, extended forward
+/// and back to `anchor`, if it is far and long enough. `literals` holds
+/// where it starts.
+fn extend(input: &[u8], candidate: usize, pos: usize, anchor: usize) -> Option<LongMatch> {
+    let offset = pos - candidate;
+    if offset <= WINDOW_SIZE
+        || input[candidate..candidate + HASH_WINDOW] != input[pos..pos + HASH_WINDOW]
+    {
+        return None;
+    }
+    let forward = input[candidate + HASH_WINDOW..]
+        .iter()
+        .zip(&input[pos + HASH_WINDOW..])
+        .take_while(|(a, b)| a == b)
+        .count();
+    let back = input[..candidate]
+        .iter()
+        .rev()
+        .zip(input[anchor..pos].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let length = back + HASH_WINDOW + forward;
+    (length >= LONG_RANGE_MIN_MATCH).then_some(LongMatch {
+        literals: pos - back,
+        offset,
+        length,
+    })
+}
+
+/// Runs the pre-pass and compresses what is left with `options` otherwise
+/// unchanged, or returns `None` if there are no far repeats.
+pub fn compress_long_range(
+    input: &[u8],
+    options: &CompressOptions,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+    chunks: &mut Vec<ChunkReport>,
+) -> Result<Option<Vec<u8>>, Error> {
+    let (matches, residual) = find_long_matches(input);
+    if matches.is_empty() {
+        log_message!(logger, LogLevel::Debug, "No long-range matches found");
+        return Ok(None);
+    }
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Long-range pre-pass: {} matches, {} of {} bytes left",
+        matches.len(),
+        residual.len(),
+        input.len()
+    );
+
+    let inner_options = CompressOptions {
+        long_range: false,
+        ..options.clone()
+    };
+    let inner = compress_into(&residual, &inner_options, logger, progress, chunks)?;
+
+    let mut output = Vec::with_capacity(inner.len() + 8 * matches.len() + 16);
+    output.push(LONG_RANGE_FLAG);
+    write_varint(&mut output, input.len() as u64);
+    write_varint(&mut output, matches.len() as u64);
+    for found in &matches {
+        write_varint(&mut output, found.literals as u64);
+        write_varint(&mut output, found.offset as u64);
+        write_varint(&mut output, found.length as u64);
+    }
+    output.extend(inner);
+    Ok(Some(output))
+}
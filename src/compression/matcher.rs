@@ -1,80 +1,283 @@
-use crate::constants::{LOG_LEVEL_DEBUG, LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE, MAX_MATCH_LEN, MIN_MATCH_LEN, WINDOW_SIZE};
-use crate::shared::token::Token;
-use crate::utils::log_message;
-use std::collections::HashMap;
+use crate::compression::binary_tree::BinaryTreeFinder;
+use crate::compression::levels::LevelParams;
+use crate::constants::{MAX_MATCH_LEN, MIN_MATCH_LEN, WINDOW_SIZE};
+use crate::shared::dictionary::STATIC_DICTIONARY;
+use crate::shared::token::{RecentOffsets, Token};
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+use core::mem;
 
-pub fn find_matches(data: &[u8], log_level: &str, verbose: bool) -> Vec<Token> {
-    let mut tokens = Vec::new();
-    let mut i = 0;
-    let mut hash_table: HashMap<u32, Vec<usize>> = HashMap::new();
+// Candidate positions per 3-byte prefix. Both maps give the same matches;
+// HashMap is faster, BTreeMap is what `alloc` offers without std.
+#[cfg(feature = "std")]
+type HashChains = std::collections::HashMap<u32, Vec<usize>>;
+#[cfg(not(feature = "std"))]
+type HashChains = alloc::collections::BTreeMap<u32, Vec<usize>>;
 
-    log_message(LOG_LEVEL_INFO, log_level, "Finding matches in data", verbose);
+// A recent offset is coded in a few bits rather than a bucket and up to 10
+// extra bits, so it wins over a match up to this much longer
+const REPEAT_SLACK: usize = 1;
 
-    while i < data.len() {
-        let (offset, length) = find_match(data, i, &mut hash_table, log_level, verbose);
-        
-        if length >= MIN_MATCH_LEN {
-            tokens.push(Token::Match(offset as u16, length as u16));
-            log_message(LOG_LEVEL_DEBUG, log_level, &format!("Match found: offset={}, length={}", offset, length), verbose);
-            // Skip the matched sequence
-            for j in 0..length {
-                if i + j < data.len() {
-                    update_hash_table(data, i + j, &mut hash_table);
+/// A match found in the window: how far back it starts and how long it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub offset: usize,
+    pub length: usize,
+}
+
+/// Which `MatchFinder` a level uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finder {
+    HashChain,
+    BinaryTree,
+}
+
+/// Finds matches within the last `WINDOW_SIZE` bytes. Positions are passed
+/// to `find` or `skip` once each, in order, so the finder can index each one
+/// for the positions after it.
+pub trait MatchFinder {
+    /// Appends the matches of at least `MIN_MATCH_LEN` bytes at `pos` to
+    /// `out`, each longer than the one before, then indexes `pos`.
+    fn find(&mut self, data: &[u8], pos: usize, out: &mut Vec<Candidate>);
+
+    /// Indexes `pos` without searching it.
+    fn skip(&mut self, data: &[u8], pos: usize);
+}
+
+/// Chains of earlier positions per 3-byte prefix, searched newest first.
+/// Matches never overlap the position they are found for.
+pub struct HashChainFinder {
+    chains: HashChains,
+    max_chain: usize,
+}
+
+impl HashChainFinder {
+    pub fn new(max_chain: usize) -> Self {
+        HashChainFinder {
+            chains: HashChains::new(),
+            max_chain,
+        }
+    }
+}
+
+impl MatchFinder for HashChainFinder {
+    fn find(&mut self, data: &[u8], pos: usize, out: &mut Vec<Candidate>) {
+        if pos + MIN_MATCH_LEN > data.len() {
+            return;
+        }
+        let hash = calc_hash(&data[pos..pos + MIN_MATCH_LEN]);
+        let window_start = pos.saturating_sub(WINDOW_SIZE);
+        if let Some(positions) = self.chains.get(&hash) {
+            let mut best_len = MIN_MATCH_LEN - 1;
+            for &start in positions.iter().rev().take(self.max_chain) {
+                if start < window_start {
+                    break;
+                }
+                let len = data[pos..]
+                    .iter()
+                    .zip(&data[start..pos])
+                    .take(MAX_MATCH_LEN)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    best_len = len;
+                    out.push(Candidate {
+                        offset: pos - start,
+                        length: len,
+                    });
+                    if len == MAX_MATCH_LEN {
+                        break;
+                    }
                 }
             }
-            i += length;
-        } else {
-            tokens.push(Token::Literal(data[i]));
-            log_message(LOG_LEVEL_DEBUG, log_level, &format!("Literal found: {}", data[i]), verbose);
-            update_hash_table(data, i, &mut hash_table);
-            i += 1;
         }
+        self.skip(data, pos);
+    }
+
+    fn skip(&mut self, data: &[u8], pos: usize) {
+        update_hash_table(data, pos, &mut self.chains);
     }
-    tokens
 }
 
-fn find_match(data: &[u8], pos: usize, hash_table: &mut HashMap<u32, Vec<usize>>, log_level: &str, verbose: bool) -> (usize, usize) {
-    if pos + MIN_MATCH_LEN > data.len() {
-        return (0, 0);
+/// Tokens for `data`. `history`, the bytes before it, starts out in the
+/// window so matches may copy from it; with `dictionary`, matches may also
+/// copy from `STATIC_DICTIONARY`.
+pub fn find_matches(
+    history: &[u8],
+    data: &[u8],
+    params: &LevelParams,
+    dictionary: bool,
+    logger: &dyn Logger,
+) -> Vec<Token> {
+    let joined;
+    let data = if history.is_empty() {
+        data
+    } else {
+        joined = [history, data].concat();
+        &joined[..]
+    };
+    match params.finder {
+        Finder::HashChain => parse(
+            HashChainFinder::new(params.max_chain),
+            data,
+            history.len(),
+            params,
+            dictionary,
+            logger,
+        ),
+        Finder::BinaryTree => parse(
+            BinaryTreeFinder::new(data.len(), params.max_chain),
+            data,
+            history.len(),
+            params,
+            dictionary,
+            logger,
+        ),
     }
+}
 
-    let hash = calc_hash(&data[pos..pos + MIN_MATCH_LEN]);
-    let window_start = pos.saturating_sub(WINDOW_SIZE);
-    
-    if let Some(positions) = hash_table.get(&hash) {
-        let mut best_len = 0;
-        let mut best_pos = 0;
+/// Greedy or lazy parse of `data` from `start`, with matches from `finder`.
+fn parse(
+    mut finder: impl MatchFinder,
+    data: &[u8],
+    start: usize,
+    params: &LevelParams,
+    dictionary: bool,
+    logger: &dyn Logger,
+) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut i = start;
+    for pos in start.saturating_sub(WINDOW_SIZE)..start {
+        finder.skip(data, pos);
+    }
+    let dictionary_table = dictionary.then(dictionary_chains);
+    let dictionary_table = dictionary_table.as_ref();
+    let mut recent = RecentOffsets::default();
+    let mut candidates = Vec::new();
+    let mut next_candidates = Vec::new();
+    // Whether lazy matching has already searched position i
+    let mut searched = false;
 
-        for &start in positions.iter().rev() {
-            if start < window_start {
-                break;
-            }
+    log_message!(logger, LogLevel::Info, "Finding matches in data");
 
-            let mut len = 0;
-            while pos + len < data.len() 
-                && start + len < pos 
-                && data[start + len] == data[pos + len]
-                && len < MAX_MATCH_LEN 
-            {
-                len += 1;
-            }
+    'parse: while i < data.len() {
+        if !searched {
+            candidates.clear();
+            finder.find(data, i, &mut candidates);
+        }
+        searched = false;
+        let (token, length) = longest_match(
+            data,
+            i,
+            &candidates,
+            dictionary_table,
+            &recent,
+            params.max_chain,
+        );
 
-            if len > best_len {
-                best_len = len;
-                best_pos = start;
-                if best_len == MAX_MATCH_LEN {
-                    break;
+        // Lazy matching: if one of the next positions has a longer match,
+        // emit literals up to it instead. Only positions within the match
+        // are searched, so the skips below leave none unindexed
+        let lookahead = if (MIN_MATCH_LEN..MAX_MATCH_LEN).contains(&length) {
+            params.lazy.min(length - 1)
+        } else {
+            0
+        };
+        let mut first = 1;
+        while first <= lookahead {
+            next_candidates.clear();
+            finder.find(data, i + first, &mut next_candidates);
+            let (_, next_length) = longest_match(
+                data,
+                i + first,
+                &next_candidates,
+                dictionary_table,
+                &recent,
+                params.max_chain,
+            );
+            if next_length > length {
+                for &byte in &data[i..i + first] {
+                    tokens.push(Token::Literal(byte));
+                    log_message!(logger, LogLevel::Debug, "Literal deferred: {}", byte);
                 }
+                mem::swap(&mut candidates, &mut next_candidates);
+                searched = true;
+                i += first;
+                continue 'parse;
             }
+            first += 1;
         }
 
-        if best_len >= MIN_MATCH_LEN {
-            log_message(LOG_LEVEL_PERFORMANCE, log_level, &format!("Best match found: pos={}, len={}", best_pos, best_len), verbose);
-            return (pos - best_pos, best_len);
+        if length >= MIN_MATCH_LEN {
+            log_message!(logger, LogLevel::Debug, "Match found: {:?}", token);
+            if let Token::Match(offset, _) = token {
+                recent.push(offset);
+            }
+            tokens.push(token);
+            // Skip the matched sequence; positions up to i + first - 1 are indexed
+            for j in first..length {
+                finder.skip(data, i + j);
+            }
+            i += length;
+        } else {
+            tokens.push(Token::Literal(data[i]));
+            log_message!(logger, LogLevel::Debug, "Literal found: {}", data[i]);
+            i += 1;
         }
     }
+    tokens
+}
+
+/// The longest of `candidates` at `pos`, or the dictionary's match when it
+/// is longer, with its length; the length is 0 when neither reaches
+/// `MIN_MATCH_LEN`. A match at a recent offset counts as best when it is
+/// nearly as long.
+fn longest_match(
+    data: &[u8],
+    pos: usize,
+    candidates: &[Candidate],
+    dictionary_table: Option<&HashChains>,
+    recent: &RecentOffsets,
+    max_chain: usize,
+) -> (Token, usize) {
+    let (mut offset, mut length) = candidates
+        .last()
+        .map_or((0, 0), |found| (found.offset, found.length));
+    let (repeat_offset, repeat_length) = find_repeat_match(data, pos, recent);
+    if repeat_length >= MIN_MATCH_LEN && repeat_length + REPEAT_SLACK >= length {
+        (offset, length) = (repeat_offset, repeat_length);
+    }
+    if let Some(chains) = dictionary_table {
+        let (start, dictionary_length) = find_dictionary_match(data, pos, chains, max_chain);
+        if dictionary_length > length {
+            let token = Token::DictMatch(start as u16, dictionary_length as u16);
+            return (token, dictionary_length);
+        }
+    }
+    (Token::Match(offset as u16, length as u16), length)
+}
 
-    (0, 0)
+/// Longest match at one of the recent offsets, as the offset and length.
+/// Like the hash chains' matches, it never overlaps `pos`.
+fn find_repeat_match(data: &[u8], pos: usize, recent: &RecentOffsets) -> (usize, usize) {
+    let mut best = (0, 0);
+    for offset in recent.offsets() {
+        let offset = offset as usize;
+        if offset > pos {
+            continue;
+        }
+        let start = pos - offset;
+        let len = data[pos..]
+            .iter()
+            .zip(&data[start..pos])
+            .take(MAX_MATCH_LEN)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if len > best.1 {
+            best = (offset, len);
+        }
+    }
+    best
 }
 
 #[inline]
@@ -82,7 +285,52 @@ fn calc_hash(bytes: &[u8]) -> u32 {
     (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | (bytes[2] as u32)
 }
 
-fn update_hash_table(data: &[u8], pos: usize, hash_table: &mut HashMap<u32, Vec<usize>>) {
+/// Longest prefix of `data[pos..]` found in `STATIC_DICTIONARY`, as its
+/// position there and length, or (0, 0) if shorter than `MIN_MATCH_LEN`.
+fn find_dictionary_match(
+    data: &[u8],
+    pos: usize,
+    chains: &HashChains,
+    max_chain: usize,
+) -> (usize, usize) {
+    if pos + MIN_MATCH_LEN > data.len() {
+        return (0, 0);
+    }
+    let Some(positions) = chains.get(&calc_hash(&data[pos..pos + MIN_MATCH_LEN])) else {
+        return (0, 0);
+    };
+
+    let mut best = (0, 0);
+    for &start in positions.iter().rev().take(max_chain) {
+        let len = data[pos..]
+            .iter()
+            .zip(&STATIC_DICTIONARY[start..])
+            .take(MAX_MATCH_LEN)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if len > best.1 {
+            best = (start, len);
+            if len == MAX_MATCH_LEN {
+                break;
+            }
+        }
+    }
+    if best.1 >= MIN_MATCH_LEN {
+        best
+    } else {
+        (0, 0)
+    }
+}
+
+fn dictionary_chains() -> HashChains {
+    let mut chains = HashChains::new();
+    for pos in 0..STATIC_DICTIONARY.len() {
+        update_hash_table(STATIC_DICTIONARY, pos, &mut chains);
+    }
+    chains
+}
+
+fn update_hash_table(data: &[u8], pos: usize, hash_table: &mut HashChains) {
     if pos + MIN_MATCH_LEN <= data.len() {
         let hash = calc_hash(&data[pos..pos + MIN_MATCH_LEN]);
         hash_table.entry(hash).or_default().push(pos);
@@ -1,138 +1,172 @@
+pub(crate) mod analysis;
+pub(crate) mod binary_tree;
+pub(crate) mod block_matcher;
 pub(crate) mod huffman;
+pub(crate) mod levels;
+pub(crate) mod long_range;
 pub(crate) mod matcher;
-mod strategies;
+pub(crate) mod report;
+pub(crate) mod strategies;
 
 use crate::constants::{
-    ALGO_BWT, ALGO_DELTA, ALGO_LZ_HUFFMAN, ALGO_RLE, BWT_FLAG, COMPRESSED_FLAG, DELTA_FLAG,
-    LOG_LEVEL_DEBUG, LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE, MIN_FILE_SIZE, RLE_FLAG,
-    UNCOMPRESSED_FLAG,
+    BWT_FLAG, COMPRESSED_FLAG, DELTA_FLAG, FSE_FLAG, MIN_FILE_SIZE, NUMPACK_FLAG, RECORD_FLAG,
+    RLE_FLAG, UNCOMPRESSED_FLAG,
 };
+use crate::shared::algorithm::Algorithm;
 use crate::shared::compression::CompressionResult;
-use crate::utils::{get_log_level, log_message};
-use strategies::{compress_bwt, compress_chunked, compress_delta, compress_lz, compress_rle};
-use wasm_bindgen::JsValue;
+use crate::shared::error::Error;
+use crate::shared::options::CompressOptions;
+use crate::shared::progress::{check_cancelled, Progress};
+use crate::utils::{log_message, now_ms, LogLevel, Logger};
+use long_range::compress_long_range;
+use report::{ChunkReport, CompressionReport};
+use strategies::{
+    compress_bwt, compress_chunked, compress_delta, compress_fse, compress_lz, compress_lz4,
+    compress_numpack, compress_record, compress_rle, compress_snappy,
+};
+pub use strategies::compress_snappy_raw;
+use alloc::vec::Vec;
 
-pub fn compress(input: &[u8], options: &JsValue) -> Vec<u8> {
-    let log_level = get_log_level(options);
-    let verbose = js_sys::Reflect::get(options, &JsValue::from_str("verbose"))
-        .ok()
-        .and_then(|val| val.as_bool())
-        .unwrap_or(false);
+pub fn compress(
+    input: &[u8],
+    options: &CompressOptions,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+) -> Result<Vec<u8>, Error> {
+    Ok(compress_with_report(input, options, logger, progress)?.0)
+}
 
-    let algorithm: String = js_sys::Reflect::get(options, &JsValue::from_str("algorithm"))
-        .ok()
-        .and_then(|val| val.as_string())
-        .unwrap_or_else(|| "best".to_string());
+/// `compress`, also returning a report of the chosen methods, sizes and timing.
+pub fn compress_with_report(
+    input: &[u8],
+    options: &CompressOptions,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+) -> Result<(Vec<u8>, CompressionReport), Error> {
+    let start = now_ms();
+    let mut chunks = Vec::new();
+    let output = compress_into(input, options, logger, progress, &mut chunks)?;
 
-    log_message(LOG_LEVEL_INFO, &log_level, "Starting compression", verbose);
+    let report = CompressionReport {
+        algorithm: options.algorithm,
+        flag: output[0],
+        original_size: input.len(),
+        compressed_size: output.len(),
+        ratio: report::ratio(output.len(), input.len()),
+        time_ms: now_ms() - start,
+        chunks,
+    };
+    Ok((output, report))
+}
+
+fn compress_into(
+    input: &[u8],
+    options: &CompressOptions,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+    chunks: &mut Vec<ChunkReport>,
+) -> Result<Vec<u8>, Error> {
+    check_cancelled(progress)?;
+    log_message!(logger, LogLevel::Info, "Starting compression");
+
+    // LZ4 output is a bare frame, even for small inputs, so standard tools can read it
+    if options.algorithm == Algorithm::Lz4 {
+        log_message!(logger, LogLevel::Performance, "Using LZ4 compression");
+        return Ok(compress_lz4(input, options.level, logger));
+    }
+
+    // Likewise a bare Snappy stream, starting with its stream identifier
+    if options.algorithm == Algorithm::Snappy {
+        log_message!(logger, LogLevel::Performance, "Using Snappy compression");
+        return Ok(compress_snappy(input, options.level, logger));
+    }
 
     // Early exit for small files
     if input.len() < MIN_FILE_SIZE {
-        log_message(
-            LOG_LEVEL_DEBUG,
-            &log_level,
-            "File too small, storing uncompressed",
-            verbose,
-        );
+        log_message!(logger, LogLevel::Debug, "File too small, storing uncompressed");
         let mut output = Vec::with_capacity(input.len() + 1);
         output.push(UNCOMPRESSED_FLAG);
         output.extend_from_slice(input);
-        return output;
+        return Ok(output);
     }
 
-    let result = match algorithm.as_str() {
-        ALGO_RLE => {
-            log_message(
-                LOG_LEVEL_PERFORMANCE,
-                &log_level,
-                "Using RLE compression",
-                verbose,
-            );
-            CompressionResult::Compressed(compress_rle(input, &log_level, verbose), RLE_FLAG)
+    // Far repeats are taken out before chunking, which only the chunked algorithms do
+    if options.long_range && matches!(options.algorithm, Algorithm::Best | Algorithm::Cm) {
+        if let Some(output) = compress_long_range(input, options, logger, progress, chunks)? {
+            return Ok(output);
         }
-        ALGO_DELTA => {
-            log_message(
-                LOG_LEVEL_PERFORMANCE,
-                &log_level,
-                "Using Delta compression",
-                verbose,
-            );
-            CompressionResult::Compressed(compress_delta(input, &log_level, verbose), DELTA_FLAG)
+    }
+
+    let result = match options.algorithm {
+        Algorithm::Rle => {
+            log_message!(logger, LogLevel::Performance, "Using RLE compression");
+            CompressionResult::Compressed(compress_rle(input, logger), RLE_FLAG)
         }
-        ALGO_LZ_HUFFMAN => {
-            log_message(
-                LOG_LEVEL_PERFORMANCE,
-                &log_level,
-                "Using LZ+Huffman compression",
-                verbose,
-            );
-            CompressionResult::Compressed(compress_lz(input, &log_level, verbose), COMPRESSED_FLAG)
+        Algorithm::Delta => {
+            log_message!(logger, LogLevel::Performance, "Using Delta compression");
+            CompressionResult::Compressed(compress_delta(input, logger), DELTA_FLAG)
         }
-        ALGO_BWT => {
-            log_message(
-                LOG_LEVEL_PERFORMANCE,
-                &log_level,
-                "Using BWT compression",
-                verbose,
-            );
-            CompressionResult::Compressed(compress_bwt(input, &log_level, verbose), BWT_FLAG)
+        Algorithm::LzHuffman => {
+            log_message!(logger, LogLevel::Performance, "Using LZ+Huffman compression");
+            CompressionResult::Compressed(compress_lz(input, options.level, options.dictionary, logger), COMPRESSED_FLAG)
         }
-        _ => {
-            log_message(
-                LOG_LEVEL_PERFORMANCE,
-                &log_level,
-                "Using chunked compression",
-                verbose,
-            );
-            compress_chunked(input, &log_level, verbose)
+        Algorithm::Bwt => {
+            log_message!(logger, LogLevel::Performance, "Using BWT compression");
+            CompressionResult::Compressed(compress_bwt(input, logger), BWT_FLAG)
+        }
+        Algorithm::NumPack => {
+            log_message!(logger, LogLevel::Performance, "Using NumPack compression");
+            CompressionResult::Compressed(
+                compress_numpack(input, logger),
+                NUMPACK_FLAG,
+            )
+        }
+        Algorithm::Record => {
+            log_message!(logger, LogLevel::Performance, "Using Record compression");
+            CompressionResult::Compressed(
+                compress_record(input, options.record_width, logger),
+                RECORD_FLAG,
+            )
+        }
+        Algorithm::Fse => {
+            log_message!(logger, LogLevel::Performance, "Using FSE compression");
+            CompressionResult::Compressed(compress_fse(input, logger), FSE_FLAG)
+        }
+        Algorithm::Lz4 => unreachable!("LZ4 frames are written above"),
+        Algorithm::Snappy => unreachable!("Snappy streams are written above"),
+        // Chunked too, but each chunk is coded with CM alone
+        Algorithm::Best | Algorithm::Cm => {
+            log_message!(logger, LogLevel::Performance, "Using chunked compression");
+            let (result, reports) = compress_chunked(input, options, logger, progress)?;
+            *chunks = reports;
+            result
         }
     };
 
     match result {
         CompressionResult::Compressed(data, flag) => {
-            log_message(
-                LOG_LEVEL_DEBUG,
-                &log_level,
-                &format!("Compression successful, method: {:02X}", flag),
-                verbose,
-            );
+            log_message!(logger, LogLevel::Debug, "Compression successful, method: {:02X}", flag);
             let mut output = Vec::with_capacity(data.len() + 1);
             output.push(flag);
             output.extend(data);
-            output
+            Ok(output)
         }
         CompressionResult::Uncompressed(data) => {
-            log_message(
-                LOG_LEVEL_DEBUG,
-                &log_level,
-                "No effective compression found, storing uncompressed",
-                verbose,
+            log_message!(
+                logger,
+                LogLevel::Debug,
+                "No effective compression found, storing uncompressed"
             );
             let mut output = Vec::with_capacity(data.len() + 1);
             output.push(UNCOMPRESSED_FLAG);
             output.extend(data);
-            output
+            Ok(output)
         }
     }
 }
 
 #[allow(dead_code)] // Keep this function for future use
 fn is_compressible(sample: &[u8]) -> bool {
-    // Simple entropy calculation
-    let mut freqs = [0u32; 256];
-    for &byte in sample {
-        freqs[byte as usize] += 1;
-    }
-
-    let mut entropy = 0.0;
-    let sample_len = sample.len() as f64;
-    for &freq in freqs.iter() {
-        if freq > 0 {
-            let p = freq as f64 / sample_len;
-            entropy -= p * p.log2();
-        }
-    }
-
     // If entropy is high (close to 8), data is likely random
-    entropy < 7.0
+    analysis::shannon_entropy(sample) < 7.0
 }
@@ -0,0 +1,64 @@
+// Structured account of what `compress` did, returned by `compress_with_report`.
+
+use crate::shared::algorithm::Algorithm;
+use alloc::vec::Vec;
+
+/// Summary of one `compress` call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionReport {
+    /// The `algorithm` option.
+    pub algorithm: Algorithm,
+    /// Flag byte written at the start of the stream.
+    pub flag: u8,
+    pub original_size: usize,
+    /// Size of the whole stream, including the flag byte.
+    pub compressed_size: usize,
+    /// `compressed_size / original_size`; 1.0 for empty input.
+    pub ratio: f64,
+    pub time_ms: f64,
+    /// One entry per chunk for chunked compression; empty otherwise.
+    pub chunks: Vec<ChunkReport>,
+}
+
+/// How one chunk's chain was built.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkReport {
+    pub original_size: usize,
+    /// Size of the chunk's payload after its chain, excluding the chunk header.
+    pub compressed_size: usize,
+    pub ratio: f64,
+    /// Zero-order entropy of the chunk, in bits per byte.
+    pub entropy: f64,
+    pub time_ms: f64,
+    /// Methods applied, in order.
+    pub chain: Vec<u8>,
+    /// One entry per chain step tried, including the step that stopped it.
+    pub stages: Vec<StageReport>,
+}
+
+/// Every method attempted at one step of a chunk's chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageReport {
+    pub input_size: usize,
+    pub candidates: Vec<CandidateReport>,
+    /// Method appended to the chain, or `None` if no candidate was good enough.
+    pub chosen: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidateReport {
+    pub method: u8,
+    /// Output size of the method on the stage input.
+    pub size: usize,
+    /// Size the candidate was ranked by; for size-preserving transforms this
+    /// is the size after a following LZ pass.
+    pub score: usize,
+}
+
+pub(crate) fn ratio(compressed: usize, original: usize) -> f64 {
+    if original == 0 {
+        1.0
+    } else {
+        compressed as f64 / original as f64
+    }
+}
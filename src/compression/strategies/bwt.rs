@@ -1,36 +1,20 @@
-use std::cmp::Ordering;
-use crate::constants::{LOG_LEVEL_DEBUG, LOG_LEVEL_ERROR, LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE};
-use crate::utils::log_message;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec;
+use alloc::vec::Vec;
 
-pub fn compress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
-    log_message(LOG_LEVEL_INFO, log_level, "Starting BWT compression", verbose);
+pub fn compress_bwt(input: &[u8], logger: &dyn Logger) -> Vec<u8> {
+    log_message!(logger, LogLevel::Info, "Starting BWT compression");
 
-    // Don't use BWT for small or empty inputs
-    if input.len() < 64 {
-        log_message(LOG_LEVEL_DEBUG, log_level, "Input too small for BWT compression", verbose);
-        return input.to_vec();
+    if input.is_empty() {
+        return Vec::new();
     }
 
     let n = input.len();
-    let mut rotations: Vec<usize> = (0..n).collect();
-    
-    // Sort rotations based on their corresponding suffixes
-    rotations.sort_by(|&a, &b| {
-        let mut i = 0;
-        while i < n {
-            let ai = (a + i) % n;
-            let bi = (b + i) % n;
-            match input[ai].cmp(&input[bi]) {
-                Ordering::Equal => i += 1,
-                other => return other,
-            }
-        }
-        Ordering::Equal
-    });
+    let rotations = sort_rotations(input);
 
     let original_idx = rotations.iter().position(|&x| x == 0).unwrap();
     if original_idx >= n {
-        log_message(LOG_LEVEL_ERROR, log_level, "Invalid original index in BWT compression", verbose);
+        log_message!(logger, LogLevel::Error, "Invalid original index in BWT compression");
         return input.to_vec(); // Invalid index
     }
 
@@ -53,7 +37,7 @@ pub fn compress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
             mtf.remove(pos);
             mtf.insert(0, byte);
         } else {
-            log_message(LOG_LEVEL_ERROR, log_level, "Invalid byte value in MTF transform", verbose);
+            log_message!(logger, LogLevel::Error, "Invalid byte value in MTF transform");
             return input.to_vec(); // Invalid byte value
         }
     }
@@ -61,7 +45,7 @@ pub fn compress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
     // Apply RLE with safety checks
     let mut rle_data = Vec::new();
     if mtf_data.is_empty() {
-        log_message(LOG_LEVEL_ERROR, log_level, "MTF data is empty", verbose);
+        log_message!(logger, LogLevel::Error, "MTF data is empty");
         return input.to_vec();
     }
 
@@ -81,14 +65,44 @@ pub fn compress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
     rle_data.push(count);
     rle_data.push(prev);
 
-    // If the compressed size is larger than input, return original
-    if rle_data.len() + 4 >= input.len() {
-        log_message(LOG_LEVEL_DEBUG, log_level, "Compressed size larger than input, returning original", verbose);
-        return input.to_vec();
-    }
-
     transformed.extend(rle_data);
 
-    log_message(LOG_LEVEL_PERFORMANCE, log_level, &format!("BWT compression complete: original_size={}, compressed_size={}", input.len(), transformed.len()), verbose);
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "BWT compression complete: original_size={}, compressed_size={}",
+        input.len(),
+        transformed.len()
+    );
     transformed
 }
+
+/// Sorts the cyclic rotations of `input` by prefix doubling: each pass ranks
+/// rotations by twice as many leading bytes as the pass before.
+fn sort_rotations(input: &[u8]) -> Vec<usize> {
+    let n = input.len();
+    let mut rotations: Vec<usize> = (0..n).collect();
+    let mut rank: Vec<usize> = input.iter().map(|&b| b as usize).collect();
+    let mut next_rank = vec![0; n];
+    let mut k = 1;
+
+    loop {
+        let key = |i: usize| (rank[i], rank[(i + k) % n]);
+        rotations.sort_by_key(|&i| key(i));
+
+        next_rank[rotations[0]] = 0;
+        for w in 1..n {
+            let (prev, curr) = (rotations[w - 1], rotations[w]);
+            next_rank[curr] = next_rank[prev] + (key(prev) != key(curr)) as usize;
+        }
+        core::mem::swap(&mut rank, &mut next_rank);
+
+        // Done once every rotation is distinct or whole rotations were compared
+        if rank[rotations[n - 1]] == n - 1 || 2 * k >= n {
+            break;
+        }
+        k *= 2;
+    }
+
+    rotations
+}
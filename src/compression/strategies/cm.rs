@@ -0,0 +1,21 @@
+use crate::entropy::cm;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+/// Codes the bytes with the context-mixing model, many times slower than
+/// the other methods but the smallest output for text such as logs.
+pub fn compress_cm(data: &[u8], logger: &dyn Logger) -> Vec<u8> {
+    log_message!(logger, LogLevel::Info, "Starting CM compression");
+
+    let result = cm::encode(data);
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "CM compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    result
+}
@@ -1,37 +1,93 @@
-use crate::constants::{LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE};
-use crate::utils::log_message;
+use crate::compression::analysis::{detect_record_width, shannon_entropy};
+use crate::constants::{DELTA_HEADER_SIZE, DELTA_SAMPLE_SIZE, MAX_RECORD_WIDTH};
+use crate::shared::delta::{read_element, write_element};
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
 
-pub fn compress_delta(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
+// (stride, width) pairs always tried; a detected record width is added on top.
+const STRIDE_CANDIDATES: [(usize, usize); 8] = [
+    (1, 1),
+    (2, 1),
+    (3, 1),
+    (4, 1),
+    (8, 1),
+    (2, 2),
+    (4, 4),
+    (8, 8),
+];
+
+pub fn compress_delta(data: &[u8], logger: &dyn Logger) -> Vec<u8> {
     if data.is_empty() {
         return Vec::new();
     }
 
-    log_message(
-        LOG_LEVEL_INFO,
-        log_level,
-        "Starting Delta compression",
-        verbose,
-    );
+    log_message!(logger, LogLevel::Info, "Starting Delta compression");
 
-    let mut result = Vec::with_capacity(data.len());
-    result.push(data[0]); // Store first byte as-is
+    let (stride, width) = detect_stride(data);
+    log_message!(
+        logger,
+        LogLevel::Debug,
+        "Delta layout selected: stride={}, width={}",
+        stride,
+        width
+    );
 
-    // Store differences between consecutive bytes
-    for window in data.windows(2) {
-        let delta = window[1].wrapping_sub(window[0]);
-        result.push(delta);
-    }
+    let mut result = Vec::with_capacity(data.len() + DELTA_HEADER_SIZE);
+    result.extend_from_slice(&(stride as u16).to_le_bytes());
+    result.push(width as u8);
+    result.extend(delta_encode(data, stride, width));
 
-    log_message(
-        LOG_LEVEL_PERFORMANCE,
-        log_level,
-        &format!(
-            "Delta compression complete: original_size={}, compressed_size={}",
-            data.len(),
-            result.len()
-        ),
-        verbose,
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Delta compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        result.len()
     );
 
     result
 }
+
+/// Replaces every `width`-byte little-endian element with its wrapping
+/// difference from the element `stride` bytes earlier. Bytes without a
+/// predecessor, and a trailing partial element, are copied as-is.
+fn delta_encode(data: &[u8], stride: usize, width: usize) -> Vec<u8> {
+    let mut result = data.to_vec();
+    let mut pos = stride;
+    while pos + width <= data.len() {
+        let current = read_element(&data[pos..pos + width]);
+        let previous = read_element(&data[pos - stride..pos - stride + width]);
+        write_element(&mut result[pos..pos + width], current.wrapping_sub(previous));
+        pos += width;
+    }
+    result
+}
+
+/// Picks the (stride, width) whose residuals have the lowest entropy on a
+/// sample from the start of the data.
+fn detect_stride(data: &[u8]) -> (usize, usize) {
+    let sample = &data[..data.len().min(DELTA_SAMPLE_SIZE)];
+
+    let mut candidates = STRIDE_CANDIDATES.to_vec();
+    if let Some(record) = detect_record_width(sample, 2, MAX_RECORD_WIDTH) {
+        for width in [1, 4] {
+            if record % width == 0 && !candidates.contains(&(record, width)) {
+                candidates.push((record, width));
+            }
+        }
+    }
+
+    let mut best = (1, 1);
+    let mut best_entropy = f64::MAX;
+    for (stride, width) in candidates {
+        if stride >= sample.len() {
+            continue;
+        }
+        let entropy = shannon_entropy(&delta_encode(sample, stride, width));
+        if entropy < best_entropy {
+            best_entropy = entropy;
+            best = (stride, width);
+        }
+    }
+    best
+}
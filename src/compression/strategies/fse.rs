@@ -0,0 +1,21 @@
+use crate::entropy::fse;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+/// Codes the bytes with FSE on their own, which suits skewed data such as
+/// BWT output, whose move-to-front ranks are mostly small.
+pub fn compress_fse(data: &[u8], logger: &dyn Logger) -> Vec<u8> {
+    log_message!(logger, LogLevel::Info, "Starting FSE compression");
+
+    let result = fse::encode(data);
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "FSE compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    result
+}
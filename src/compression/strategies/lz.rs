@@ -1,36 +1,75 @@
 use crate::compression::huffman;
+use crate::compression::levels::level_params;
 use crate::compression::matcher;
-use crate::constants::{LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE};
-use crate::utils::log_message;
-
-pub fn compress_lz(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
-    log_message(
-        LOG_LEVEL_INFO,
-        log_level,
-        "Starting LZ compression",
-        verbose,
-    );
+use crate::constants::{LZ_DICTIONARY_MARKER, LZ_FSE_HEADER, LZ_HUFFMAN_HEADER};
+use crate::entropy::fse;
+use crate::shared::token::Token;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+/// With `dictionary`, matches may copy from the static dictionary. If any
+/// does, the stream starts with LZ_DICTIONARY_MARKER. The tokens are
+/// Huffman coded behind LZ_HUFFMAN_HEADER, or laid out as bytes and FSE
+/// coded behind LZ_FSE_HEADER, whichever is smaller.
+pub fn compress_lz(data: &[u8], level: u8, dictionary: bool, logger: &dyn Logger) -> Vec<u8> {
+    compress_lz_with_history(&[], data, level, dictionary, logger).0
+}
+
+/// `compress_lz` with matches that may also copy from `history`, the bytes
+/// just before `data`, and whether any does. If so, the decoder needs the
+/// same history.
+pub fn compress_lz_with_history(
+    history: &[u8],
+    data: &[u8],
+    level: u8,
+    dictionary: bool,
+    logger: &dyn Logger,
+) -> (Vec<u8>, bool) {
+    log_message!(logger, LogLevel::Info, "Starting LZ compression");
 
     // 1. Generate LZ77 tokens
-    let tokens = matcher::find_matches(data, log_level, verbose);
+    let tokens = matcher::find_matches(history, data, &level_params(level), dictionary, logger);
 
-    // 2. Build Huffman tree
-    let tree = huffman::build_huffman_tree(&tokens);
+    // 2. Build a code for literals and lengths, and one for offsets
+    let codes = huffman::build_huffman_codes(&tokens);
 
     // 3. Encode tokens with Huffman
-    let mut result = tree.serialize();
-    result.extend(huffman::encode_tokens(&tokens, &tree));
-
-    log_message(
-        LOG_LEVEL_PERFORMANCE,
-        log_level,
-        &format!(
-            "LZ compression complete: original_size={}, compressed_size={}",
-            data.len(),
-            result.len()
-        ),
-        verbose,
+    let mut result = Vec::new();
+    if tokens.iter().any(|token| matches!(token, Token::DictMatch(..))) {
+        result.push(LZ_DICTIONARY_MARKER);
+    }
+    let encoded = huffman::encode_huffman(&tokens, &codes);
+    let coded = fse::encode(&huffman::encode_tokens(&tokens));
+    if coded.len() < encoded.len() {
+        result.push(LZ_FSE_HEADER);
+        result.extend(coded);
+    } else {
+        result.push(LZ_HUFFMAN_HEADER);
+        result.extend(encoded);
+    }
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "LZ compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        result.len()
     );
 
-    result
+    (result, copies_from_history(&tokens))
+}
+
+/// Whether a match reaches back before the first byte the tokens code.
+fn copies_from_history(tokens: &[Token]) -> bool {
+    let mut pos = 0;
+    tokens.iter().any(|token| {
+        let (length, reach) = match *token {
+            Token::Literal(_) => (1, 0),
+            Token::Match(offset, length) => (length as usize, offset as usize),
+            Token::DictMatch(_, length) => (length as usize, 0),
+        };
+        let before_start = reach > pos;
+        pos += length;
+        before_start
+    })
 }
@@ -0,0 +1,97 @@
+use crate::compression::block_matcher::BlockMatcher;
+use crate::constants::{LZ4_BLOCK_SIZE, LZ4_LAST_LITERALS, LZ4_MAGIC, LZ4_MF_LIMIT, LZ4_MIN_MATCH};
+use crate::shared::xxhash::xxh32;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+// Writes a standard LZ4 frame, readable by the reference `lz4` tool.
+//
+// Frame: [magic: u32][FLG][BD][content_size: u64][header checksum: u8]
+// followed by blocks of [size: u32][data], a zero end mark and the xxHash32
+// of the content. Blocks are independent and hold up to LZ4_BLOCK_SIZE
+// input bytes; the size's top bit marks a block stored uncompressed.
+
+const FLG: u8 = 0x40 | 0x20 | 0x08 | 0x04; // Version 1, independent blocks, content size and checksum
+const BD: u8 = 0x70; // 4 MiB blocks
+const UNCOMPRESSED_BLOCK: u32 = 1 << 31;
+
+pub fn compress_lz4(data: &[u8], level: u8, logger: &dyn Logger) -> Vec<u8> {
+    log_message!(logger, LogLevel::Info, "Starting LZ4 compression");
+
+    let mut matcher = BlockMatcher::new(data.len().min(LZ4_BLOCK_SIZE), level);
+
+    let mut result = Vec::with_capacity(data.len() / 2 + 32);
+    result.extend_from_slice(&LZ4_MAGIC.to_le_bytes());
+    result.extend_from_slice(&[FLG, BD]);
+    result.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    result.push((xxh32(&result[4..], 0) >> 8) as u8);
+
+    let mut block = Vec::new();
+    for chunk in data.chunks(LZ4_BLOCK_SIZE) {
+        block.clear();
+        compress_block(&mut matcher, chunk, &mut block);
+        if block.len() < chunk.len() {
+            result.extend_from_slice(&(block.len() as u32).to_le_bytes());
+            result.extend_from_slice(&block);
+        } else {
+            result.extend_from_slice(&(chunk.len() as u32 | UNCOMPRESSED_BLOCK).to_le_bytes());
+            result.extend_from_slice(chunk);
+        }
+    }
+    result.extend_from_slice(&0u32.to_le_bytes());
+    result.extend_from_slice(&xxh32(data, 0).to_le_bytes());
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "LZ4 compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    result
+}
+
+fn compress_block(matcher: &mut BlockMatcher, src: &[u8], out: &mut Vec<u8>) {
+    let mut anchor = 0;
+    if src.len() > LZ4_MF_LIMIT {
+        let last_start = src.len() - LZ4_MF_LIMIT;
+        let match_end = src.len() - LZ4_LAST_LITERALS;
+        anchor = matcher.parse(src, last_start, match_end, |sequence| {
+            write_sequence(
+                out,
+                &src[sequence.literals],
+                sequence.offset,
+                sequence.length,
+            );
+        });
+    }
+    write_last_literals(out, &src[anchor..]);
+}
+
+fn write_sequence(out: &mut Vec<u8>, literals: &[u8], offset: usize, length: usize) {
+    let match_code = length - LZ4_MIN_MATCH;
+    out.push((literals.len().min(15) as u8) << 4 | match_code.min(15) as u8);
+    write_length(out, literals.len());
+    out.extend_from_slice(literals);
+    out.extend_from_slice(&(offset as u16).to_le_bytes());
+    write_length(out, match_code);
+}
+
+fn write_last_literals(out: &mut Vec<u8>, literals: &[u8]) {
+    out.push((literals.len().min(15) as u8) << 4);
+    write_length(out, literals.len());
+    out.extend_from_slice(literals);
+}
+
+/// Continues a token nibble of 15 with bytes of 255 and a final remainder.
+fn write_length(out: &mut Vec<u8>, length: usize) {
+    if length >= 15 {
+        let mut rest = length - 15;
+        while rest >= 255 {
+            out.push(255);
+            rest -= 255;
+        }
+        out.push(rest as u8);
+    }
+}
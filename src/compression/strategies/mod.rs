@@ -1,120 +1,284 @@
 mod bwt;
+mod cm;
 mod delta;
+mod fse;
 mod lz;
+mod lz4;
+mod numpack;
+mod record;
 mod rle;
+mod snappy;
 
 use crate::constants::{
-    BWT_FLAG, CHUNKED_FLAG, COMPRESSED_FLAG, DELTA_FLAG, LOG_LEVEL_DEBUG, LOG_LEVEL_INFO,
-    LOG_LEVEL_PERFORMANCE, RLE_FLAG,
+    BWT_FLAG, CHUNKED_FLAG, CHUNK_DEPENDS_ON_PREVIOUS, CM_FLAG, COMPRESSED_FLAG, DELTA_FLAG,
+    FSE_FLAG, NUMPACK_FLAG, RECORD_FLAG, RLE_FLAG, WINDOW_SIZE,
 };
+use crate::compression::analysis::shannon_entropy;
+use crate::compression::levels::level_params;
+use crate::compression::report::{self, CandidateReport, ChunkReport, StageReport};
+use crate::shared::algorithm::Algorithm;
 use crate::shared::compression::CompressionResult;
-use crate::utils::log_message;
+use crate::shared::error::Error;
+use crate::shared::options::{ChunkMode, CompressOptions};
+use crate::shared::progress::{check_cancelled, Progress};
+use crate::utils::{log_message, now_ms, LogLevel, Logger};
 pub use bwt::compress_bwt;
+pub use cm::compress_cm;
 pub use delta::compress_delta;
-pub use lz::compress_lz;
+pub use fse::compress_fse;
+pub use lz::{compress_lz, compress_lz_with_history};
+pub use lz4::compress_lz4;
+pub use numpack::compress_numpack;
+pub use record::compress_record;
 pub use rle::compress_rle;
+pub use snappy::{compress_snappy, compress_snappy_raw};
+use alloc::vec;
+use alloc::vec::Vec;
 
-pub const CHUNK_SIZE: usize = 32 * 1024; // 32KB chunks
+pub const CHUNK_SIZE: usize = 32 * 1024; // Default chunk size (32KB)
 const CHAIN_THRESHOLD: f64 = 0.90; // If compression ratio > 90%, try chaining
-const MAX_METHODS: usize = 3; // Maximum number of compression methods to apply
+pub(crate) const MAX_METHODS: usize = 3; // Maximum number of compression methods to apply
 
-type CompressionFn = fn(&[u8], &str, bool) -> Vec<u8>;
+// Methods tried at each step of a chunk's chain; earlier entries win ties.
+pub(crate) const CHAIN_METHODS: [u8; 7] = [
+    COMPRESSED_FLAG,
+    RLE_FLAG,
+    DELTA_FLAG,
+    BWT_FLAG,
+    NUMPACK_FLAG,
+    RECORD_FLAG,
+    FSE_FLAG,
+];
 
 #[derive(Debug)]
 struct CompressedChunk {
     compressed_data: Vec<u8>,
     methods: Vec<u8>,
+    stages: Vec<StageReport>,
+    /// Whether LZ copied from the previous chunk.
+    depends_on_previous: bool,
 }
 
-pub fn compress_chunked(data: &[u8], log_level: &str, verbose: bool) -> CompressionResult {
-    let chunks: Vec<&[u8]> = data.chunks(CHUNK_SIZE).collect();
+/// Compresses each chunk with its own chain, also returning how each chain
+/// was chosen. Reports progress after every chunk. In dependent chunk mode,
+/// LZ may also copy from the last WINDOW_SIZE bytes of the previous chunk.
+pub fn compress_chunked(
+    data: &[u8],
+    options: &CompressOptions,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+) -> Result<(CompressionResult, Vec<ChunkReport>), Error> {
+    let chunks: Vec<&[u8]> = data.chunks(options.chunk_size.max(1)).collect();
     let total_chunks = chunks.len() as u32;
     let mut compressed_chunks = Vec::new();
+    let mut reports = Vec::with_capacity(chunks.len());
 
-    log_message(
-        LOG_LEVEL_INFO,
-        log_level,
-        "Starting chunked compression",
-        verbose,
-    );
+    log_message!(logger, LogLevel::Info, "Starting chunked compression");
 
     // Write total metadata
     compressed_chunks.extend_from_slice(&total_chunks.to_le_bytes());
     compressed_chunks.extend_from_slice(&(data.len() as u32).to_le_bytes());
 
-    for chunk in chunks.iter() {
-        let info = compress_chunk(chunk, log_level, verbose);
+    let mut done = 0;
+    for (index, chunk) in chunks.iter().enumerate() {
+        check_cancelled(progress)?;
+        let start = now_ms();
+        // The LZ window may start with the end of the previous chunk
+        let history = match (options.chunk_mode, index.checked_sub(1)) {
+            (ChunkMode::Dependent, Some(previous)) => {
+                let previous = chunks[previous];
+                &previous[previous.len().saturating_sub(WINDOW_SIZE)..]
+            }
+            _ => &[][..],
+        };
+        let info = compress_chunk(chunk, history, options, logger);
+        reports.push(ChunkReport {
+            original_size: chunk.len(),
+            compressed_size: info.compressed_data.len(),
+            ratio: report::ratio(info.compressed_data.len(), chunk.len()),
+            entropy: shannon_entropy(chunk),
+            time_ms: now_ms() - start,
+            chain: info.methods.clone(),
+            stages: info.stages.clone(),
+        });
 
         // Format: [chunk_size: u32][methods_count: u8][methods...][compressed_data...]
+        // with CHUNK_DEPENDS_ON_PREVIOUS set in the count if LZ used the history
         let chunk_header = (info.compressed_data.len() as u32).to_le_bytes();
         compressed_chunks.extend_from_slice(&chunk_header);
-        compressed_chunks.push(info.methods.len() as u8);
+        let mut methods_count = info.methods.len() as u8;
+        if info.depends_on_previous {
+            methods_count |= CHUNK_DEPENDS_ON_PREVIOUS;
+        }
+        compressed_chunks.push(methods_count);
         compressed_chunks.extend_from_slice(&info.methods);
         compressed_chunks.extend_from_slice(&info.compressed_data);
+
+        done += chunk.len();
+        progress.report(done, data.len());
     }
 
-    log_message(
-        LOG_LEVEL_PERFORMANCE,
-        log_level,
-        &format!(
-            "Chunked compression complete: original_size={}, compressed_size={}",
-            data.len(),
-            compressed_chunks.len()
-        ),
-        verbose,
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Chunked compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        compressed_chunks.len()
     );
 
-    if compressed_chunks.len() < data.len() {
+    let result = if compressed_chunks.len() < data.len() {
         CompressionResult::Compressed(compressed_chunks, CHUNKED_FLAG)
     } else {
         CompressionResult::Uncompressed(data.to_vec())
-    }
+    };
+    Ok((result, reports))
 }
 
-fn compress_chunk(chunk: &[u8], log_level: &str, verbose: bool) -> CompressedChunk {
+/// `history` is what LZ may copy from besides the chunk itself, when LZ is
+/// the chain's first stage and so sees the chunk's own bytes.
+fn compress_chunk(
+    chunk: &[u8],
+    history: &[u8],
+    options: &CompressOptions,
+    logger: &dyn Logger,
+) -> CompressedChunk {
+    if options.algorithm == Algorithm::Cm {
+        return compress_chunk_cm(chunk, logger);
+    }
+    let lz = |history: &[u8], data: &[u8]| {
+        compress_lz_with_history(history, data, options.level, options.dictionary, logger)
+    };
+
     let mut methods = Vec::new();
+    let mut stages = Vec::new();
     let mut current_data = chunk.to_vec();
+    let max_methods = level_params(options.level).max_methods.min(MAX_METHODS);
+    let mut depends_on_previous = false;
 
-    let attempts: [(CompressionFn, u8); 4] = [
-        (compress_lz as CompressionFn, COMPRESSED_FLAG),
-        (compress_rle as CompressionFn, RLE_FLAG),
-        (compress_delta as CompressionFn, DELTA_FLAG),
-        (compress_bwt as CompressionFn, BWT_FLAG),
-    ];
+    while methods.len() < max_methods {
+        // After a transform the data no longer lines up with the history
+        let lz_history = if methods.is_empty() { history } else { &[] };
+        let mut lz_used_history = false;
 
-    while methods.len() < MAX_METHODS {
-        let best_attempt = attempts
+        // The record transform doesn't shrink data itself, so it is ranked by
+        // how well LZ compresses its output, if LZ can still follow it
+        let lookahead = !methods.contains(&COMPRESSED_FLAG) && methods.len() + 2 <= max_methods;
+
+        let attempts: Vec<(usize, Vec<u8>, u8)> = CHAIN_METHODS
             .iter()
-            .filter(|(_, flag)| !methods.contains(flag))
-            .map(|(compress_fn, flag)| {
-                let compressed = compress_fn(&current_data, log_level, verbose);
-                (compressed.len(), compressed, *flag)
+            .filter(|flag| !methods.contains(*flag))
+            .map(|&flag| {
+                let compressed = match flag {
+                    COMPRESSED_FLAG => {
+                        let (compressed, used_history) = lz(lz_history, &current_data);
+                        lz_used_history = used_history;
+                        compressed
+                    }
+                    _ => compress_method(flag, &current_data, options, logger),
+                };
+                let score = if lookahead && flag == RECORD_FLAG {
+                    lz(&[], &compressed).0.len()
+                } else {
+                    compressed.len()
+                };
+                (score, compressed, flag)
             })
-            .min_by_key(|(size, _, _)| *size)
+            .collect();
+
+        let mut stage = StageReport {
+            input_size: current_data.len(),
+            candidates: attempts
+                .iter()
+                .map(|(score, compressed, flag)| CandidateReport {
+                    method: *flag,
+                    size: compressed.len(),
+                    score: *score,
+                })
+                .collect(),
+            chosen: None,
+        };
+
+        let best_attempt = attempts
+            .into_iter()
+            .min_by_key(|(score, _, _)| *score)
             .unwrap();
 
         let ratio = best_attempt.0 as f64 / current_data.len() as f64;
 
-        log_message(
-            LOG_LEVEL_DEBUG,
-            log_level,
-            &format!(
-                "Compression attempt: method={:02X}, ratio={:.2}",
-                best_attempt.2, ratio
-            ),
-            verbose,
+        log_message!(
+            logger,
+            LogLevel::Debug,
+            "Compression attempt: method={:02X}, ratio={:.2}",
+            best_attempt.2,
+            ratio
         );
 
         if ratio > CHAIN_THRESHOLD {
+            stages.push(stage);
             break;
         }
 
+        stage.chosen = Some(best_attempt.2);
+        stages.push(stage);
         methods.push(best_attempt.2);
         current_data = best_attempt.1;
+        depends_on_previous |= best_attempt.2 == COMPRESSED_FLAG && lz_used_history;
     }
 
     CompressedChunk {
         compressed_data: current_data,
         methods,
+        stages,
+        depends_on_previous,
+    }
+}
+
+/// CM on its own, with no chain search: it beats any chain that could
+/// precede it, so the chunk is stored unless CM shrinks it.
+fn compress_chunk_cm(chunk: &[u8], logger: &dyn Logger) -> CompressedChunk {
+    let compressed = compress_cm(chunk, logger);
+    let chosen = (compressed.len() < chunk.len()).then_some(CM_FLAG);
+    let stages = vec![StageReport {
+        input_size: chunk.len(),
+        candidates: vec![CandidateReport {
+            method: CM_FLAG,
+            size: compressed.len(),
+            score: compressed.len(),
+        }],
+        chosen,
+    }];
+
+    match chosen {
+        Some(flag) => CompressedChunk {
+            compressed_data: compressed,
+            methods: vec![flag],
+            stages,
+            depends_on_previous: false,
+        },
+        None => CompressedChunk {
+            compressed_data: chunk.to_vec(),
+            methods: Vec::new(),
+            stages,
+            depends_on_previous: false,
+        },
+    }
+}
+
+pub(crate) fn compress_method(
+    method: u8,
+    data: &[u8],
+    options: &CompressOptions,
+    logger: &dyn Logger,
+) -> Vec<u8> {
+    match method {
+        COMPRESSED_FLAG => compress_lz(data, options.level, options.dictionary, logger),
+        RLE_FLAG => compress_rle(data, logger),
+        DELTA_FLAG => compress_delta(data, logger),
+        BWT_FLAG => compress_bwt(data, logger),
+        NUMPACK_FLAG => compress_numpack(data, logger),
+        RECORD_FLAG => compress_record(data, options.record_width, logger),
+        FSE_FLAG => compress_fse(data, logger),
+        CM_FLAG => compress_cm(data, logger),
+        _ => data.to_vec(),
     }
 }
@@ -0,0 +1,124 @@
+use crate::constants::{NUMPACK_MARKER, NUMPACK_MAX_DIGITS};
+use crate::shared::varint::{write_varint, zigzag_encode};
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+// Moves decimal numbers out of the text into a separate stream.
+//
+// Format: [text_len: u32][text...][numbers...]
+// Every number in the text is replaced by NUMPACK_MARKER. For each marker the
+// numbers stream holds a varint descriptor followed by the zigzag varint of
+// the signed mantissa minus the previous number's mantissa. The descriptor is
+// `digits | frac_digits << 5 | negative << 10`, where `digits` counts every
+// digit written (including leading zeros); a descriptor of 0 stands for a
+// literal NUMPACK_MARKER byte in the original data.
+pub fn compress_numpack(data: &[u8], logger: &dyn Logger) -> Vec<u8> {
+    log_message!(logger, LogLevel::Info, "Starting NumPack compression");
+
+    let mut text = Vec::with_capacity(data.len());
+    let mut numbers = Vec::new();
+    let mut previous = 0i64;
+    let mut count = 0usize;
+    let mut i = 0;
+
+    while i < data.len() {
+        let byte = data[i];
+        if byte == NUMPACK_MARKER {
+            text.push(NUMPACK_MARKER);
+            write_varint(&mut numbers, 0);
+            i += 1;
+            continue;
+        }
+
+        match scan_number(data, i) {
+            Some(number) => {
+                let value = if number.negative {
+                    -(number.mantissa as i64)
+                } else {
+                    number.mantissa as i64
+                };
+                let descriptor =
+                    number.digits | number.frac_digits << 5 | (number.negative as usize) << 10;
+
+                text.push(NUMPACK_MARKER);
+                write_varint(&mut numbers, descriptor as u64);
+                write_varint(&mut numbers, zigzag_encode(value.wrapping_sub(previous)));
+                previous = value;
+                count += 1;
+                i = number.end;
+            }
+            None => {
+                text.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    log_message!(logger, LogLevel::Debug, "NumPack extracted {} numbers", count);
+
+    let mut result = Vec::with_capacity(4 + text.len() + numbers.len());
+    result.extend_from_slice(&(text.len() as u32).to_le_bytes());
+    result.extend(text);
+    result.extend(numbers);
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "NumPack compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    result
+}
+
+struct NumberToken {
+    mantissa: u64,
+    digits: usize,
+    frac_digits: usize,
+    negative: bool,
+    end: usize,
+}
+
+/// Recognizes `-?[0-9]+(\.[0-9]+)?` at `start`, capped at NUMPACK_MAX_DIGITS
+/// digits. A `-` only counts as a sign when it does not follow a word
+/// character, so ranges and dates like `2020-01` keep their dash as text.
+fn scan_number(data: &[u8], start: usize) -> Option<NumberToken> {
+    let negative = data[start] == b'-'
+        && data.get(start + 1).is_some_and(u8::is_ascii_digit)
+        && (start == 0 || !data[start - 1].is_ascii_alphanumeric());
+    let mut pos = if negative { start + 1 } else { start };
+    if !data[pos].is_ascii_digit() {
+        return None;
+    }
+
+    let mut mantissa = 0u64;
+    let mut digits = 0;
+    while pos < data.len() && data[pos].is_ascii_digit() && digits < NUMPACK_MAX_DIGITS {
+        mantissa = mantissa * 10 + (data[pos] - b'0') as u64;
+        digits += 1;
+        pos += 1;
+    }
+
+    let mut frac_digits = 0;
+    if digits < NUMPACK_MAX_DIGITS
+        && data.get(pos) == Some(&b'.')
+        && data.get(pos + 1).is_some_and(u8::is_ascii_digit)
+    {
+        pos += 1;
+        while pos < data.len() && data[pos].is_ascii_digit() && digits < NUMPACK_MAX_DIGITS {
+            mantissa = mantissa * 10 + (data[pos] - b'0') as u64;
+            digits += 1;
+            frac_digits += 1;
+            pos += 1;
+        }
+    }
+
+    Some(NumberToken {
+        mantissa,
+        digits,
+        frac_digits,
+        negative,
+        end: pos,
+    })
+}
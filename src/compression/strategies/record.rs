@@ -0,0 +1,61 @@
+use crate::compression::analysis::{detect_record_width, shannon_entropy};
+use crate::constants::{
+    DELTA_SAMPLE_SIZE, MAX_RECORD_WIDTH, RECORD_HEADER_SIZE, RECORD_MODE_SUB, RECORD_MODE_XOR,
+};
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+// Fixed-width record transform: every byte is combined with the byte at the
+// same offset of the previous record, by XOR or by wrapping subtraction.
+//
+// Format: [width: u16][mode: u8][transformed data...]
+pub fn compress_record(
+    data: &[u8],
+    record_width: Option<usize>,
+    logger: &dyn Logger,
+) -> Vec<u8> {
+    log_message!(logger, LogLevel::Info, "Starting Record compression");
+
+    let sample = &data[..data.len().min(DELTA_SAMPLE_SIZE)];
+    let width = record_width
+        .or_else(|| detect_record_width(sample, 2, MAX_RECORD_WIDTH))
+        .unwrap_or(1)
+        .clamp(1, u16::MAX as usize);
+
+    let xor_entropy = shannon_entropy(&record_encode(sample, width, RECORD_MODE_XOR));
+    let sub_entropy = shannon_entropy(&record_encode(sample, width, RECORD_MODE_SUB));
+    let mode = if sub_entropy < xor_entropy {
+        RECORD_MODE_SUB
+    } else {
+        RECORD_MODE_XOR
+    };
+
+    log_message!(logger, LogLevel::Debug, "Record layout selected: width={}, mode={}", width, mode);
+
+    let mut result = Vec::with_capacity(data.len() + RECORD_HEADER_SIZE);
+    result.extend_from_slice(&(width as u16).to_le_bytes());
+    result.push(mode);
+    result.extend(record_encode(data, width, mode));
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Record compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    result
+}
+
+fn record_encode(data: &[u8], width: usize, mode: u8) -> Vec<u8> {
+    let mut result = data.to_vec();
+    for i in width..data.len() {
+        result[i] = if mode == RECORD_MODE_SUB {
+            data[i].wrapping_sub(data[i - width])
+        } else {
+            data[i] ^ data[i - width]
+        };
+    }
+    result
+}
@@ -1,11 +1,11 @@
-use crate::constants::{LOG_LEVEL_DEBUG, LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE};
-use crate::utils::log_message;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
 
-pub fn compress_rle(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
+pub fn compress_rle(data: &[u8], logger: &dyn Logger) -> Vec<u8> {
     let mut result = Vec::with_capacity(data.len());
     let mut i = 0;
 
-    log_message(LOG_LEVEL_INFO, log_level, "Starting RLE compression", verbose);
+    log_message!(logger, LogLevel::Info, "Starting RLE compression");
 
     while i < data.len() {
         let mut count = 1;
@@ -16,7 +16,13 @@ pub fn compress_rle(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
         }
 
         if count >= 4 {
-            log_message(LOG_LEVEL_DEBUG, log_level, &format!("RLE match found: value={}, count={}", current, count), verbose);
+            log_message!(
+                logger,
+                LogLevel::Debug,
+                "RLE match found: value={}, count={}",
+                current,
+                count
+            );
             // Format: [marker byte, count, value]
             result.push(0xFF);
             result.push(count as u8);
@@ -25,7 +31,10 @@ pub fn compress_rle(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
         } else {
             // Store literals until we find a run
             let literal_start = i;
-            while i < data.len() && (i + 1 >= data.len() || data[i] != data[i + 1] || count < 4) {
+            while i < data.len()
+                && i - literal_start < 255
+                && (i + 1 >= data.len() || data[i] != data[i + 1] || count < 4)
+            {
                 i += 1;
                 count = 1;
                 while i + count < data.len() && data[i + count] == data[i] && count < 4 {
@@ -34,7 +43,12 @@ pub fn compress_rle(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
             }
             let literal_length = i - literal_start;
             if literal_length > 0 {
-                log_message(LOG_LEVEL_DEBUG, log_level, &format!("Literal sequence found: length={}", literal_length), verbose);
+                log_message!(
+                    logger,
+                    LogLevel::Debug,
+                    "Literal sequence found: length={}",
+                    literal_length
+                );
                 result.push(0xFE); // Literal marker
                 result.push(literal_length as u8); // Length of literals
                 result.extend_from_slice(&data[literal_start..i]);
@@ -42,6 +56,12 @@ pub fn compress_rle(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
         }
     }
 
-    log_message(LOG_LEVEL_PERFORMANCE, log_level, &format!("RLE compression complete: original_size={}, compressed_size={}", data.len(), result.len()), verbose);
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "RLE compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        result.len()
+    );
     result
 }
@@ -0,0 +1,130 @@
+use crate::compression::block_matcher::{BlockMatcher, MIN_MATCH};
+use crate::constants::{SNAPPY_BLOCK_SIZE, SNAPPY_STREAM_IDENTIFIER};
+use crate::shared::crc32c::masked_crc32c;
+use crate::shared::varint::write_varint;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+// Writes the Snappy framing format, readable by any Snappy implementation.
+//
+// Stream: the stream identifier chunk, then one chunk per SNAPPY_BLOCK_SIZE
+// bytes of input. Each chunk is [type: u8][length: u24][masked CRC-32C of
+// the uncompressed data: u32][data], where type 0x00 holds raw Snappy and
+// 0x01 the data itself, used when compression saves less than 1/8.
+//
+// Raw Snappy is a varint of the uncompressed length followed by literal and
+// copy elements, each introduced by a tag byte whose low two bits give its
+// kind: 0 literal, 1 copy with an 11-bit offset, 2 copy with a 16-bit offset.
+
+const COMPRESSED_CHUNK: u8 = 0x00;
+const UNCOMPRESSED_CHUNK: u8 = 0x01;
+
+pub fn compress_snappy(data: &[u8], level: u8, logger: &dyn Logger) -> Vec<u8> {
+    log_message!(logger, LogLevel::Info, "Starting Snappy compression");
+
+    let mut matcher = BlockMatcher::new(data.len().min(SNAPPY_BLOCK_SIZE), level);
+    let mut result = Vec::with_capacity(data.len() / 2 + 32);
+    result.extend_from_slice(&SNAPPY_STREAM_IDENTIFIER);
+
+    let mut raw = Vec::new();
+    for chunk in data.chunks(SNAPPY_BLOCK_SIZE) {
+        raw.clear();
+        compress_raw_into(&mut matcher, chunk, &mut raw);
+        let (kind, body) = if raw.len() < chunk.len() - chunk.len() / 8 {
+            (COMPRESSED_CHUNK, &raw[..])
+        } else {
+            (UNCOMPRESSED_CHUNK, chunk)
+        };
+        result.push(kind);
+        result.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes()[..3]);
+        result.extend_from_slice(&masked_crc32c(chunk).to_le_bytes());
+        result.extend_from_slice(body);
+    }
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Snappy compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    result
+}
+
+/// Raw Snappy, without framing or checksums, as stored by LevelDB and
+/// similar stores.
+pub fn compress_snappy_raw(data: &[u8], level: u8, logger: &dyn Logger) -> Vec<u8> {
+    log_message!(logger, LogLevel::Info, "Starting raw Snappy compression");
+
+    let mut matcher = BlockMatcher::new(data.len().min(SNAPPY_BLOCK_SIZE), level);
+    let mut result = Vec::with_capacity(data.len() / 2 + 8);
+    compress_raw_into(&mut matcher, data, &mut result);
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Raw Snappy compression complete: original_size={}, compressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    result
+}
+
+/// Matches stay within SNAPPY_BLOCK_SIZE fragments, as in the reference
+/// encoder, so every offset fits a 2-byte copy.
+fn compress_raw_into(matcher: &mut BlockMatcher, data: &[u8], out: &mut Vec<u8>) {
+    write_varint(out, data.len() as u64);
+    for fragment in data.chunks(SNAPPY_BLOCK_SIZE) {
+        let mut anchor = 0;
+        if fragment.len() >= MIN_MATCH {
+            let last_start = fragment.len() - MIN_MATCH;
+            anchor = matcher.parse(fragment, last_start, fragment.len(), |sequence| {
+                write_literal(out, &fragment[sequence.literals]);
+                write_copy(out, sequence.offset, sequence.length);
+            });
+        }
+        write_literal(out, &fragment[anchor..]);
+    }
+}
+
+fn write_literal(out: &mut Vec<u8>, literal: &[u8]) {
+    if literal.is_empty() {
+        return;
+    }
+    let n = literal.len() - 1;
+    if n < 60 {
+        out.push((n as u8) << 2);
+    } else {
+        // Tags 60-63 say the length follows in 1-4 bytes
+        let bytes = (usize::BITS - n.leading_zeros()).div_ceil(8) as usize;
+        out.push((59 + bytes as u8) << 2);
+        out.extend_from_slice(&(n as u32).to_le_bytes()[..bytes]);
+    }
+    out.extend_from_slice(literal);
+}
+
+/// Splits a match into copies of at most 64 bytes, keeping the last at
+/// least 4 bytes long, and uses the short form where it fits.
+fn write_copy(out: &mut Vec<u8>, offset: usize, mut length: usize) {
+    while length >= 68 {
+        write_copy2(out, offset, 64);
+        length -= 64;
+    }
+    if length > 64 {
+        write_copy2(out, offset, 60);
+        length -= 60;
+    }
+    if length < 12 && offset < 2048 {
+        out.push(0x01 | ((length - 4) as u8) << 2 | ((offset >> 8) as u8) << 5);
+        out.push(offset as u8);
+    } else {
+        write_copy2(out, offset, length);
+    }
+}
+
+fn write_copy2(out: &mut Vec<u8>, offset: usize, length: usize) {
+    out.push(0x02 | ((length - 1) as u8) << 2);
+    out.extend_from_slice(&(offset as u16).to_le_bytes());
+}
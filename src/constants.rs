@@ -1,18 +1,38 @@
 pub const WINDOW_SIZE: usize = 4096; // 4KB
 pub const MIN_MATCH_LEN: usize = 3;   // Reduced from 4 to catch more matches
 pub const MAX_MATCH_LEN: usize = 255; // 1 byte for length
+pub const LZ_DICTIONARY_MARKER: u8 = 0xFD; // Precedes an LZ stream whose matches may use the static dictionary
+pub const LZ_FSE_HEADER: u8 = 0xFE; // In place of the tree byte, when the token stream is FSE coded
+pub const LZ_HUFFMAN_HEADER: u8 = 0xFC; // In place of the tree byte, when the tokens are Huffman coded
 pub const UNCOMPRESSED_FLAG: u8 = 0xAA; // 10101010
 pub const COMPRESSED_FLAG: u8 = 0xBB; // 10111011
 pub const RLE_FLAG: u8 = 0xCC; // 11001100
 pub const DELTA_FLAG: u8 = 0xDD; // 11011101
 pub const BWT_FLAG: u8 = 0xEE; // 11101110
 pub const CHUNKED_FLAG: u8 = 0xFF; // 11111111
+pub const NUMPACK_FLAG: u8 = 0x99; // 10011001
+pub const RECORD_FLAG: u8 = 0x88; // 10001000
+pub const LZ4_FLAG: u8 = 0x04; // First byte of LZ4_MAGIC; LZ4 streams are bare frames
+pub const FSE_FLAG: u8 = 0x77; // 01110111
+pub const CM_FLAG: u8 = 0x66; // 01100110
+pub const LONG_RANGE_FLAG: u8 = 0x55; // 01010101
 
 pub const ALGO_UNCOMPRESSED: &str = "Uncompressed";
+pub const ALGO_BEST: &str = "best";
 pub const ALGO_LZ_HUFFMAN: &str = "LZ+Huffman";
 pub const ALGO_RLE: &str = "RLE";
 pub const ALGO_DELTA: &str = "Delta";
 pub const ALGO_BWT: &str = "bwt";
+pub const ALGO_NUMPACK: &str = "NumPack";
+pub const ALGO_RECORD: &str = "Record";
+pub const ALGO_LZ4: &str = "LZ4";
+pub const ALGO_SNAPPY: &str = "Snappy";
+pub const ALGO_FSE: &str = "FSE";
+pub const ALGO_CM: &str = "CM";
+pub const ALGO_LONG_RANGE: &str = "Long-range";
+
+pub const CHUNK_MODE_INDEPENDENT: &str = "independent";
+pub const CHUNK_MODE_DEPENDENT: &str = "dependent";
 
 pub const LOG_LEVEL_NONE: &str = "none";
 pub const LOG_LEVEL_ERROR: &str = "error";
@@ -20,4 +40,38 @@ pub const LOG_LEVEL_INFO: &str = "info";
 pub const LOG_LEVEL_DEBUG: &str = "debug";
 pub const LOG_LEVEL_PERFORMANCE: &str = "performance";
 
-pub const MIN_FILE_SIZE: usize = 64;  // Don't compress files smaller than this
\ No newline at end of file
+pub const LONG_RANGE_MIN_MATCH: usize = 64; // Shortest repeat the long-range pre-pass replaces
+
+pub const CHUNK_DEPENDS_ON_PREVIOUS: u8 = 0x80; // Set in a chunk's methods count when its first-stage LZ copies from the previous chunk
+
+pub const MIN_FILE_SIZE: usize = 64;  // Don't compress files smaller than this
+
+pub const DEFAULT_MAX_OUTPUT_SIZE: usize = 256 * 1024 * 1024; // Decompressed size allowed by default
+
+pub const MIN_LEVEL: u8 = 1;
+pub const MAX_LEVEL: u8 = 9;
+pub const DEFAULT_LEVEL: u8 = 6;
+
+pub const DELTA_HEADER_SIZE: usize = 3; // [stride: u16][width: u8]
+pub const DELTA_SAMPLE_SIZE: usize = 16 * 1024; // Bytes examined when detecting the stride
+pub const MAX_RECORD_WIDTH: usize = 256; // Largest record width tried by autocorrelation
+
+pub const NUMPACK_MARKER: u8 = 0xF8; // Never valid in UTF-8, so text rarely needs escaping
+pub const NUMPACK_MAX_DIGITS: usize = 18; // Digits per number token, keeps the mantissa within i64
+
+pub const RECORD_HEADER_SIZE: usize = 3; // [width: u16][mode: u8]
+pub const RECORD_MODE_XOR: u8 = 0;
+pub const RECORD_MODE_SUB: u8 = 1;
+
+pub const LZ4_MAGIC: u32 = 0x184D_2204;
+pub const LZ4_SKIPPABLE_MAGIC: u32 = 0x184D_2A50; // Low nibble is free, so 16 magics in all
+pub const LZ4_BLOCK_SIZE: usize = 4 * 1024 * 1024; // Block size written, the reference default
+pub const LZ4_MIN_MATCH: usize = 4;
+pub const LZ4_MAX_OFFSET: usize = 65535;
+pub const LZ4_LAST_LITERALS: usize = 5; // A block ends with at least this many literals
+pub const LZ4_MF_LIMIT: usize = 12; // Last match starts at least this far from the block end
+
+// Framed Snappy streams start with CHUNKED_FLAG, so only the whole identifier
+// tells them apart from chunked streams
+pub const SNAPPY_STREAM_IDENTIFIER: [u8; 10] = *b"\xFF\x06\x00\x00sNaPpY";
+pub const SNAPPY_BLOCK_SIZE: usize = 64 * 1024; // Largest uncompressed chunk, and the raw fragment size
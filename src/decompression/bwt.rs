@@ -1,13 +1,19 @@
-use crate::constants::{LOG_LEVEL_DEBUG, LOG_LEVEL_ERROR, LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE};
-use crate::utils::log_message;
+use crate::decompression::limit::OutputLimit;
+use crate::shared::error::Error;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
 
-pub fn decompress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
-    log_message(LOG_LEVEL_INFO, log_level, "Starting BWT decompression", verbose);
+pub fn decompress_bwt(
+    input: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    log_message!(logger, LogLevel::Info, "Starting BWT decompression");
 
     // Return early if input is too small
     if input.len() <= 4 {
-        log_message(LOG_LEVEL_DEBUG, log_level, "Input too small for BWT decompression", verbose);
-        return input.to_vec();
+        log_message!(logger, LogLevel::Debug, "Input too small for BWT decompression");
+        return Ok(input.to_vec());
     }
 
     // Read original index
@@ -16,8 +22,8 @@ pub fn decompress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
 
     // Safety check for empty compressed data
     if compressed.is_empty() {
-        log_message(LOG_LEVEL_ERROR, log_level, "Compressed data is empty", verbose);
-        return input.to_vec();
+        log_message!(logger, LogLevel::Error, "Compressed data is empty");
+        return Ok(input.to_vec());
     }
 
     // Reverse RLE
@@ -26,22 +32,17 @@ pub fn decompress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
     while i < compressed.len() - 1 {
         let count = compressed[i] as usize;
         let value = compressed[i + 1];
-        
-        // Prevent potential overflow
-        if mtf_data.len() + count > mtf_data.capacity() {
-            mtf_data.reserve(count);
-        }
-        
-        for _ in 0..count {
-            mtf_data.push(value);
-        }
+
+        // The runs expand to the final output size, so cap them as they grow
+        limit.check(mtf_data.len() + count)?;
+        mtf_data.extend(core::iter::repeat_n(value, count));
         i += 2;
     }
 
     // Safety check for empty MTF data
     if mtf_data.is_empty() {
-        log_message(LOG_LEVEL_ERROR, log_level, "MTF data is empty", verbose);
-        return input.to_vec();
+        log_message!(logger, LogLevel::Error, "MTF data is empty");
+        return Ok(input.to_vec());
     }
 
     // Reverse Move-To-Front transform
@@ -51,8 +52,8 @@ pub fn decompress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
     for &pos in &mtf_data {
         let pos_usize = pos as usize;
         if pos_usize >= mtf.len() {
-            log_message(LOG_LEVEL_ERROR, log_level, "Invalid MTF index", verbose);
-            return input.to_vec(); // Invalid MTF index
+            log_message!(logger, LogLevel::Error, "Invalid MTF index");
+            return Ok(input.to_vec()); // Invalid MTF index
         }
         let byte = mtf[pos_usize];
         bwt_data.push(byte);
@@ -62,14 +63,14 @@ pub fn decompress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
 
     // Safety check for empty BWT data
     if bwt_data.is_empty() {
-        log_message(LOG_LEVEL_ERROR, log_level, "BWT data is empty", verbose);
-        return input.to_vec();
+        log_message!(logger, LogLevel::Error, "BWT data is empty");
+        return Ok(input.to_vec());
     }
 
     // Validate original index
     if original_idx >= bwt_data.len() {
-        log_message(LOG_LEVEL_ERROR, log_level, "Invalid original index in BWT decompression", verbose);
-        return input.to_vec();
+        log_message!(logger, LogLevel::Error, "Invalid original index in BWT decompression");
+        return Ok(input.to_vec());
     }
 
     // Reverse BWT
@@ -84,8 +85,8 @@ pub fn decompress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
     let mut safety_counter = 0;
     while result.len() < n && safety_counter < n {
         if idx >= table.len() {
-            log_message(LOG_LEVEL_ERROR, log_level, "Invalid index during BWT decompression", verbose);
-            return input.to_vec(); // Invalid index
+            log_message!(logger, LogLevel::Error, "Invalid index during BWT decompression");
+            return Ok(input.to_vec()); // Invalid index
         }
         let (byte, next_idx) = table[idx];
         result.push(byte);
@@ -95,20 +96,17 @@ pub fn decompress_bwt(input: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
 
     // Check if we got all the data
     if result.len() != n {
-        log_message(LOG_LEVEL_ERROR, log_level, "Incomplete BWT decompression", verbose);
-        return input.to_vec();
+        log_message!(logger, LogLevel::Error, "Incomplete BWT decompression");
+        return Ok(input.to_vec());
     }
 
-    log_message(
-        LOG_LEVEL_PERFORMANCE,
-        log_level,
-        &format!(
-            "BWT decompression complete: original_size={}, decompressed_size={}",
-            input.len(),
-            result.len()
-        ),
-        verbose,
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "BWT decompression complete: original_size={}, decompressed_size={}",
+        input.len(),
+        result.len()
     );
 
-    result
+    Ok(result)
 }
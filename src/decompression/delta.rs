@@ -1,36 +1,58 @@
-use crate::constants::{LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE};
-use crate::utils::log_message;
+use crate::constants::DELTA_HEADER_SIZE;
+use crate::shared::delta::{is_valid_layout, read_element, write_element};
+use crate::decompression::limit::OutputLimit;
+use crate::shared::error::Error;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
 
-pub fn decompress_delta(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
+pub fn decompress_delta(
+    data: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
     if data.is_empty() {
-        return Vec::new();
+        return Ok(Vec::new());
     }
 
-    log_message(
-        LOG_LEVEL_INFO,
-        log_level,
-        "Starting Delta decompression",
-        verbose,
-    );
+    log_message!(logger, LogLevel::Info, "Starting Delta decompression");
+
+    if data.len() < DELTA_HEADER_SIZE {
+        log_message!(logger, LogLevel::Error, "Delta header is truncated");
+        return Ok(data.to_vec());
+    }
 
-    let mut result = Vec::with_capacity(data.len());
-    result.push(data[0]);
+    // Read layout header
+    let stride = u16::from_le_bytes([data[0], data[1]]) as usize;
+    let width = data[2] as usize;
+    if !is_valid_layout(stride, width) {
+        log_message!(
+            logger,
+            LogLevel::Error,
+            "Invalid delta layout: stride={}, width={}",
+            stride,
+            width
+        );
+        return Ok(data.to_vec());
+    }
 
-    for &delta in &data[1..] {
-        let next = result.last().unwrap().wrapping_add(delta);
-        result.push(next);
+    // Elements are restored front to back so each predecessor is already decoded
+    limit.check(data.len() - DELTA_HEADER_SIZE)?;
+    let mut result = data[DELTA_HEADER_SIZE..].to_vec();
+    let mut pos = stride;
+    while pos + width <= result.len() {
+        let delta = read_element(&result[pos..pos + width]);
+        let previous = read_element(&result[pos - stride..pos - stride + width]);
+        write_element(&mut result[pos..pos + width], previous.wrapping_add(delta));
+        pos += width;
     }
 
-    log_message(
-        LOG_LEVEL_PERFORMANCE,
-        log_level,
-        &format!(
-            "Delta decompression complete: original_size={}, decompressed_size={}",
-            data.len(),
-            result.len()
-        ),
-        verbose,
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Delta decompression complete: original_size={}, decompressed_size={}",
+        data.len(),
+        result.len()
     );
 
-    result
+    Ok(result)
 }
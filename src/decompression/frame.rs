@@ -0,0 +1,33 @@
+use crate::shared::error::Error;
+use alloc::vec::Vec;
+
+// Pieces shared by the decoders of foreign stream formats, LZ4 and Snappy.
+
+/// Why a frame can't be decoded: it is malformed, so the input is returned
+/// unchanged, or its output would break a limit, which is passed on.
+pub(crate) enum FrameError {
+    Malformed(&'static str),
+    Limit(Error),
+}
+
+impl From<Error> for FrameError {
+    fn from(err: Error) -> Self {
+        FrameError::Limit(err)
+    }
+}
+
+pub(crate) type FrameResult<T> = Result<T, FrameError>;
+
+/// Appends `length` bytes copied from `offset` bytes back, which must be
+/// within `out`. A copy longer than its offset overlaps itself and repeats
+/// the last `offset` bytes.
+pub(crate) fn copy_match(out: &mut Vec<u8>, offset: usize, length: usize) {
+    let from = out.len() - offset;
+    if offset >= length {
+        out.extend_from_within(from..from + length);
+    } else {
+        for k in 0..length {
+            out.push(out[from + k]);
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use crate::decompression::limit::OutputLimit;
+use crate::entropy::fse;
+use crate::shared::error::Error;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+pub fn decompress_fse(
+    data: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    log_message!(logger, LogLevel::Info, "Starting FSE decompression");
+
+    let Some(result) = fse::decode(data, |len| limit.check(len))? else {
+        log_message!(logger, LogLevel::Error, "Invalid FSE stream");
+        return Ok(data.to_vec());
+    };
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "FSE decompression complete: original_size={}, decompressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    Ok(result)
+}
@@ -1,15 +1,170 @@
-use crate::constants::{LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE};
-use crate::shared::token::Token;
-use crate::utils::log_message;
-use std::convert::TryInto;
-
-pub fn decode_huffman(data: &[u8], log_level: &str, verbose: bool) -> Vec<Token> {
-    log_message(
-        LOG_LEVEL_INFO,
-        log_level,
-        "Starting Huffman decoding",
-        verbose,
-    );
+use crate::constants::MIN_MATCH_LEN;
+use crate::shared::bits::BitReader;
+use crate::shared::huffman::{
+    length_base, offset_base, END_OF_STREAM, FIRST_DICTIONARY_SYMBOL, FIRST_WINDOW_SYMBOL,
+    LITERAL_LENGTH_SYMBOLS, MAX_CODE_LEN, OFFSET_SYMBOLS,
+};
+use crate::shared::token::{RecentOffsets, Token};
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Tokens of a Huffman-coded stream, as written by `encode_huffman`,
+/// decoded as they are read. Iteration stops at the end of the stream or at
+/// the first malformed token, which sets `malformed`.
+pub struct HuffmanTokens<'a> {
+    reader: BitReader<'a>,
+    literal_lengths: DecodeTable,
+    offsets: DecodeTable,
+    recent: RecentOffsets,
+    done: bool,
+    pub malformed: bool,
+}
+
+impl<'a> HuffmanTokens<'a> {
+    /// Reads the code tables, or returns `None` if they are malformed.
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let mut reader = BitReader::new(data);
+        let literal_lengths = DecodeTable::read(&mut reader, LITERAL_LENGTH_SYMBOLS)?;
+        let offsets = DecodeTable::read(&mut reader, OFFSET_SYMBOLS)?;
+        Some(HuffmanTokens {
+            reader,
+            literal_lengths,
+            offsets,
+            recent: RecentOffsets::default(),
+            done: false,
+            malformed: false,
+        })
+    }
+
+    /// The next token, `Some(None)` at the end of the stream, or `None` if
+    /// the bits run out or don't form a token.
+    fn read_token(&mut self) -> Option<Option<Token>> {
+        let symbol = self.literal_lengths.decode(&mut self.reader)?;
+        if symbol < END_OF_STREAM {
+            return Some(Some(Token::Literal(symbol as u8)));
+        }
+        if symbol == END_OF_STREAM {
+            return Some(None);
+        }
+
+        let (base, extra_bits) = length_base(symbol);
+        let length = MIN_MATCH_LEN as u32 + base + self.reader.read(extra_bits)?;
+        let symbol = self.offsets.decode(&mut self.reader)?;
+        if symbol >= FIRST_DICTIONARY_SYMBOL {
+            let (base, extra_bits) = offset_base(symbol);
+            let position = base + self.reader.read(extra_bits)?;
+            return Some(Some(Token::DictMatch(position as u16, length as u16)));
+        }
+        let offset = if symbol < FIRST_WINDOW_SYMBOL {
+            self.recent.get(symbol)
+        } else {
+            let (base, extra_bits) = offset_base(symbol);
+            (base + self.reader.read(extra_bits)?) as u16
+        };
+        self.recent.push(offset);
+        Some(Some(Token::Match(offset, length as u16)))
+    }
+}
+
+impl Iterator for HuffmanTokens<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        if self.done {
+            return None;
+        }
+        match self.read_token() {
+            Some(Some(token)) => Some(token),
+            end => {
+                self.done = true;
+                // Only padding may follow the end of the stream
+                self.malformed = end.is_none() || !self.reader.at_end();
+                None
+            }
+        }
+    }
+}
+
+/// Canonical code lengths of an alphabet, as counted for decoding.
+struct DecodeTable {
+    /// Codes of each length.
+    counts: [u16; MAX_CODE_LEN as usize + 1],
+    /// Symbols in code order: by length, then by symbol.
+    symbols: Vec<u16>,
+}
+
+impl DecodeTable {
+    /// Reads the code lengths of an alphabet of `symbols`, and rejects them
+    /// unless they form a prefix code. An incomplete code is allowed, since
+    /// an alphabet with a single symbol has one.
+    fn read(reader: &mut BitReader, symbols: usize) -> Option<Self> {
+        let mut lengths = Vec::with_capacity(symbols);
+        while lengths.len() < symbols {
+            let length = reader.read(4)? as u8;
+            lengths.push(length);
+            if length == 0 {
+                let zeros = reader.read_gamma()? as usize - 1;
+                if zeros > symbols - lengths.len() {
+                    return None;
+                }
+                lengths.resize(lengths.len() + zeros, 0);
+            }
+        }
+
+        let mut counts = [0u16; MAX_CODE_LEN as usize + 1];
+        for &length in &lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return None;
+            }
+        }
+
+        let mut starts = [0usize; MAX_CODE_LEN as usize + 2];
+        for length in 1..=MAX_CODE_LEN as usize {
+            starts[length + 1] = starts[length] + counts[length] as usize;
+        }
+        let mut ordered = vec![0u16; starts[MAX_CODE_LEN as usize + 1]];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length > 0 {
+                ordered[starts[length as usize]] = symbol as u16;
+                starts[length as usize] += 1;
+            }
+        }
+        Some(DecodeTable {
+            counts,
+            symbols: ordered,
+        })
+    }
+
+    /// Reads a code a bit at a time, highest bit first. Within a length,
+    /// canonical codes are consecutive, so one comparison per length tells
+    /// whether the code read so far is complete.
+    fn decode(&self, reader: &mut BitReader) -> Option<usize> {
+        let (mut code, mut first, mut index) = (0usize, 0usize, 0usize);
+        for &count in &self.counts[1..] {
+            code |= reader.read_bit()? as usize;
+            let count = count as usize;
+            if code - first < count {
+                return Some(self.symbols[index + code - first] as usize);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+/// Decodes tokens laid out by `encode_tokens`, stopping at the first that is
+/// cut short or has an unknown tag.
+pub fn decode_token_bytes(data: &[u8], logger: &dyn Logger) -> Vec<Token> {
+    log_message!(logger, LogLevel::Info, "Starting token decoding");
 
     let mut tokens = Vec::new();
     let mut i = 0;
@@ -24,12 +179,16 @@ pub fn decode_huffman(data: &[u8], log_level: &str, verbose: bool) -> Vec<Token>
                     i += 1;
                 }
             }
-            1 => {
-                // Match(offset, length)
+            1 | 2 => {
+                // Match(offset, length) or DictMatch(position, length)
                 if i + 3 < data.len() {
-                    let offset = u16::from_le_bytes(data[i..i + 2].try_into().unwrap());
+                    let first = u16::from_le_bytes(data[i..i + 2].try_into().unwrap());
                     let length = u16::from_le_bytes(data[i + 2..i + 4].try_into().unwrap());
-                    tokens.push(Token::Match(offset, length));
+                    tokens.push(if tag == 1 {
+                        Token::Match(first, length)
+                    } else {
+                        Token::DictMatch(first, length)
+                    });
                     i += 4;
                 } else {
                     break;
@@ -42,15 +201,12 @@ pub fn decode_huffman(data: &[u8], log_level: &str, verbose: bool) -> Vec<Token>
         }
     }
 
-    log_message(
-        LOG_LEVEL_PERFORMANCE,
-        log_level,
-        &format!(
-            "Huffman decoding complete: original_size={}, decoded_size={}",
-            data.len(),
-            tokens.len()
-        ),
-        verbose,
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Token decoding complete: original_size={}, decoded_size={}",
+        data.len(),
+        tokens.len()
     );
 
     tokens
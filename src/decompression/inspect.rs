@@ -0,0 +1,143 @@
+use crate::constants::{
+    ALGO_BWT, ALGO_CM, ALGO_DELTA, ALGO_FSE, ALGO_LONG_RANGE, ALGO_LZ4, ALGO_LZ_HUFFMAN,
+    ALGO_NUMPACK, ALGO_RECORD, ALGO_RLE, ALGO_SNAPPY, ALGO_UNCOMPRESSED, BWT_FLAG, CHUNKED_FLAG,
+    CHUNK_DEPENDS_ON_PREVIOUS, CM_FLAG, COMPRESSED_FLAG, DELTA_FLAG, FSE_FLAG, LONG_RANGE_FLAG,
+    LZ4_FLAG, LZ4_MAGIC, NUMPACK_FLAG, RECORD_FLAG, RLE_FLAG, SNAPPY_STREAM_IDENTIFIER,
+    UNCOMPRESSED_FLAG,
+};
+use crate::decompression::long_range::read_header;
+use alloc::vec::Vec;
+
+/// Header information of a compressed stream, read without decoding it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamInfo {
+    /// Leading flag byte selecting the top-level method.
+    pub flag: u8,
+    /// Decompressed size, when the header records it.
+    pub original_size: Option<usize>,
+    /// Per-chunk layout for chunked streams; empty otherwise.
+    pub chunks: Vec<ChunkInfo>,
+}
+
+/// One chunk of a chunked stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkInfo {
+    pub compressed_size: usize,
+    /// Methods in the order they were applied.
+    pub methods: Vec<u8>,
+    /// Whether the chunk's LZ copies from the end of the previous chunk, so
+    /// it can't be decoded without decoding that one first. Random
+    /// access or parallel decoding must refuse such chunks.
+    pub depends_on_previous: bool,
+}
+
+/// Reads the headers of `input`. Returns `None` for an empty input or a
+/// chunked or long-range stream whose headers are truncated.
+pub fn inspect(input: &[u8]) -> Option<StreamInfo> {
+    let (&flag, data) = input.split_first()?;
+
+    match flag {
+        UNCOMPRESSED_FLAG => Some(StreamInfo {
+            flag,
+            original_size: Some(data.len()),
+            chunks: Vec::new(),
+        }),
+        // Snappy streams don't record their size
+        CHUNKED_FLAG if input.starts_with(&SNAPPY_STREAM_IDENTIFIER) => Some(StreamInfo {
+            flag,
+            original_size: None,
+            chunks: Vec::new(),
+        }),
+        CHUNKED_FLAG => {
+            let total_chunks = read_u32(data, 0)?;
+            let total_size = read_u32(data, 4)?;
+            let mut chunks = Vec::new();
+            let mut pos = 8;
+
+            for _ in 0..total_chunks {
+                let compressed_size = read_u32(data, pos)?;
+                let methods_byte = *data.get(pos + 4)?;
+                let methods_count = (methods_byte & !CHUNK_DEPENDS_ON_PREVIOUS) as usize;
+                pos += 5;
+                let methods = data.get(pos..pos + methods_count)?.to_vec();
+                pos += methods_count;
+                if pos + compressed_size > data.len() {
+                    return None;
+                }
+                pos += compressed_size;
+                chunks.push(ChunkInfo {
+                    compressed_size,
+                    methods,
+                    depends_on_previous: methods_byte & CHUNK_DEPENDS_ON_PREVIOUS != 0,
+                });
+            }
+
+            Some(StreamInfo {
+                flag,
+                original_size: Some(total_size),
+                chunks,
+            })
+        }
+        // The size is the restored one; chunks are those of the residual stream
+        LONG_RANGE_FLAG => {
+            let header = read_header(data)?;
+            Some(StreamInfo {
+                flag,
+                original_size: Some(header.original_len),
+                chunks: inspect(header.inner)?.chunks,
+            })
+        }
+        // Frames from other writers may leave the content size out
+        LZ4_FLAG if read_u32(input, 0) == Some(LZ4_MAGIC as usize) => Some(StreamInfo {
+            flag,
+            original_size: match data.get(3) {
+                Some(flg) if flg & 0x08 != 0 => read_u64(data, 5),
+                _ => None,
+            },
+            chunks: Vec::new(),
+        }),
+        _ => Some(StreamInfo {
+            flag,
+            original_size: None,
+            chunks: Vec::new(),
+        }),
+    }
+}
+
+/// Name of the algorithm behind a flag or chain method byte.
+pub fn method_name(flag: u8) -> Option<&'static str> {
+    match flag {
+        UNCOMPRESSED_FLAG => Some(ALGO_UNCOMPRESSED),
+        COMPRESSED_FLAG => Some(ALGO_LZ_HUFFMAN),
+        RLE_FLAG => Some(ALGO_RLE),
+        DELTA_FLAG => Some(ALGO_DELTA),
+        BWT_FLAG => Some(ALGO_BWT),
+        NUMPACK_FLAG => Some(ALGO_NUMPACK),
+        RECORD_FLAG => Some(ALGO_RECORD),
+        FSE_FLAG => Some(ALGO_FSE),
+        CM_FLAG => Some(ALGO_CM),
+        LONG_RANGE_FLAG => Some(ALGO_LONG_RANGE),
+        LZ4_FLAG => Some(ALGO_LZ4),
+        CHUNKED_FLAG => Some("Chunked"),
+        _ => None,
+    }
+}
+
+/// Name of the top-level method of `input`. Unlike `method_name` of its
+/// flag, this tells Snappy streams apart from chunked ones.
+pub fn stream_name(input: &[u8]) -> Option<&'static str> {
+    if input.starts_with(&SNAPPY_STREAM_IDENTIFIER) {
+        return Some(ALGO_SNAPPY);
+    }
+    method_name(*input.first()?)
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<usize> {
+    let bytes = data.get(pos..pos + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+}
+
+fn read_u64(data: &[u8], pos: usize) -> Option<usize> {
+    let bytes = data.get(pos..pos + 8)?;
+    usize::try_from(u64::from_le_bytes(bytes.try_into().unwrap())).ok()
+}
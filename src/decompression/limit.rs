@@ -0,0 +1,56 @@
+use crate::shared::error::Error;
+use crate::shared::options::DecompressOptions;
+
+// Output reserved up front, as a multiple of the input
+const MAX_PREALLOCATION_RATIO: usize = 4;
+
+/// Capacity to reserve for an output that a stream of `input_len` bytes
+/// claims is `len` bytes long. The claim is untrusted, so this is at most a
+/// small multiple of the input; an honest output grows past it as decoded.
+pub(crate) fn untrusted_capacity(len: usize, input_len: usize) -> usize {
+    len.min(input_len.saturating_mul(MAX_PREALLOCATION_RATIO))
+}
+
+/// Output caps for one `decompress` call. Every decoder checks them before
+/// growing its output, including intermediate stages of a chunk's chain.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OutputLimit {
+    max_output_size: usize,
+    max_ratio: Option<f64>,
+    input_len: usize,
+}
+
+impl OutputLimit {
+    pub fn new(options: &DecompressOptions, input_len: usize) -> Self {
+        OutputLimit {
+            max_output_size: options.max_output_size,
+            max_ratio: options.max_ratio,
+            input_len,
+        }
+    }
+
+    /// Fails if an output of `len` bytes would break either cap.
+    pub fn check(&self, len: usize) -> Result<(), Error> {
+        if len > self.max_output_size {
+            return Err(Error::OutputTooLarge {
+                limit: self.max_output_size,
+            });
+        }
+        match self.max_ratio {
+            Some(ratio) if len as f64 > ratio * self.input_len as f64 => {
+                Err(Error::RatioExceeded { limit: ratio })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Largest output that passes `check`, for sizing up-front reservations.
+    pub fn max_len(&self) -> usize {
+        match self.max_ratio {
+            Some(ratio) => self
+                .max_output_size
+                .min((ratio * self.input_len as f64) as usize),
+            None => self.max_output_size,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::compression::long_range::LongMatch;
+use crate::constants::LONG_RANGE_FLAG;
+use crate::decompression::frame::copy_match;
+use crate::decompression::limit::{untrusted_capacity, OutputLimit};
+use crate::decompression::decompress_stream;
+use crate::shared::error::Error;
+use crate::shared::progress::Progress;
+use crate::shared::varint::read_varint;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+/// Header of a long-range stream, laid out as in `compression::long_range`.
+pub(crate) struct LongRangeHeader<'a> {
+    pub original_len: usize,
+    pub matches: Vec<LongMatch>,
+    /// The residual stream.
+    pub inner: &'a [u8],
+}
+
+/// Reads the header after the flag, or returns `None` if it is cut short.
+pub(crate) fn read_header(data: &[u8]) -> Option<LongRangeHeader<'_>> {
+    let mut pos = 0;
+    let mut read = || usize::try_from(read_varint(data, &mut pos)?).ok();
+    let original_len = read()?;
+    let count = read()?;
+    // Each match takes at least three bytes, so the count can't reserve more
+    let mut matches = Vec::with_capacity(count.min(data.len() / 3));
+    for _ in 0..count {
+        matches.push(LongMatch {
+            literals: read()?,
+            offset: read()?,
+            length: read()?,
+        });
+    }
+    Some(LongRangeHeader {
+        original_len,
+        matches,
+        inner: &data[pos..],
+    })
+}
+
+pub fn decompress_long_range(
+    data: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+) -> Result<Vec<u8>, Error> {
+    log_message!(logger, LogLevel::Info, "Starting long-range decompression");
+
+    // The residual is never itself a long-range stream, which also bounds
+    // the recursion on forged input
+    let header = match read_header(data) {
+        Some(header) if header.inner.first() != Some(&LONG_RANGE_FLAG) => header,
+        _ => {
+            log_message!(logger, LogLevel::Error, "Invalid long-range header");
+            return Ok(data.to_vec());
+        }
+    };
+    limit.check(header.original_len)?;
+
+    let residual = decompress_stream(header.inner, limit, logger, progress)?;
+    let Some(result) = restore(&residual, &header.matches, header.original_len, data.len()) else {
+        log_message!(
+            logger,
+            LogLevel::Error,
+            "Long-range matches don't fit the stream"
+        );
+        return Ok(data.to_vec());
+    };
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Long-range decompression complete: original_size={}, decompressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    Ok(result)
+}
+
+/// Interleaves the residual with the matches, or returns `None` if a match
+/// reaches back before the start or the output isn't `original_len` bytes.
+fn restore(
+    residual: &[u8],
+    matches: &[LongMatch],
+    original_len: usize,
+    input_len: usize,
+) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(untrusted_capacity(original_len, input_len));
+    let mut literals = residual;
+    for found in matches {
+        let (before, rest) = literals.split_at_checked(found.literals)?;
+        output.extend_from_slice(before);
+        literals = rest;
+
+        if found.offset == 0
+            || found.offset > output.len()
+            || found.length > original_len.saturating_sub(output.len())
+        {
+            return None;
+        }
+        copy_match(&mut output, found.offset, found.length);
+    }
+    output.extend_from_slice(literals);
+    (output.len() == original_len).then_some(output)
+}
@@ -0,0 +1,226 @@
+use crate::constants::{LZ4_MAGIC, LZ4_MAX_OFFSET, LZ4_MIN_MATCH, LZ4_SKIPPABLE_MAGIC};
+use crate::decompression::frame::{copy_match, FrameError, FrameResult};
+use crate::decompression::limit::{untrusted_capacity, OutputLimit};
+use crate::shared::error::Error;
+use crate::shared::xxhash::xxh32;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+// Reads LZ4 frames as written by `compress_lz4` or the reference `lz4`
+// tool: linked or independent blocks, any block size, optional block and
+// content checksums, and further frames or skippable frames after the first.
+// Frames with a dictionary ID are not supported.
+
+/// Decodes `input`, which starts at the frame magic. Malformed or corrupt
+/// frames are returned unchanged, like other undecodable input.
+pub fn decompress_lz4(
+    input: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    log_message!(logger, LogLevel::Info, "Starting LZ4 decompression");
+
+    let mut result = Vec::new();
+    let mut pos = 0;
+    while pos < input.len() {
+        let step = match read_u32(input, pos) {
+            Some(LZ4_MAGIC) => decode_frame(input, pos, &mut result, limit),
+            Some(magic) if magic & 0xFFFF_FFF0 == LZ4_SKIPPABLE_MAGIC => {
+                match read_u32(input, pos + 4) {
+                    Some(size) => Ok((size as usize).saturating_add(8)),
+                    None => Err(FrameError::Malformed("truncated skippable frame")),
+                }
+            }
+            _ => Err(FrameError::Malformed("unknown frame magic")),
+        };
+        match step {
+            Ok(length) => pos = pos.saturating_add(length),
+            Err(FrameError::Limit(err)) => return Err(err),
+            Err(FrameError::Malformed(reason)) => {
+                log_message!(logger, LogLevel::Error, "Invalid LZ4 frame: {}", reason);
+                return Ok(input.to_vec());
+            }
+        }
+    }
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "LZ4 decompression complete: original_size={}, decompressed_size={}",
+        input.len(),
+        result.len()
+    );
+
+    Ok(result)
+}
+
+/// Appends the frame at `start` to `out`, returning its length in bytes.
+fn decode_frame(
+    input: &[u8],
+    start: usize,
+    out: &mut Vec<u8>,
+    limit: &OutputLimit,
+) -> FrameResult<usize> {
+    let [flg, bd] = match input.get(start + 4..start + 6) {
+        Some(&[flg, bd]) => [flg, bd],
+        _ => return Err(FrameError::Malformed("truncated frame")),
+    };
+    if flg >> 6 != 1 || flg & 0x02 != 0 || bd & 0x8F != 0 {
+        return Err(FrameError::Malformed("unsupported frame descriptor"));
+    }
+    if flg & 0x01 != 0 {
+        return Err(FrameError::Malformed("dictionary IDs are not supported"));
+    }
+    let independent = flg & 0x20 != 0;
+    let block_checksum = flg & 0x10 != 0;
+    let has_content_size = flg & 0x08 != 0;
+    let content_checksum = flg & 0x04 != 0;
+    let block_max = match bd >> 4 {
+        id @ 4..=7 => 1 << (8 + 2 * id),
+        _ => return Err(FrameError::Malformed("unknown block size")),
+    };
+
+    let descriptor_end = start + 6 + if has_content_size { 8 } else { 0 };
+    let content_size = if has_content_size {
+        Some(read_u64(input, start + 6).ok_or(FrameError::Malformed("truncated frame"))?)
+    } else {
+        None
+    };
+    let header_checksum = *input
+        .get(descriptor_end)
+        .ok_or(FrameError::Malformed("truncated frame"))?;
+    if (xxh32(&input[start + 4..descriptor_end], 0) >> 8) as u8 != header_checksum {
+        return Err(FrameError::Malformed("header checksum mismatch"));
+    }
+
+    let frame_start = out.len();
+    if let Some(size) = content_size {
+        let size = usize::try_from(size).unwrap_or(usize::MAX);
+        limit.check(frame_start.saturating_add(size))?;
+        out.reserve(untrusted_capacity(size, input.len()));
+    }
+
+    let mut pos = descriptor_end + 1;
+    loop {
+        let header = read_u32(input, pos).ok_or(FrameError::Malformed("truncated block"))?;
+        pos += 4;
+        if header == 0 {
+            break;
+        }
+        let size = (header & 0x7FFF_FFFF) as usize;
+        if size > block_max {
+            return Err(FrameError::Malformed("block larger than the block size"));
+        }
+        let block = input
+            .get(pos..pos + size)
+            .ok_or(FrameError::Malformed("truncated block"))?;
+        pos += size;
+        if block_checksum {
+            let checksum = read_u32(input, pos).ok_or(FrameError::Malformed("truncated block"))?;
+            pos += 4;
+            if xxh32(block, 0) != checksum {
+                return Err(FrameError::Malformed("block checksum mismatch"));
+            }
+        }
+
+        if header & 0x8000_0000 != 0 {
+            limit.check(out.len() + block.len())?;
+            out.extend_from_slice(block);
+        } else {
+            let window_start = if independent { out.len() } else { frame_start };
+            decode_block(block, out, window_start, block_max, limit)?;
+        }
+    }
+
+    if content_checksum {
+        let checksum = read_u32(input, pos).ok_or(FrameError::Malformed("truncated frame"))?;
+        pos += 4;
+        if xxh32(&out[frame_start..], 0) != checksum {
+            return Err(FrameError::Malformed("content checksum mismatch"));
+        }
+    }
+    if content_size.is_some_and(|size| size != (out.len() - frame_start) as u64) {
+        return Err(FrameError::Malformed("content size mismatch"));
+    }
+    Ok(pos - start)
+}
+
+/// Appends one compressed block to `out`. Matches may reach back to
+/// `window_start`: the block's own start for independent blocks, the frame's
+/// for linked ones.
+fn decode_block(
+    block: &[u8],
+    out: &mut Vec<u8>,
+    window_start: usize,
+    block_max: usize,
+    limit: &OutputLimit,
+) -> FrameResult<()> {
+    let block_end = out.len() + block_max;
+    let mut i = 0;
+    loop {
+        let token = *block
+            .get(i)
+            .ok_or(FrameError::Malformed("truncated sequence"))?;
+        i += 1;
+
+        let literals = read_length(block, &mut i, (token >> 4) as usize)?;
+        let literal_bytes = block
+            .get(i..i + literals)
+            .ok_or(FrameError::Malformed("truncated literals"))?;
+        check_growth(out.len() + literals, block_end, limit)?;
+        out.extend_from_slice(literal_bytes);
+        i += literals;
+        if i == block.len() {
+            return Ok(());
+        }
+
+        let offset = block
+            .get(i..i + 2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+            .ok_or(FrameError::Malformed("truncated offset"))?;
+        i += 2;
+        if offset == 0 || offset > out.len() - window_start || offset > LZ4_MAX_OFFSET {
+            return Err(FrameError::Malformed("match offset out of range"));
+        }
+        let length = read_length(block, &mut i, (token & 0x0F) as usize)? + LZ4_MIN_MATCH;
+        check_growth(out.len() + length, block_end, limit)?;
+
+        copy_match(out, offset, length);
+    }
+}
+
+/// Adds the 255-continued extension bytes to a token nibble of 15.
+fn read_length(block: &[u8], i: &mut usize, nibble: usize) -> FrameResult<usize> {
+    let mut length = nibble;
+    if nibble == 15 {
+        loop {
+            let byte = *block
+                .get(*i)
+                .ok_or(FrameError::Malformed("truncated length"))?;
+            *i += 1;
+            length += byte as usize;
+            if byte != 255 {
+                break;
+            }
+        }
+    }
+    Ok(length)
+}
+
+fn check_growth(len: usize, block_end: usize, limit: &OutputLimit) -> FrameResult<()> {
+    if len > block_end {
+        return Err(FrameError::Malformed("block decodes past the block size"));
+    }
+    limit.check(len)?;
+    Ok(())
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    let bytes = data.get(pos..pos + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u64(data: &[u8], pos: usize) -> Option<u64> {
+    let bytes = data.get(pos..pos + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
@@ -1,36 +1,117 @@
-use crate::constants::{LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE, WINDOW_SIZE};
-use crate::decompression::huff_decode::decode_huffman;
-use crate::utils::log_message;
-
-pub fn decompress_lz_huffman(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
-    log_message(
-        LOG_LEVEL_INFO,
-        log_level,
-        "Starting LZ+Huffman decompression",
-        verbose,
-    );
+use crate::constants::{
+    LZ_DICTIONARY_MARKER, LZ_FSE_HEADER, LZ_HUFFMAN_HEADER, MAX_MATCH_LEN, WINDOW_SIZE,
+};
+use crate::decompression::huff_decode::{decode_token_bytes, HuffmanTokens};
+use crate::decompression::limit::OutputLimit;
+use crate::entropy::fse;
+use crate::shared::dictionary::STATIC_DICTIONARY;
+use crate::shared::error::Error;
+use crate::shared::token::Token;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+pub fn decompress_lz_huffman(
+    data: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    decompress_lz_with_history(&[], data, limit, logger)
+}
+
+/// `decompress_lz_huffman` of a stream whose matches may copy from
+/// `history`, the bytes before it, which are not part of the output.
+pub fn decompress_lz_with_history(
+    history: &[u8],
+    data: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    log_message!(logger, LogLevel::Info, "Starting LZ+Huffman decompression");
+
+    // Dictionary matches are only valid behind the marker
+    let (dictionary, stream) = match data.split_first() {
+        Some((&LZ_DICTIONARY_MARKER, rest)) => (true, rest),
+        _ => (false, data),
+    };
 
-    // 1. Decode the Huffman tree
-    //    In our placeholder logic, the tree is just 1 byte (0xFF).
-    //    Then the rest is the token stream.
-    let _tree_byte = data[0];
-    let token_data = &data[1..];
+    // 1. The header byte says how the tokens are coded; before Huffman
+    //    coding it was a placeholder tree of 1 byte (0xFF)
+    let Some((&header, token_data)) = stream.split_first() else {
+        log_message!(logger, LogLevel::Error, "LZ+Huffman stream is empty");
+        return Ok(Vec::new());
+    };
 
-    // 2. Decode tokens from the token stream
-    let tokens = decode_huffman(token_data, log_level, verbose);
+    // 2. Decode tokens and reconstruct the original bytes from them
+    let output = match header {
+        LZ_HUFFMAN_HEADER => {
+            let Some(mut tokens) = HuffmanTokens::new(token_data) else {
+                log_message!(logger, LogLevel::Error, "Invalid Huffman code tables");
+                return Ok(data.to_vec());
+            };
+            let output = reconstruct(&mut tokens, history, dictionary, limit, logger)?;
+            if tokens.malformed {
+                log_message!(logger, LogLevel::Error, "Invalid Huffman token stream");
+                return Ok(data.to_vec());
+            }
+            output
+        }
+        // Every token takes at most twice the bytes it expands to
+        LZ_FSE_HEADER => {
+            let Some(bytes) = fse::decode(token_data, |len| limit.check(len / 2))? else {
+                log_message!(logger, LogLevel::Error, "Invalid FSE token stream");
+                return Ok(data.to_vec());
+            };
+            let tokens = decode_token_bytes(&bytes, logger);
+            reconstruct(tokens, history, dictionary, limit, logger)?
+        }
+        _ => {
+            let tokens = decode_token_bytes(token_data, logger);
+            reconstruct(tokens, history, dictionary, limit, logger)?
+        }
+    };
 
-    // 3. Reconstruct original bytes from tokens
-    let mut output = Vec::with_capacity(WINDOW_SIZE);
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "LZ+Huffman decompression complete: original_size={}, decompressed_size={}",
+        data.len(),
+        output.len()
+    );
+
+    Ok(output)
+}
+
+/// Replays `tokens` after `history`, stopping at the first that the
+/// encoder could not have written.
+fn reconstruct(
+    tokens: impl IntoIterator<Item = Token>,
+    history: &[u8],
+    dictionary: bool,
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    // Matches reach at most WINDOW_SIZE back, so that much history is enough
+    let history = &history[history.len().saturating_sub(WINDOW_SIZE)..];
+    let mut output = Vec::with_capacity(history.len() + WINDOW_SIZE);
+    output.extend_from_slice(history);
     for t in tokens {
+        let written = output.len() - history.len();
         match t {
             // Just push the literal byte
-            crate::shared::token::Token::Literal(b) => {
+            Token::Literal(b) => {
+                limit.check(written + 1)?;
                 output.push(b);
             }
             // Copy data from existing output
-            crate::shared::token::Token::Match(offset, length) => {
+            Token::Match(offset, length) => {
                 let offset = offset as usize;
                 let length = length as usize;
+                // The encoder never emits these; stop rather than expand garbage
+                if offset == 0 || offset > WINDOW_SIZE || length > MAX_MATCH_LEN {
+                    log_message!(logger, LogLevel::Error, "Invalid match token");
+                    break;
+                }
+                limit.check(written + length)?;
                 let start = output.len().saturating_sub(offset);
                 for j in 0..length {
                     if start + j < output.len() {
@@ -41,19 +122,18 @@ pub fn decompress_lz_huffman(data: &[u8], log_level: &str, verbose: bool) -> Vec
                     }
                 }
             }
+            Token::DictMatch(position, length) => {
+                let start = position as usize;
+                let end = start + length as usize;
+                if !dictionary || length as usize > MAX_MATCH_LEN || end > STATIC_DICTIONARY.len() {
+                    log_message!(logger, LogLevel::Error, "Invalid dictionary match token");
+                    break;
+                }
+                limit.check(written + end - start)?;
+                output.extend_from_slice(&STATIC_DICTIONARY[start..end]);
+            }
         }
     }
-
-    log_message(
-        LOG_LEVEL_PERFORMANCE,
-        log_level,
-        &format!(
-            "LZ+Huffman decompression complete: original_size={}, decompressed_size={}",
-            data.len(),
-            output.len()
-        ),
-        verbose,
-    );
-
-    output
+    output.drain(..history.len());
+    Ok(output)
 }
@@ -1,27 +1,63 @@
 use crate::constants::{
-    ALGO_BWT, ALGO_DELTA, ALGO_LZ_HUFFMAN, ALGO_RLE, ALGO_UNCOMPRESSED, BWT_FLAG, CHUNKED_FLAG, COMPRESSED_FLAG, DELTA_FLAG, LOG_LEVEL_DEBUG, LOG_LEVEL_PERFORMANCE, RLE_FLAG, UNCOMPRESSED_FLAG
+    ALGO_BWT, ALGO_CM, ALGO_DELTA, ALGO_FSE, ALGO_LONG_RANGE, ALGO_LZ4, ALGO_LZ_HUFFMAN,
+    ALGO_NUMPACK, ALGO_RECORD, ALGO_RLE, ALGO_SNAPPY, ALGO_UNCOMPRESSED, BWT_FLAG, CHUNKED_FLAG,
+    CHUNK_DEPENDS_ON_PREVIOUS, CM_FLAG, COMPRESSED_FLAG, DELTA_FLAG, FSE_FLAG, LONG_RANGE_FLAG,
+    LZ4_FLAG, NUMPACK_FLAG, RECORD_FLAG, RLE_FLAG, SNAPPY_STREAM_IDENTIFIER, UNCOMPRESSED_FLAG,
 };
 mod bwt;
+mod cm;
 mod delta;
+mod frame;
+mod fse;
 mod huff_decode;
+pub(crate) mod inspect;
+pub(crate) mod limit;
+pub(crate) mod long_range;
+pub(crate) mod lz4;
 mod lz_huffman;
+mod numpack;
+mod record;
 mod rle;
+pub(crate) mod snappy;
 use crate::decompression::bwt::decompress_bwt;
+use crate::decompression::cm::decompress_cm;
 use crate::decompression::delta::decompress_delta;
-use crate::decompression::lz_huffman::decompress_lz_huffman;
+use crate::decompression::fse::decompress_fse;
+use crate::decompression::long_range::decompress_long_range;
+use crate::decompression::lz4::decompress_lz4;
+use crate::decompression::lz_huffman::{decompress_lz_huffman, decompress_lz_with_history};
+use crate::decompression::numpack::decompress_numpack;
+use crate::decompression::record::decompress_record;
 use crate::decompression::rle::decompress_rle;
-use crate::utils::{get_log_level, log_message};
-use wasm_bindgen::JsValue;
+use crate::decompression::snappy::decompress_snappy;
+use crate::shared::error::Error;
+use crate::shared::options::DecompressOptions;
+use crate::shared::progress::{check_cancelled, Progress};
+use limit::{untrusted_capacity, OutputLimit};
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+pub fn decompress(
+    input: &[u8],
+    options: &DecompressOptions,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+) -> Result<Vec<u8>, Error> {
+    let limit = OutputLimit::new(options, input.len());
+    decompress_stream(input, &limit, logger, progress)
+}
 
-pub fn decompress(input: &[u8], options: &JsValue) -> Vec<u8> {
-    let log_level = get_log_level(options);
-    let verbose = js_sys::Reflect::get(options, &JsValue::from_str("verbose"))
-        .ok()
-        .and_then(|val| val.as_bool())
-        .unwrap_or(false);
+/// `decompress` of a whole stream, flag first, under an existing limit.
+pub(crate) fn decompress_stream(
+    input: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+) -> Result<Vec<u8>, Error> {
+    check_cancelled(progress)?;
 
     if input.is_empty() {
-        return Vec::new();
+        return Ok(Vec::new());
     }
 
     let flag = input[0];
@@ -29,74 +65,92 @@ pub fn decompress(input: &[u8], options: &JsValue) -> Vec<u8> {
 
     match flag {
         UNCOMPRESSED_FLAG => {
-            log_message(
-                LOG_LEVEL_DEBUG,
-                &log_level,
-                &format!("Decompressing: {}", ALGO_UNCOMPRESSED),
-                verbose,
-            );
-            data.to_vec()
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_UNCOMPRESSED);
+            limit.check(data.len())?;
+            Ok(data.to_vec())
+        }
+        // The stream identifier starts with the chunked flag
+        CHUNKED_FLAG if input.starts_with(&SNAPPY_STREAM_IDENTIFIER) => {
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_SNAPPY);
+            decompress_snappy(input, limit, logger)
         }
         CHUNKED_FLAG => {
-            log_message(LOG_LEVEL_DEBUG, &log_level, "Decompressing: Chunked", verbose);
-            decompress_chunked(data, &log_level, verbose)
+            log_message!(logger, LogLevel::Debug, "Decompressing: Chunked");
+            decompress_chunked(data, limit, logger, progress)
         }
         COMPRESSED_FLAG => {
-            log_message(
-                LOG_LEVEL_DEBUG,
-                &log_level,
-                &format!("Decompressing: {}", ALGO_LZ_HUFFMAN),
-                verbose,
-            );
-            decompress_lz_huffman(data, &log_level, verbose)
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_LZ_HUFFMAN);
+            decompress_lz_huffman(data, limit, logger)
         }
         RLE_FLAG => {
-            log_message(
-                LOG_LEVEL_DEBUG,
-                &log_level,
-                &format!("Decompressing: {}", ALGO_RLE),
-                verbose,
-            );
-            decompress_rle(data, &log_level, verbose)
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_RLE);
+            decompress_rle(data, limit, logger)
         }
         DELTA_FLAG => {
-            log_message(
-                LOG_LEVEL_DEBUG,
-                &log_level,
-                &format!("Decompressing: {}", ALGO_DELTA),
-                verbose,
-            );
-            decompress_delta(data, &log_level, verbose)
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_DELTA);
+            decompress_delta(data, limit, logger)
         }
         BWT_FLAG => {
-            log_message(
-                LOG_LEVEL_DEBUG,
-                &log_level,
-                &format!("Decompressing: {}", ALGO_BWT),
-                verbose,
-            );
-            decompress_bwt(data, &log_level, verbose)
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_BWT);
+            decompress_bwt(data, limit, logger)
+        }
+        NUMPACK_FLAG => {
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_NUMPACK);
+            decompress_numpack(data, limit, logger)
+        }
+        RECORD_FLAG => {
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_RECORD);
+            decompress_record(data, limit, logger)
+        }
+        FSE_FLAG => {
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_FSE);
+            decompress_fse(data, limit, logger)
+        }
+        CM_FLAG => {
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_CM);
+            decompress_cm(data, limit, logger)
+        }
+        LONG_RANGE_FLAG => {
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_LONG_RANGE);
+            decompress_long_range(data, limit, logger, progress)
+        }
+        LZ4_FLAG => {
+            // The flag is the first byte of the frame magic, so the frame starts at the flag
+            log_message!(logger, LogLevel::Debug, "Decompressing: {}", ALGO_LZ4);
+            decompress_lz4(input, limit, logger)
         }
         _ => {
-            log_message(LOG_LEVEL_DEBUG, &log_level, "Unknown compression flag", verbose);
-            input.to_vec()
+            log_message!(logger, LogLevel::Debug, "Unknown compression flag");
+            Ok(input.to_vec())
         }
     }
 }
 
-pub fn decompress_chunked(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
+/// Reports progress against the header's total size after every chunk. A
+/// chunk marked CHUNK_DEPENDS_ON_PREVIOUS decodes its first stage, if LZ,
+/// after the previous chunk's output.
+pub fn decompress_chunked(
+    data: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+) -> Result<Vec<u8>, Error> {
     if data.len() < 8 {
-        return Vec::new();
+        return Ok(Vec::new());
     }
 
     // Read metadata
     let total_chunks = u32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
     let total_size = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+    limit.check(total_size)?;
 
-    let mut result = Vec::with_capacity(total_size);
+    let mut result = Vec::with_capacity(untrusted_capacity(total_size, data.len()));
     let mut pos = 8;
+    // Where the previous chunk's output starts in `result`
+    let mut previous_start = 0;
 
     for _ in 0..total_chunks {
+        check_cancelled(progress)?;
         if pos + 4 >= data.len() {
             break;
         }
@@ -108,7 +162,8 @@ pub fn decompress_chunked(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8
         if pos >= data.len() {
             break;
         }
-        let methods_count = data[pos] as usize;
+        let dependent = data[pos] & CHUNK_DEPENDS_ON_PREVIOUS != 0;
+        let methods_count = (data[pos] & !CHUNK_DEPENDS_ON_PREVIOUS) as usize;
         pos += 1;
 
         if pos + methods_count >= data.len() {
@@ -123,30 +178,65 @@ pub fn decompress_chunked(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8
         let mut chunk_data = data[pos..pos + chunk_size].to_vec();
         pos += chunk_size;
 
-        // Apply decompression methods in reverse order
-        for &method in methods.iter().rev() {
+        // Apply decompression methods in reverse order; only an LZ first
+        // stage copies from the previous chunk
+        let history = if dependent {
+            &result[previous_start..]
+        } else {
+            &[][..]
+        };
+        for (index, &method) in methods.iter().enumerate().rev() {
             chunk_data = match method {
-                COMPRESSED_FLAG => decompress_lz_huffman(&chunk_data, log_level, verbose),
-                RLE_FLAG => decompress_rle(&chunk_data, log_level, verbose),
-                DELTA_FLAG => decompress_delta(&chunk_data, log_level, verbose),
-                BWT_FLAG => decompress_bwt(&chunk_data, log_level, verbose),
-                _ => chunk_data,
+                COMPRESSED_FLAG if index == 0 => {
+                    decompress_lz_with_history(history, &chunk_data, limit, logger)?
+                }
+                _ => decompress_method(method, &chunk_data, limit, logger)?,
             };
         }
 
+        limit.check(result.len() + chunk_data.len())?;
+        previous_start = result.len();
         result.extend(chunk_data);
+        progress.report(result.len(), total_size);
     }
 
-    log_message(
-        LOG_LEVEL_PERFORMANCE,
-        log_level,
-        &format!(
-            "Chunked decompression complete: original_size={}, decompressed_size={}",
-            data.len(),
-            result.len()
-        ),
-        verbose,
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Chunked decompression complete: original_size={}, decompressed_size={}",
+        data.len(),
+        result.len()
     );
 
-    result
+    Ok(result)
+}
+
+/// Reverses a single chain method; unknown methods pass the data through.
+pub(crate) fn decompress_method(
+    method: u8,
+    data: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    match method {
+        COMPRESSED_FLAG => decompress_lz_huffman(data, limit, logger),
+        RLE_FLAG => decompress_rle(data, limit, logger),
+        DELTA_FLAG => decompress_delta(data, limit, logger),
+        BWT_FLAG => decompress_bwt(data, limit, logger),
+        NUMPACK_FLAG => decompress_numpack(data, limit, logger),
+        RECORD_FLAG => decompress_record(data, limit, logger),
+        FSE_FLAG => decompress_fse(data, limit, logger),
+        CM_FLAG => decompress_cm(data, limit, logger),
+        _ => Ok(data.to_vec()),
+    }
+}
+
+/// Decodes raw Snappy, for callers that know the input holds it.
+pub fn decompress_snappy_raw(
+    input: &[u8],
+    options: &DecompressOptions,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    let limit = OutputLimit::new(options, input.len());
+    snappy::decompress_snappy_raw(input, &limit, logger)
 }
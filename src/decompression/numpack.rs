@@ -0,0 +1,98 @@
+use crate::constants::{NUMPACK_MARKER, NUMPACK_MAX_DIGITS};
+use crate::decompression::limit::OutputLimit;
+use crate::shared::error::Error;
+use crate::shared::varint::{read_varint, zigzag_decode};
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::format;
+use alloc::vec::Vec;
+
+pub fn decompress_numpack(
+    data: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    log_message!(logger, LogLevel::Info, "Starting NumPack decompression");
+
+    if data.len() < 4 {
+        log_message!(logger, LogLevel::Error, "NumPack header is truncated");
+        return Ok(data.to_vec());
+    }
+
+    let text_len = u32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
+    if text_len > data.len() - 4 {
+        log_message!(logger, LogLevel::Error, "NumPack text length out of range");
+        return Ok(data.to_vec());
+    }
+    let text = &data[4..4 + text_len];
+    let numbers = &data[4 + text_len..];
+
+    let mut result = Vec::with_capacity((data.len() * 2).min(limit.max_len()));
+    let mut pos = 0;
+    let mut previous = 0i64;
+
+    for &byte in text {
+        if byte != NUMPACK_MARKER {
+            limit.check(result.len() + 1)?;
+            result.push(byte);
+            continue;
+        }
+
+        match restore_number(numbers, &mut pos, &mut previous, &mut result) {
+            // A number adds at most NUMPACK_MAX_DIGITS + 2 bytes, so check after it
+            Some(()) => limit.check(result.len())?,
+            None => {
+                log_message!(logger, LogLevel::Error, "Invalid NumPack number stream");
+                return Ok(data.to_vec());
+            }
+        }
+    }
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "NumPack decompression complete: original_size={}, decompressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    Ok(result)
+}
+
+fn restore_number(
+    numbers: &[u8],
+    pos: &mut usize,
+    previous: &mut i64,
+    output: &mut Vec<u8>,
+) -> Option<()> {
+    let descriptor = read_varint(numbers, pos)? as usize;
+    if descriptor == 0 {
+        output.push(NUMPACK_MARKER);
+        return Some(());
+    }
+
+    let digits = descriptor & 0x1F;
+    let frac_digits = (descriptor >> 5) & 0x1F;
+    let negative = (descriptor >> 10) & 1 == 1;
+    if descriptor >> 11 != 0 || digits > NUMPACK_MAX_DIGITS || frac_digits >= digits {
+        return None;
+    }
+
+    let value = previous.wrapping_add(zigzag_decode(read_varint(numbers, pos)?));
+    *previous = value;
+
+    let formatted = format!("{:0width$}", value.unsigned_abs(), width = digits);
+    if formatted.len() != digits {
+        return None;
+    }
+
+    if negative {
+        output.push(b'-');
+    }
+    let split = digits - frac_digits;
+    output.extend_from_slice(&formatted.as_bytes()[..split]);
+    if frac_digits > 0 {
+        output.push(b'.');
+        output.extend_from_slice(&formatted.as_bytes()[split..]);
+    }
+    Some(())
+}
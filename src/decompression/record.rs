@@ -0,0 +1,52 @@
+use crate::constants::{RECORD_HEADER_SIZE, RECORD_MODE_SUB, RECORD_MODE_XOR};
+use crate::decompression::limit::OutputLimit;
+use crate::shared::error::Error;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+pub fn decompress_record(
+    data: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    log_message!(logger, LogLevel::Info, "Starting Record decompression");
+
+    if data.len() < RECORD_HEADER_SIZE {
+        log_message!(logger, LogLevel::Error, "Record header is truncated");
+        return Ok(data.to_vec());
+    }
+
+    let width = u16::from_le_bytes([data[0], data[1]]) as usize;
+    let mode = data[2];
+    if width == 0 || (mode != RECORD_MODE_XOR && mode != RECORD_MODE_SUB) {
+        log_message!(
+            logger,
+            LogLevel::Error,
+            "Invalid record layout: width={}, mode={}",
+            width,
+            mode
+        );
+        return Ok(data.to_vec());
+    }
+
+    // Records are restored front to back so the previous record is already decoded
+    limit.check(data.len() - RECORD_HEADER_SIZE)?;
+    let mut result = data[RECORD_HEADER_SIZE..].to_vec();
+    for i in width..result.len() {
+        result[i] = if mode == RECORD_MODE_SUB {
+            result[i].wrapping_add(result[i - width])
+        } else {
+            result[i] ^ result[i - width]
+        };
+    }
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Record decompression complete: original_size={}, decompressed_size={}",
+        data.len(),
+        result.len()
+    );
+
+    Ok(result)
+}
@@ -1,39 +1,47 @@
-use crate::constants::{LOG_LEVEL_INFO, LOG_LEVEL_PERFORMANCE};
-use crate::utils::log_message;
+use crate::decompression::limit::OutputLimit;
+use crate::shared::error::Error;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
 
-pub fn decompress_rle(data: &[u8], log_level: &str, verbose: bool) -> Vec<u8> {
-    log_message(
-        LOG_LEVEL_INFO,
-        log_level,
-        "Starting RLE decompression",
-        verbose,
-    );
+pub fn decompress_rle(
+    data: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    log_message!(logger, LogLevel::Info, "Starting RLE decompression");
 
     let mut result = Vec::with_capacity(data.len());
     let mut i = 0;
 
     while i < data.len() {
         if data[i] == 0xFF && i + 2 < data.len() {
+            // Run: [0xFF, count, value]
             let count = data[i + 1];
             let value = data[i + 2];
-            result.extend(std::iter::repeat(value).take(count as usize));
+            limit.check(result.len() + count as usize)?;
+            result.extend(core::iter::repeat_n(value, count as usize));
             i += 3;
+        } else if data[i] == 0xFE && i + 1 < data.len() {
+            // Literals: [0xFE, length, bytes...]
+            let start = i + 2;
+            let end = (start + data[i + 1] as usize).min(data.len());
+            limit.check(result.len() + end - start)?;
+            result.extend_from_slice(&data[start..end]);
+            i = end;
         } else {
+            limit.check(result.len() + 1)?;
             result.push(data[i]);
             i += 1;
         }
     }
 
-    log_message(
-        LOG_LEVEL_PERFORMANCE,
-        log_level,
-        &format!(
-            "RLE decompression complete: original_size={}, decompressed_size={}",
-            data.len(),
-            result.len()
-        ),
-        verbose,
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "RLE decompression complete: original_size={}, decompressed_size={}",
+        data.len(),
+        result.len()
     );
 
-    result
+    Ok(result)
 }
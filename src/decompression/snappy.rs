@@ -0,0 +1,214 @@
+use crate::constants::{SNAPPY_BLOCK_SIZE, SNAPPY_STREAM_IDENTIFIER};
+use crate::decompression::frame::{copy_match, FrameError, FrameResult};
+use crate::decompression::limit::{untrusted_capacity, OutputLimit};
+use crate::shared::crc32c::masked_crc32c;
+use crate::shared::error::Error;
+use crate::shared::varint::read_varint;
+use crate::utils::{log_message, LogLevel, Logger};
+use alloc::vec::Vec;
+
+// Reads Snappy framing-format streams as written by `compress_snappy` or any
+// other Snappy implementation, including repeated stream identifiers,
+// padding and skippable chunks, and raw Snappy as stored by LevelDB.
+
+const COMPRESSED_CHUNK: u8 = 0x00;
+const UNCOMPRESSED_CHUNK: u8 = 0x01;
+const PADDING_CHUNK: u8 = 0xFE;
+const STREAM_IDENTIFIER_CHUNK: u8 = 0xFF;
+
+/// Decodes `input`, which starts at the stream identifier. Malformed or
+/// corrupt streams are returned unchanged, like other undecodable input.
+pub fn decompress_snappy(
+    input: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    log_message!(logger, LogLevel::Info, "Starting Snappy decompression");
+
+    let mut result = Vec::new();
+    match decode_frames(input, &mut result, limit) {
+        Ok(()) => {}
+        Err(FrameError::Limit(err)) => return Err(err),
+        Err(FrameError::Malformed(reason)) => {
+            log_message!(logger, LogLevel::Error, "Invalid Snappy stream: {}", reason);
+            return Ok(input.to_vec());
+        }
+    }
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Snappy decompression complete: original_size={}, decompressed_size={}",
+        input.len(),
+        result.len()
+    );
+
+    Ok(result)
+}
+
+/// Decodes raw Snappy, which has no header to recognize it by. Malformed
+/// input is returned unchanged, as for `decompress_snappy`.
+pub fn decompress_snappy_raw(
+    input: &[u8],
+    limit: &OutputLimit,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    log_message!(logger, LogLevel::Info, "Starting raw Snappy decompression");
+
+    let mut result = Vec::new();
+    match decode_raw(input, &mut result, usize::MAX, limit) {
+        Ok(()) => {}
+        Err(FrameError::Limit(err)) => return Err(err),
+        Err(FrameError::Malformed(reason)) => {
+            log_message!(logger, LogLevel::Error, "Invalid raw Snappy: {}", reason);
+            return Ok(input.to_vec());
+        }
+    }
+
+    log_message!(
+        logger,
+        LogLevel::Performance,
+        "Raw Snappy decompression complete: original_size={}, decompressed_size={}",
+        input.len(),
+        result.len()
+    );
+
+    Ok(result)
+}
+
+fn decode_frames(input: &[u8], out: &mut Vec<u8>, limit: &OutputLimit) -> FrameResult<()> {
+    let mut pos = 0;
+    while pos < input.len() {
+        let header = input
+            .get(pos..pos + 4)
+            .ok_or(FrameError::Malformed("truncated chunk header"))?;
+        let kind = header[0];
+        let length = u32::from_le_bytes([header[1], header[2], header[3], 0]) as usize;
+        pos += 4;
+        let body = input
+            .get(pos..pos + length)
+            .ok_or(FrameError::Malformed("truncated chunk"))?;
+        pos += length;
+
+        match kind {
+            STREAM_IDENTIFIER_CHUNK => {
+                if body != &SNAPPY_STREAM_IDENTIFIER[4..] {
+                    return Err(FrameError::Malformed("bad stream identifier"));
+                }
+            }
+            COMPRESSED_CHUNK | UNCOMPRESSED_CHUNK => {
+                let (checksum, data) = match body.split_first_chunk::<4>() {
+                    Some((checksum, data)) => (u32::from_le_bytes(*checksum), data),
+                    None => return Err(FrameError::Malformed("chunk too short")),
+                };
+                let start = out.len();
+                if kind == COMPRESSED_CHUNK {
+                    decode_raw(data, out, SNAPPY_BLOCK_SIZE, limit)?;
+                } else {
+                    if data.len() > SNAPPY_BLOCK_SIZE {
+                        return Err(FrameError::Malformed("chunk larger than the block size"));
+                    }
+                    limit.check(out.len() + data.len())?;
+                    out.extend_from_slice(data);
+                }
+                if masked_crc32c(&out[start..]) != checksum {
+                    return Err(FrameError::Malformed("chunk checksum mismatch"));
+                }
+            }
+            // Padding and reserved skippable chunks carry no data
+            PADDING_CHUNK | 0x80..=0xFD => {}
+            0x02..=0x7F => return Err(FrameError::Malformed("reserved unskippable chunk")),
+        }
+    }
+    Ok(())
+}
+
+/// Appends one raw Snappy block to `out`, which must decode to at most
+/// `max_len` bytes. Copies may only reach back to the block's start.
+fn decode_raw(
+    block: &[u8],
+    out: &mut Vec<u8>,
+    max_len: usize,
+    limit: &OutputLimit,
+) -> FrameResult<()> {
+    let mut i = 0;
+    let length = read_varint(block, &mut i).ok_or(FrameError::Malformed("bad length"))?;
+    let length = usize::try_from(length)
+        .ok()
+        .filter(|&length| length <= max_len && length <= u32::MAX as usize)
+        .ok_or(FrameError::Malformed("length out of range"))?;
+    let start = out.len();
+    let end = start + length;
+    limit.check(end)?;
+    out.reserve(untrusted_capacity(length, block.len()));
+
+    while i < block.len() {
+        let tag = block[i];
+        i += 1;
+
+        let (copy_length, offset) = match tag & 0x03 {
+            0 => {
+                let literal_length = match tag >> 2 {
+                    n @ 0..=59 => n as usize + 1,
+                    n => {
+                        // Tags 60-63 say the length follows in 1-4 bytes
+                        let bytes = (n - 59) as usize;
+                        let extra = block
+                            .get(i..i + bytes)
+                            .ok_or(FrameError::Malformed("truncated literal length"))?;
+                        i += bytes;
+                        let mut word = [0; 4];
+                        word[..bytes].copy_from_slice(extra);
+                        usize::try_from(u32::from_le_bytes(word))
+                            .ok()
+                            .and_then(|n| n.checked_add(1))
+                            .ok_or(FrameError::Malformed("literal length out of range"))?
+                    }
+                };
+                let literal = block
+                    .get(i..i.saturating_add(literal_length))
+                    .ok_or(FrameError::Malformed("truncated literal"))?;
+                if out.len() + literal_length > end {
+                    return Err(FrameError::Malformed("decodes past the recorded length"));
+                }
+                out.extend_from_slice(literal);
+                i += literal_length;
+                continue;
+            }
+            1 => {
+                let low = *block
+                    .get(i)
+                    .ok_or(FrameError::Malformed("truncated offset"))?;
+                i += 1;
+                (
+                    4 + ((tag >> 2) & 0x07) as usize,
+                    ((tag >> 5) as usize) << 8 | low as usize,
+                )
+            }
+            width => {
+                // Tag 2 has a 2-byte offset, tag 3 a 4-byte one
+                let bytes = if width == 2 { 2 } else { 4 };
+                let extra = block
+                    .get(i..i + bytes)
+                    .ok_or(FrameError::Malformed("truncated offset"))?;
+                i += bytes;
+                let mut word = [0; 4];
+                word[..bytes].copy_from_slice(extra);
+                ((tag >> 2) as usize + 1, u32::from_le_bytes(word) as usize)
+            }
+        };
+
+        if offset == 0 || offset > out.len() - start {
+            return Err(FrameError::Malformed("copy offset out of range"));
+        }
+        if out.len() + copy_length > end {
+            return Err(FrameError::Malformed("decodes past the recorded length"));
+        }
+        copy_match(out, offset, copy_length);
+    }
+
+    if out.len() != end {
+        return Err(FrameError::Malformed("length mismatch"));
+    }
+    Ok(())
+}
@@ -0,0 +1,366 @@
+use crate::decompression::limit::untrusted_capacity;
+use crate::shared::error::Error;
+use crate::shared::varint::{read_varint, write_varint};
+use alloc::vec;
+use alloc::vec::Vec;
+
+// Context mixing: a bitwise predictor driving a binary arithmetic coder, for
+// when ratio matters far more than speed. Everything is integer arithmetic
+// and the model set is fixed, so both sides predict identically on every
+// platform. Changing any constant or model here changes the format.
+//
+// Each bit is predicted from five contexts: the last 1, 2, 3 and 4 bytes,
+// and the current word (ASCII letters, ignoring case), each hashed with the
+// bits of the current byte seen so far into its own table of 2^16 adaptive
+// counters. A mixer, with a weight set per partial byte, combines their
+// predictions in the logistic domain, and an APM refines the result.
+//
+// Format: [len: varint][arithmetic coded bits...]
+
+const TABLE_BITS: u32 = 16;
+const MODELS: usize = 5; // Orders 1-4 and the word
+const INPUTS: usize = MODELS + 1; // Plus a bias
+const COUNT_LIMIT: u16 = 15;
+const INITIAL_WEIGHT: i32 = 20_000; // About 0.3, in 16.16 fixed point
+const MAX_WEIGHT: i32 = 1 << 20; // 16, past which a weight only saturates the mix
+const APM_RATE: i32 = 7;
+
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() / 3 + 8);
+    write_varint(&mut out, data.len() as u64);
+
+    let mut predictor = Predictor::new();
+    let mut encoder = Encoder::new(out);
+    for &byte in data {
+        for i in (0..8).rev() {
+            let bit = (byte >> i) & 1;
+            encoder.encode(bit, predictor.p());
+            predictor.update(bit);
+        }
+    }
+    encoder.finish()
+}
+
+/// Decodes a stream written by `encode`, or returns `Ok(None)` if it is
+/// malformed. Fails only when `check` rejects the recorded length.
+pub fn decode(
+    data: &[u8],
+    check: impl Fn(usize) -> Result<(), Error>,
+) -> Result<Option<Vec<u8>>, Error> {
+    let mut pos = 0;
+    let Some(len) = read_varint(data, &mut pos).and_then(|len| usize::try_from(len).ok()) else {
+        return Ok(None);
+    };
+    check(len)?;
+
+    let mut out = Vec::with_capacity(untrusted_capacity(len, data.len()));
+    let mut predictor = Predictor::new();
+    let mut decoder = Decoder::new(&data[pos..]);
+    for _ in 0..len {
+        let mut byte = 0;
+        for _ in 0..8 {
+            let bit = decoder.decode(predictor.p());
+            predictor.update(bit);
+            byte = byte << 1 | bit;
+        }
+        // The encoder's output ends where the decoder stops reading
+        if decoder.overrun {
+            return Ok(None);
+        }
+        out.push(byte);
+    }
+    Ok(Some(out))
+}
+
+// Logistic helpers on 12-bit probabilities, as in lpaq: `squash` maps the
+// stretched domain (-2047..=2047) to a probability and `stretch` inverts it.
+
+fn squash(d: i32) -> i32 {
+    const T: [i32; 33] = [
+        1, 2, 3, 6, 10, 16, 27, 45, 73, 120, 194, 310, 488, 747, 1101, 1546, 2047, 2549, 2994,
+        3348, 3607, 3785, 3901, 3975, 4022, 4050, 4068, 4079, 4085, 4089, 4092, 4093, 4094,
+    ];
+    if d > 2047 {
+        return 4095;
+    }
+    if d < -2047 {
+        return 1;
+    }
+    let w = d & 127;
+    let i = ((d >> 7) + 16) as usize;
+    (T[i] * (128 - w) + T[i + 1] * w + 64) >> 7
+}
+
+fn stretch_table() -> Vec<i16> {
+    let mut table = vec![0i16; 4096];
+    let mut pi = 0;
+    for x in -2047..=2047 {
+        let v = squash(x);
+        for entry in &mut table[pi..=v as usize] {
+            *entry = x as i16;
+        }
+        pi = v as usize + 1;
+    }
+    for entry in &mut table[pi..] {
+        *entry = 2047;
+    }
+    table
+}
+
+#[inline]
+fn hash(value: u32, salt: u32) -> u32 {
+    (value ^ salt.wrapping_mul(0x2545_F491))
+        .wrapping_mul(0x9E37_79B1)
+        .rotate_left(13)
+        .wrapping_mul(0x85EB_CA6B)
+}
+
+/// Adaptive probability, packed as a 12-bit probability of a 1 above a
+/// 4-bit count of updates. Early updates move it further.
+#[inline]
+fn update_counter(counter: &mut u16, bit: u8) {
+    // Reciprocals of count + 1.5, in 16-bit fixed point
+    const RATES: [i32; 16] = [
+        43690, 26214, 18724, 14563, 11915, 10082, 8738, 7710, 6898, 6241, 5698, 5242, 4854, 4519,
+        4228, 3971,
+    ];
+    let p = (*counter >> 4) as i32;
+    let n = *counter & 15;
+    let target = if bit == 1 { 4095 } else { 0 };
+    let p = p + (((target - p) * RATES[n as usize]) >> 16);
+    *counter = (p as u16) << 4 | (n + 1).min(COUNT_LIMIT);
+}
+
+/// Adaptive probability map: refines a probability given a small context by
+/// interpolating between 33 learned points along the stretched domain.
+struct Apm {
+    table: Vec<u16>,
+    index: usize,
+}
+
+impl Apm {
+    fn new(contexts: usize) -> Self {
+        let row: Vec<u16> = (0..33)
+            .map(|j| (squash((j - 16) * 128) * 16) as u16)
+            .collect();
+        Apm {
+            table: row.repeat(contexts),
+            index: 0,
+        }
+    }
+
+    fn refine(&mut self, stretched: i32, context: usize) -> i32 {
+        let s = stretched + 2048;
+        let weight = s & 127;
+        let base = (s >> 7) as usize + context * 33;
+        // Only the nearer point learns from the bit
+        self.index = base + (weight >> 6) as usize;
+        ((self.table[base] as i32 * (128 - weight) + self.table[base + 1] as i32 * weight) >> 11)
+            .clamp(1, 4095)
+    }
+
+    fn update(&mut self, bit: u8) {
+        let target = (bit as i32) << 16;
+        let entry = &mut self.table[self.index];
+        *entry = (*entry as i32 + ((target - *entry as i32) >> APM_RATE)).clamp(0, 65535) as u16;
+    }
+}
+
+struct Predictor {
+    stretch: Vec<i16>,
+    tables: Vec<u16>,
+    /// Hash of each model's context for the current byte.
+    contexts: [u32; MODELS],
+    /// Table index each model used for the current bit.
+    slots: [usize; MODELS],
+    inputs: [i32; INPUTS],
+    weights: Vec<i32>,
+    apm: Apm,
+    /// Bits of the current byte so far, behind a leading 1.
+    partial: u32,
+    /// Last four bytes, most recent lowest.
+    history: u32,
+    word: u32,
+    mixed: i32,
+    pr: i32,
+}
+
+impl Predictor {
+    fn new() -> Self {
+        let mut weights = vec![INITIAL_WEIGHT; 256 * INPUTS];
+        for set in weights.chunks_mut(INPUTS) {
+            set[MODELS] = 0;
+        }
+        let mut predictor = Predictor {
+            stretch: stretch_table(),
+            tables: vec![2048 << 4; MODELS << TABLE_BITS],
+            contexts: [0; MODELS],
+            slots: [0; MODELS],
+            inputs: [0; INPUTS],
+            weights,
+            apm: Apm::new(256),
+            partial: 1,
+            history: 0,
+            word: 0,
+            mixed: 0,
+            pr: 2048,
+        };
+        predictor.next_byte();
+        predictor.predict();
+        predictor
+    }
+
+    /// Probability that the next bit is a 1, in 12 bits.
+    fn p(&self) -> u32 {
+        self.pr as u32
+    }
+
+    fn update(&mut self, bit: u8) {
+        for &slot in &self.slots {
+            update_counter(&mut self.tables[slot], bit);
+        }
+
+        let err = (((bit as i32) << 12) - self.mixed) * 7;
+        let set = self.partial as usize * INPUTS;
+        for (weight, &input) in self.weights[set..set + INPUTS].iter_mut().zip(&self.inputs) {
+            *weight = (*weight + ((input * err) >> 10)).clamp(-MAX_WEIGHT, MAX_WEIGHT);
+        }
+        self.apm.update(bit);
+
+        self.partial = self.partial << 1 | bit as u32;
+        if self.partial >= 256 {
+            let byte = self.partial as u8;
+            self.history = self.history << 8 | byte as u32;
+            self.word = if byte.is_ascii_alphabetic() {
+                (self.word ^ byte.to_ascii_lowercase() as u32).wrapping_mul(0x0100_0193)
+            } else {
+                0
+            };
+            self.partial = 1;
+            self.next_byte();
+        }
+        self.predict();
+    }
+
+    fn next_byte(&mut self) {
+        let history = self.history;
+        self.contexts = [
+            hash(history & 0xFF, 1),
+            hash(history & 0xFFFF, 2),
+            hash(history & 0xFF_FFFF, 3),
+            hash(history, 4),
+            hash(self.word, 5),
+        ];
+    }
+
+    fn predict(&mut self) {
+        let mask = (1 << TABLE_BITS) - 1;
+        for model in 0..MODELS {
+            let slot = (hash(self.contexts[model], self.partial) >> (32 - TABLE_BITS)) as usize;
+            self.slots[model] = model << TABLE_BITS | (slot & mask);
+            let p = self.tables[self.slots[model]] >> 4;
+            self.inputs[model] = self.stretch[p as usize] as i32;
+        }
+        self.inputs[MODELS] = 256;
+
+        let set = self.partial as usize * INPUTS;
+        let dot: i64 = self.weights[set..set + INPUTS]
+            .iter()
+            .zip(&self.inputs)
+            .map(|(&weight, &input)| weight as i64 * input as i64)
+            .sum();
+        let stretched = ((dot >> 16) as i32).clamp(-2047, 2047);
+        self.mixed = squash(stretched);
+        let refined = self.apm.refine(stretched, self.partial as usize);
+        self.pr = ((self.mixed + 3 * refined) >> 2).clamp(1, 4095);
+    }
+}
+
+/// Carryless binary arithmetic coder over a 32-bit range.
+struct Encoder {
+    out: Vec<u8>,
+    low: u32,
+    high: u32,
+}
+
+impl Encoder {
+    fn new(out: Vec<u8>) -> Self {
+        Encoder {
+            out,
+            low: 0,
+            high: u32::MAX,
+        }
+    }
+
+    fn encode(&mut self, bit: u8, p: u32) {
+        let mid = self.low + ((self.high - self.low) >> 12) * p;
+        if bit == 1 {
+            self.high = mid;
+        } else {
+            self.low = mid + 1;
+        }
+        while (self.low ^ self.high) & 0xFF00_0000 == 0 {
+            self.out.push((self.high >> 24) as u8);
+            self.low <<= 8;
+            self.high = self.high << 8 | 255;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.out.extend_from_slice(&self.low.to_be_bytes());
+        self.out
+    }
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    low: u32,
+    high: u32,
+    x: u32,
+    /// Set once the decoder needs bytes past the end of `data`.
+    overrun: bool,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        let mut decoder = Decoder {
+            data,
+            pos: 0,
+            low: 0,
+            high: u32::MAX,
+            x: 0,
+            overrun: false,
+        };
+        for _ in 0..4 {
+            decoder.x = decoder.x << 8 | decoder.next_byte();
+        }
+        decoder
+    }
+
+    fn next_byte(&mut self) -> u32 {
+        let byte = self.data.get(self.pos).copied().unwrap_or_else(|| {
+            self.overrun = true;
+            0
+        });
+        self.pos += 1;
+        byte as u32
+    }
+
+    fn decode(&mut self, p: u32) -> u8 {
+        let mid = self.low + ((self.high - self.low) >> 12) * p;
+        let bit = if self.x <= mid {
+            self.high = mid;
+            1
+        } else {
+            self.low = mid + 1;
+            0
+        };
+        while (self.low ^ self.high) & 0xFF00_0000 == 0 {
+            self.low <<= 8;
+            self.high = self.high << 8 | 255;
+            self.x = self.x << 8 | self.next_byte();
+        }
+        bit
+    }
+}
@@ -0,0 +1,297 @@
+use crate::decompression::limit::untrusted_capacity;
+use crate::shared::bits::{highest_bit, BitReader, BitWriter};
+use crate::shared::error::Error;
+use crate::shared::varint::{read_varint, write_varint};
+use alloc::vec;
+use alloc::vec::Vec;
+
+// Table-based asymmetric numeral systems (tANS), as in FSE: an order-0 coder
+// that, unlike Huffman, can spend a fraction of a bit on a frequent symbol.
+//
+// Format: [len: varint][table_log: u8][max_symbol: u8][bits...]
+// Bits are read LSB first. They start with the normalized count of each
+// symbol up to max_symbol, as Elias gamma codes of count + 1; a zero count is
+// followed by the gamma code of 1 + how many more symbols have one, which
+// keeps tables for sparse alphabets small. The counts sum to
+// 1 << table_log, the number of coder states. Then come the encoder's final
+// state in table_log bits and, for each symbol in order, the low bits of the
+// state that produced it. Symbols are encoded last to first so that they
+// decode first to last.
+//
+// Data that doesn't shrink is stored instead: [len: varint][0][bytes...].
+
+const MIN_TABLE_LOG: u32 = 5;
+const MAX_TABLE_LOG: u32 = 12;
+const STORED: u8 = 0; // In place of the table log
+
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() / 2 + 16);
+    write_varint(&mut out, data.len() as u64);
+    if data.is_empty() {
+        return out;
+    }
+
+    let mut counts = [0usize; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    let max_symbol = counts.iter().rposition(|&count| count > 0).unwrap();
+    let counts = &counts[..=max_symbol];
+    let table_log = table_log(data.len(), counts.iter().filter(|&&c| c > 0).count());
+    let norm = normalize(counts, data.len(), table_log);
+
+    let header_len = out.len();
+    out.push(table_log as u8);
+    out.push(max_symbol as u8);
+
+    let table = EncodeTable::new(&norm, table_log);
+    let size = 1u32 << table_log;
+    let mut state = size;
+    let mut chunks = Vec::with_capacity(data.len());
+    for &byte in data.iter().rev() {
+        let transform = &table.symbols[byte as usize];
+        let bits = (state + transform.delta_bits) >> 16;
+        chunks.push((state & ((1 << bits) - 1), bits));
+        state = table.states[((state >> bits) as i32 + transform.delta_state) as usize] as u32;
+    }
+
+    let mut writer = BitWriter::new(&mut out);
+    let mut symbol = 0;
+    while symbol < norm.len() {
+        writer.write_gamma(norm[symbol] + 1);
+        symbol += 1;
+        if norm[symbol - 1] == 0 {
+            let zeros = norm[symbol..]
+                .iter()
+                .take_while(|&&count| count == 0)
+                .count();
+            writer.write_gamma(zeros as u32 + 1);
+            symbol += zeros;
+        }
+    }
+    writer.write(state - size, table_log);
+    for &(value, bits) in chunks.iter().rev() {
+        writer.write(value, bits);
+    }
+    writer.finish();
+
+    if out.len() > header_len + 1 + data.len() {
+        out.truncate(header_len);
+        out.push(STORED);
+        out.extend_from_slice(data);
+    }
+    out
+}
+
+/// Decodes a stream written by `encode`, or returns `Ok(None)` if it is
+/// malformed. Fails only when `check` rejects the recorded length, which it
+/// sees before anything is allocated.
+pub fn decode(
+    data: &[u8],
+    check: impl Fn(usize) -> Result<(), Error>,
+) -> Result<Option<Vec<u8>>, Error> {
+    let mut pos = 0;
+    let Some(len) = read_varint(data, &mut pos).and_then(|len| usize::try_from(len).ok()) else {
+        return Ok(None);
+    };
+    if len == 0 {
+        return Ok(Some(Vec::new()));
+    }
+    check(len)?;
+
+    if data.get(pos) == Some(&STORED) {
+        let stored = &data[pos + 1..];
+        return Ok((stored.len() == len).then(|| stored.to_vec()));
+    }
+    let Some((&log, &max_symbol)) = data.get(pos).zip(data.get(pos + 1)) else {
+        return Ok(None);
+    };
+    let mut reader = BitReader::new(&data[pos + 2..]);
+    let Some(table) = read_table(&mut reader, log as u32, max_symbol) else {
+        return Ok(None);
+    };
+    let Some(mut state) = reader.read(table.log) else {
+        return Ok(None);
+    };
+
+    let mut out = Vec::with_capacity(untrusted_capacity(len, data.len()));
+    for _ in 0..len {
+        let entry = table.entries[state as usize];
+        out.push(entry.symbol);
+        let Some(bits) = reader.read(entry.bits as u32) else {
+            return Ok(None);
+        };
+        state = entry.base as u32 + bits;
+    }
+    // Decoding retraces the encoder back to its initial state
+    if state != 0 {
+        return Ok(None);
+    }
+    Ok(Some(out))
+}
+
+/// Enough states to resolve the data's probabilities, but no more than a
+/// small input can pay for in its header.
+fn table_log(len: usize, symbols: usize) -> u32 {
+    let for_len = (usize::BITS - 1 - len.leading_zeros()).saturating_sub(2);
+    let for_symbols = usize::BITS - (symbols - 1).leading_zeros() + 1;
+    for_len.max(for_symbols).clamp(MIN_TABLE_LOG, MAX_TABLE_LOG)
+}
+
+/// Scales `counts` to sum to 1 << `table_log`, keeping every present symbol
+/// at least 1. Rounding errors are settled on the most frequent symbols,
+/// where they cost the least.
+fn normalize(counts: &[usize], total: usize, table_log: u32) -> Vec<u32> {
+    let size = 1u64 << table_log;
+    let mut norm: Vec<u32> = counts
+        .iter()
+        .map(|&count| match count {
+            0 => 0,
+            count => ((count as u64 * size + total as u64 / 2) / total as u64).max(1) as u32,
+        })
+        .collect();
+
+    let mut sum: u64 = norm.iter().map(|&n| n as u64).sum();
+    while sum != size {
+        let largest = (0..norm.len())
+            .filter(|&s| sum < size || norm[s] > 1)
+            .max_by_key(|&s| (norm[s], counts[s]))
+            .unwrap();
+        if sum > size {
+            norm[largest] -= 1;
+            sum -= 1;
+        } else {
+            norm[largest] += 1;
+            sum += 1;
+        }
+    }
+    norm
+}
+
+/// Symbol for each state, spread so that every symbol's states are
+/// scattered across the table.
+fn spread(norm: &[u32], table_log: u32) -> Vec<u8> {
+    let size = 1usize << table_log;
+    let mask = size - 1;
+    let step = (size >> 1) + (size >> 3) + 3; // Odd, so every state is visited
+    let mut table = vec![0u8; size];
+    let mut pos = 0;
+    for (symbol, &count) in norm.iter().enumerate() {
+        for _ in 0..count {
+            table[pos] = symbol as u8;
+            pos = (pos + step) & mask;
+        }
+    }
+    table
+}
+
+#[derive(Clone, Copy, Default)]
+struct SymbolTransform {
+    /// Added to the state, its high 16 bits give the number of bits to emit.
+    delta_bits: u32,
+    /// Added to the shifted state to index `states`.
+    delta_state: i32,
+}
+
+struct EncodeTable {
+    /// Next state, offset by the table size, grouped by symbol.
+    states: Vec<u16>,
+    symbols: [SymbolTransform; 256],
+}
+
+impl EncodeTable {
+    fn new(norm: &[u32], table_log: u32) -> Self {
+        let size = 1u32 << table_log;
+        let mut cumulative = vec![0u32; norm.len() + 1];
+        for (symbol, &count) in norm.iter().enumerate() {
+            cumulative[symbol + 1] = cumulative[symbol] + count;
+        }
+
+        let mut states = vec![0u16; size as usize];
+        let mut seen = cumulative.clone();
+        for (state, &symbol) in spread(norm, table_log).iter().enumerate() {
+            let slot = &mut seen[symbol as usize];
+            states[*slot as usize] = (size + state as u32) as u16;
+            *slot += 1;
+        }
+
+        let mut symbols = [SymbolTransform::default(); 256];
+        for (symbol, &count) in norm.iter().enumerate() {
+            let start = cumulative[symbol] as i32;
+            symbols[symbol] = match count {
+                0 => continue,
+                1 => SymbolTransform {
+                    delta_bits: (table_log << 16) - size,
+                    delta_state: start - 1,
+                },
+                count => {
+                    let max_bits = table_log - highest_bit(count - 1);
+                    SymbolTransform {
+                        delta_bits: (max_bits << 16) - (count << max_bits),
+                        delta_state: start - count as i32,
+                    }
+                }
+            };
+        }
+        EncodeTable { states, symbols }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct DecodeEntry {
+    symbol: u8,
+    bits: u8,
+    /// Next state, before adding the bits read.
+    base: u16,
+}
+
+struct DecodeTable {
+    log: u32,
+    entries: Vec<DecodeEntry>,
+}
+
+/// Reads and checks the counts at the start of the bit stream.
+fn read_table(reader: &mut BitReader, log: u32, max_symbol: u8) -> Option<DecodeTable> {
+    if !(MIN_TABLE_LOG..=MAX_TABLE_LOG).contains(&log) {
+        return None;
+    }
+    let size = 1u32 << log;
+
+    let symbols = max_symbol as usize + 1;
+    let mut norm = Vec::with_capacity(symbols);
+    let mut sum = 0;
+    while norm.len() < symbols {
+        let count = reader.read_gamma()? - 1;
+        if count > size - sum {
+            return None;
+        }
+        sum += count;
+        norm.push(count);
+        if count == 0 {
+            let zeros = reader.read_gamma()? as usize - 1;
+            if zeros > symbols - norm.len() {
+                return None;
+            }
+            norm.resize(norm.len() + zeros, 0);
+        }
+    }
+    if sum != size {
+        return None;
+    }
+
+    let mut next = norm.clone();
+    let entries = spread(&norm, log)
+        .into_iter()
+        .map(|symbol| {
+            let state = next[symbol as usize];
+            next[symbol as usize] += 1;
+            let bits = log - highest_bit(state);
+            DecodeEntry {
+                symbol,
+                bits: bits as u8,
+                base: ((state << bits) - size) as u16,
+            }
+        })
+        .collect();
+    Some(DecodeTable { log, entries })
+}
@@ -0,0 +1,5 @@
+// Entropy coders, shared by the compressor and decompressor. Each turns a
+// byte stream into a self-describing coded stream and back.
+
+pub(crate) mod cm;
+pub(crate) mod fse;
@@ -0,0 +1,332 @@
+// C ABI for callers outside Rust and JS (C, C++, Go via cgo, Python via
+// ctypes/cffi). The header is generated from this file:
+//
+//     cbindgen --config cbindgen.toml --output include/goud.h src/ffi.rs
+//
+// Functions return a byte count on success and a negative GOUD_ERROR_* code on
+// failure; goud_last_error describes the most recent failure on the thread.
+
+use crate::constants::{MAX_LEVEL, MIN_LEVEL};
+use crate::shared::algorithm::Algorithm;
+use crate::shared::error::Error;
+use crate::shared::options::{ChunkMode, CompressOptions, DecompressOptions};
+use crate::shared::progress::NoProgress;
+use crate::utils::NoopLogger;
+use crate::{compression, decompression};
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::mem::{offset_of, size_of};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// A required pointer argument was NULL.
+pub const GOUD_ERROR_NULL_POINTER: i64 = -1;
+/// A `GoudOptions` field is out of range.
+pub const GOUD_ERROR_INVALID_OPTIONS: i64 = -2;
+/// `out_cap` is smaller than the compressed size; see `goud_compress_bound`.
+pub const GOUD_ERROR_BUFFER_TOO_SMALL: i64 = -3;
+/// Decompressed output would exceed `max_output_size`.
+pub const GOUD_ERROR_OUTPUT_TOO_LARGE: i64 = -4;
+/// Decompressed output would exceed `max_ratio` times the input size.
+pub const GOUD_ERROR_RATIO_EXCEEDED: i64 = -5;
+/// The operation was cancelled.
+pub const GOUD_ERROR_CANCELLED: i64 = -6;
+/// A bug in the library; the message has details.
+pub const GOUD_ERROR_INTERNAL: i64 = -7;
+
+/// Values for `GoudOptions.algorithm`, matching the `Algorithm` discriminants.
+pub const GOUD_ALGORITHM_BEST: u32 = 0;
+pub const GOUD_ALGORITHM_LZ_HUFFMAN: u32 = 1;
+pub const GOUD_ALGORITHM_RLE: u32 = 2;
+pub const GOUD_ALGORITHM_DELTA: u32 = 3;
+pub const GOUD_ALGORITHM_BWT: u32 = 4;
+pub const GOUD_ALGORITHM_NUMPACK: u32 = 5;
+pub const GOUD_ALGORITHM_RECORD: u32 = 6;
+pub const GOUD_ALGORITHM_LZ4: u32 = 7;
+pub const GOUD_ALGORITHM_SNAPPY: u32 = 8;
+pub const GOUD_ALGORITHM_FSE: u32 = 9;
+pub const GOUD_ALGORITHM_CM: u32 = 10;
+
+/// Values for `GoudOptions.chunk_mode`.
+pub const GOUD_CHUNK_MODE_INDEPENDENT: u32 = 0;
+pub const GOUD_CHUNK_MODE_DEPENDENT: u32 = 1;
+
+/// Settings for `goud_compress` and `goud_decompress`. Set `struct_size` to
+/// `sizeof(GoudOptions)` and zero the other fields for their defaults, or
+/// pass a NULL pointer; each function ignores the fields meant for the other.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GoudOptions {
+    /// `sizeof(GoudOptions)` in the caller's header. Fields are only ever
+    /// appended, so the library reads this many bytes and gives any fields
+    /// beyond them their defaults, and callers built against an older header
+    /// keep working.
+    pub struct_size: u32,
+    /// One of GOUD_ALGORITHM_*.
+    pub algorithm: u32,
+    /// 1 (fastest) to 9 (smallest); 0 for the default.
+    pub level: u32,
+    /// Bytes per chunk for GOUD_ALGORITHM_BEST; 0 for the default.
+    pub chunk_size: u32,
+    /// Record width for GOUD_ALGORITHM_RECORD, at most 65535; 0 to detect it.
+    pub record_width: u32,
+    /// Largest decompressed size accepted, in bytes; 0 for the default (256 MiB).
+    pub max_output_size: u64,
+    /// Largest accepted ratio of decompressed to compressed size; 0 for unlimited.
+    pub max_ratio: f64,
+    /// Nonzero to let LZ copy from the built-in dictionary of web and JSON
+    /// tokens.
+    pub dictionary: u32,
+    /// Nonzero to replace far repeats across the whole input before
+    /// chunking, for GOUD_ALGORITHM_BEST and GOUD_ALGORITHM_CM.
+    pub long_range: u32,
+    /// One of GOUD_CHUNK_MODE_*.
+    pub chunk_mode: u32,
+}
+
+impl Default for GoudOptions {
+    fn default() -> Self {
+        GoudOptions {
+            struct_size: size_of::<GoudOptions>() as u32,
+            algorithm: GOUD_ALGORITHM_BEST,
+            level: 0,
+            chunk_size: 0,
+            record_width: 0,
+            max_output_size: 0,
+            max_ratio: 0.0,
+            dictionary: 0,
+            long_range: 0,
+            chunk_mode: GOUD_CHUNK_MODE_INDEPENDENT,
+        }
+    }
+}
+
+// The fields every caller must provide; those from `dictionary` on may be
+// left out and take their defaults
+const MIN_OPTIONS_SIZE: usize = offset_of!(GoudOptions, dictionary);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Largest output `goud_compress` can produce for `len` input bytes. LZ
+/// tokens, BWT runs and NumPack numbers cost at most twice their input, plus
+/// a few header bytes; an LZ4 frame adds 23 bytes plus 4 per 4 MiB block
+/// to its input, and a Snappy stream 10 bytes plus 8 per 64 KiB.
+#[no_mangle]
+pub extern "C" fn goud_compress_bound(len: usize) -> usize {
+    len.saturating_mul(2).saturating_add(32)
+}
+
+/// Compresses `len` bytes at `src` into `out`, returning the number of bytes
+/// written or a negative GOUD_ERROR_* code.
+///
+/// # Safety
+///
+/// `src` must be valid for reading `len` bytes (it may be NULL if `len` is
+/// 0), `out` valid for writing `out_cap` bytes, and `opts` NULL or valid.
+#[no_mangle]
+pub unsafe extern "C" fn goud_compress(
+    src: *const u8,
+    len: usize,
+    opts: *const GoudOptions,
+    out: *mut u8,
+    out_cap: usize,
+) -> i64 {
+    guard(|| {
+        let input = input_slice(src, len)?;
+        if out.is_null() {
+            return Err((GOUD_ERROR_NULL_POINTER, "out is NULL".to_string()));
+        }
+        let options = compress_options(&read_options(opts)?)?;
+        let output =
+            compression::compress(input, &options, &NoopLogger, &NoProgress).map_err(from_error)?;
+        if output.len() > out_cap {
+            return Err((
+                GOUD_ERROR_BUFFER_TOO_SMALL,
+                format!(
+                    "output needs {} bytes, out_cap is {}",
+                    output.len(),
+                    out_cap
+                ),
+            ));
+        }
+        ptr::copy_nonoverlapping(output.as_ptr(), out, output.len());
+        Ok(output.len())
+    })
+}
+
+/// Decompresses `len` bytes at `src` into a new buffer stored in `*out`,
+/// returning its length or a negative GOUD_ERROR_* code. Release the buffer
+/// with `goud_free(*out, length)`. On error `*out` is set to NULL.
+///
+/// # Safety
+///
+/// `src` must be valid for reading `len` bytes (it may be NULL if `len` is
+/// 0), `out` valid for writing one pointer, and `opts` NULL or valid.
+#[no_mangle]
+pub unsafe extern "C" fn goud_decompress(
+    src: *const u8,
+    len: usize,
+    opts: *const GoudOptions,
+    out: *mut *mut u8,
+) -> i64 {
+    if out.is_null() {
+        return fail(GOUD_ERROR_NULL_POINTER, "out is NULL".to_string());
+    }
+    *out = ptr::null_mut();
+    guard(|| {
+        let input = input_slice(src, len)?;
+        let options = decompress_options(&read_options(opts)?)?;
+        let output = decompression::decompress(input, &options, &NoopLogger, &NoProgress)
+            .map_err(from_error)?;
+        let length = output.len();
+        *out = Box::into_raw(output.into_boxed_slice()) as *mut u8;
+        Ok(length)
+    })
+}
+
+/// Frees a buffer returned by `goud_decompress`. NULL is ignored.
+///
+/// # Safety
+///
+/// `ptr` must be NULL or a buffer from `goud_decompress` with its returned
+/// length `len`, not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn goud_free(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)));
+    }
+}
+
+/// Message for the last error on this thread, or NULL if the last call
+/// succeeded. Valid until the next goud_* call on the same thread.
+#[no_mangle]
+pub extern "C" fn goud_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+type FfiResult<T> = Result<T, (i64, String)>;
+
+/// Runs `f`, recording its error (or a panic) for `goud_last_error`.
+fn guard(f: impl FnOnce() -> FfiResult<usize>) -> i64 {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(written)) => {
+            set_last_error(None);
+            written as i64
+        }
+        Ok(Err((code, message))) => fail(code, message),
+        Err(_) => fail(GOUD_ERROR_INTERNAL, "internal error".to_string()),
+    }
+}
+
+fn fail(code: i64, message: String) -> i64 {
+    set_last_error(Some(message));
+    code
+}
+
+fn set_last_error(message: Option<String>) {
+    let message = message.map(|message| CString::new(message).unwrap_or_default());
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+fn from_error(err: Error) -> (i64, String) {
+    let code = match err {
+        Error::OutputTooLarge { .. } => GOUD_ERROR_OUTPUT_TOO_LARGE,
+        Error::RatioExceeded { .. } => GOUD_ERROR_RATIO_EXCEEDED,
+        Error::Cancelled => GOUD_ERROR_CANCELLED,
+    };
+    (code, err.to_string())
+}
+
+unsafe fn input_slice<'a>(src: *const u8, len: usize) -> FfiResult<&'a [u8]> {
+    match (src.is_null(), len) {
+        (true, 0) => Ok(&[]),
+        (true, _) => Err((GOUD_ERROR_NULL_POINTER, "src is NULL".to_string())),
+        (false, _) => Ok(std::slice::from_raw_parts(src, len)),
+    }
+}
+
+/// Copies the caller's options, which may be an older, shorter struct than
+/// `GoudOptions`, so only `struct_size` bytes of it are read.
+unsafe fn read_options(opts: *const GoudOptions) -> FfiResult<GoudOptions> {
+    let mut options = GoudOptions::default();
+    if opts.is_null() {
+        return Ok(options);
+    }
+    let size = (opts as *const u32).read() as usize;
+    if size < MIN_OPTIONS_SIZE {
+        return Err(invalid(format!(
+            "struct_size {} is too small; set it to sizeof(GoudOptions)",
+            size
+        )));
+    }
+    ptr::copy_nonoverlapping(
+        opts as *const u8,
+        &mut options as *mut GoudOptions as *mut u8,
+        size.min(size_of::<GoudOptions>()),
+    );
+    Ok(options)
+}
+
+fn invalid(message: String) -> (i64, String) {
+    (GOUD_ERROR_INVALID_OPTIONS, message)
+}
+
+fn compress_options(opts: &GoudOptions) -> FfiResult<CompressOptions> {
+    let defaults = CompressOptions::default();
+    let algorithm = *Algorithm::ALL
+        .get(opts.algorithm as usize)
+        .ok_or_else(|| invalid(format!("unknown algorithm {}", opts.algorithm)))?;
+    let level = match opts.level {
+        0 => defaults.level,
+        level if (MIN_LEVEL as u32..=MAX_LEVEL as u32).contains(&level) => level as u8,
+        level => return Err(invalid(format!("level {} is not 0 or 1-9", level))),
+    };
+    let chunk_mode = *ChunkMode::ALL
+        .get(opts.chunk_mode as usize)
+        .ok_or_else(|| invalid(format!("unknown chunk_mode {}", opts.chunk_mode)))?;
+    if opts.record_width > u16::MAX as u32 {
+        return Err(invalid(format!(
+            "record_width {} exceeds 65535",
+            opts.record_width
+        )));
+    }
+
+    Ok(CompressOptions {
+        algorithm,
+        level,
+        chunk_size: match opts.chunk_size {
+            0 => defaults.chunk_size,
+            size => size as usize,
+        },
+        chunk_mode,
+        record_width: (opts.record_width != 0).then_some(opts.record_width as usize),
+        dictionary: opts.dictionary != 0,
+        long_range: opts.long_range != 0,
+        ..defaults
+    })
+}
+
+fn decompress_options(opts: &GoudOptions) -> FfiResult<DecompressOptions> {
+    let defaults = DecompressOptions::default();
+    if !(opts.max_ratio >= 0.0 && opts.max_ratio.is_finite()) {
+        return Err(invalid(format!(
+            "max_ratio {} is not a finite ratio",
+            opts.max_ratio
+        )));
+    }
+
+    Ok(DecompressOptions {
+        max_output_size: match opts.max_output_size {
+            0 => defaults.max_output_size,
+            size => usize::try_from(size).unwrap_or(usize::MAX),
+        },
+        max_ratio: (opts.max_ratio > 0.0).then_some(opts.max_ratio),
+        ..defaults
+    })
+}
@@ -0,0 +1,98 @@
+// Entry points shared by the `fuzz/` targets and the in-tree corpus replay
+// test. Not a stable API; enable with the `fuzzing` feature.
+
+use crate::constants::{
+    BWT_FLAG, CM_FLAG, COMPRESSED_FLAG, DELTA_FLAG, FSE_FLAG, NUMPACK_FLAG, RECORD_FLAG, RLE_FLAG,
+};
+use crate::decompression::limit::OutputLimit;
+use crate::decompression::long_range::decompress_long_range;
+use crate::decompression::lz4::decompress_lz4;
+use crate::decompression::snappy::{decompress_snappy, decompress_snappy_raw};
+use crate::decompression::{decompress_chunked, decompress_method};
+use crate::{
+    compress_with_options, decompress_with_options, Algorithm, CompressOptions, DecompressOptions,
+    Error, NoProgress, NoopLogger,
+};
+
+/// Output cap for the decoding targets, so bombs fail fast with an error
+/// instead of tripping the fuzzer's memory limit.
+pub const MAX_OUTPUT_SIZE: usize = 1 << 20;
+
+/// A decoder `decode_single` can select.
+#[derive(Clone, Copy, Debug)]
+pub enum Decoder {
+    /// A chain method, by its flag.
+    Method(u8),
+    Chunked,
+    Lz4,
+    /// Framed Snappy, which has no flag of its own.
+    Snappy,
+    /// Raw Snappy, as in LevelDB values.
+    SnappyRaw,
+    LongRange,
+}
+
+/// Decoders reachable from `decode_single`, indexed by its selector byte.
+pub const DECODERS: [Decoder; 13] = [
+    Decoder::Method(COMPRESSED_FLAG),
+    Decoder::Method(RLE_FLAG),
+    Decoder::Method(DELTA_FLAG),
+    Decoder::Method(BWT_FLAG),
+    Decoder::Method(NUMPACK_FLAG),
+    Decoder::Method(RECORD_FLAG),
+    Decoder::Chunked,
+    Decoder::Lz4,
+    Decoder::Method(FSE_FLAG),
+    Decoder::Method(CM_FLAG),
+    Decoder::Snappy,
+    Decoder::SnappyRaw,
+    Decoder::LongRange,
+];
+
+/// `decompress` target: the whole input is treated as a compressed stream.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    decompress_with_options(data, &options())
+}
+
+/// `decoders` target: the first byte selects a decoder, the rest is its input.
+pub fn decode_single(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let Some((&selector, payload)) = data.split_first() else {
+        return Ok(Vec::new());
+    };
+    let limit = OutputLimit::new(&options(), payload.len());
+    match DECODERS[selector as usize % DECODERS.len()] {
+        Decoder::Method(method) => decompress_method(method, payload, &limit, &NoopLogger),
+        Decoder::Chunked => decompress_chunked(payload, &limit, &NoopLogger, &NoProgress),
+        Decoder::Lz4 => decompress_lz4(payload, &limit, &NoopLogger),
+        Decoder::Snappy => decompress_snappy(payload, &limit, &NoopLogger),
+        Decoder::SnappyRaw => decompress_snappy_raw(payload, &limit, &NoopLogger),
+        Decoder::LongRange => decompress_long_range(payload, &limit, &NoopLogger, &NoProgress),
+    }
+}
+
+fn options() -> DecompressOptions {
+    DecompressOptions {
+        max_output_size: MAX_OUTPUT_SIZE,
+        ..DecompressOptions::default()
+    }
+}
+
+/// `round_trip` target: the first byte selects one of `Algorithm::ALL`, the rest
+/// is compressed and must decompress back unchanged.
+pub fn round_trip(data: &[u8]) {
+    let Some((&selector, payload)) = data.split_first() else {
+        return;
+    };
+    let options = CompressOptions {
+        algorithm: Algorithm::ALL[selector as usize % Algorithm::ALL.len()],
+        ..CompressOptions::default()
+    };
+    let compressed = compress_with_options(payload, &options);
+    let decompressed = decompress_with_options(&compressed, &DecompressOptions::default());
+    assert!(
+        decompressed.as_deref() == Ok(payload),
+        "{} round trip failed for {} byte input",
+        options.algorithm,
+        payload.len()
+    );
+}
@@ -1,18 +1,213 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 mod compression;
 mod constants;
 mod decompression;
+mod entropy;
 mod shared;
 mod utils;
 
+#[cfg(any(test, feature = "ffi"))]
+pub mod ffi;
+#[cfg(any(test, feature = "fuzzing"))]
+#[doc(hidden)]
+pub mod fuzzing;
+#[cfg(test)]
+mod tests;
+
+pub use compression::report::{CandidateReport, ChunkReport, CompressionReport, StageReport};
+pub use constants::{DEFAULT_LEVEL, DEFAULT_MAX_OUTPUT_SIZE, MAX_LEVEL, MIN_LEVEL};
+pub use decompression::inspect::{inspect, method_name, stream_name, ChunkInfo, StreamInfo};
+pub use shared::algorithm::Algorithm;
+pub use shared::error::Error;
+pub use shared::options::{ChunkMode, CompressOptions, DecompressOptions};
+pub use shared::progress::{NoProgress, Progress, ProgressHooks};
+#[cfg(feature = "std")]
+pub use utils::StderrLogger;
+pub use utils::{LevelFilter, LogLevel, Logger, NoopLogger};
+use alloc::vec::Vec;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::JsValue;
 
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const OPTIONS_TS: &'static str = r#"
+export type AlgorithmName =
+    | "best" | "auto" | "chunked"
+    | "LZ+Huffman" | "lz"
+    | "RLE" | "rle" | "Delta" | "delta" | "bwt"
+    | "NumPack" | "numpack" | "Record" | "record" | "LZ4" | "lz4"
+    | "Snappy" | "snappy" | "FSE" | "fse" | "CM" | "cm";
+export type LogLevelName = "none" | "error" | "info" | "debug" | "performance";
+
+/** Options for `compress`, `compress_with_report` and `decompress`. One
+ * object can be shared: each function ignores the keys meant for the other,
+ * but any other key, or a value of the wrong type or range, throws. */
+export interface CompressorOptions {
+    algorithm?: Algorithm | AlgorithmName;
+    /** 1 (fastest) to 9 (smallest). */
+    level?: number;
+    chunkSize?: number;
+    /** "dependent" lets each chunk's LZ copy from the end of the chunk
+     * before it; chunks then decode only in order. */
+    chunkMode?: "independent" | "dependent";
+    /** Record width for the Record algorithm, 1 to 65535. */
+    recordWidth?: number;
+    /** Let LZ copy from the built-in dictionary of web and JSON tokens. */
+    dictionary?: boolean;
+    /** Replace repeats of 64 bytes or more from anywhere earlier in the
+     * input before chunking, for `best` and `CM`. */
+    longRange?: boolean;
+    maxOutputSize?: number;
+    maxRatio?: number;
+    logLevel?: LogLevel | LogLevelName;
+    verbose?: boolean;
+    onLog?: (level: LogLevelName, message: string) => void;
+    onProgress?: (bytesDone: number, bytesTotal: number) => void;
+    signal?: AbortSignal;
+}
+"#;
+
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn compress(input: &[u8], options: &JsValue) -> Vec<u8> {
-    compression::compress(input, options)
+extern "C" {
+    #[wasm_bindgen(typescript_type = "CompressorOptions | undefined")]
+    pub type CompressorOptions;
 }
 
+/// Logs through `options.onLog(level, message)` when given, else the console.
+/// Calls `options.onProgress(bytesDone, bytesTotal)` between chunks and
+/// throws "cancelled" once `options.signal.aborted` is set.
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn decompress(input: &[u8], options: &JsValue) -> Vec<u8> {
-    decompression::decompress(input, options)
+pub fn compress(input: &[u8], options: &CompressorOptions) -> Result<Vec<u8>, JsError> {
+    let options = utils::parse_options(options).map_err(|err| JsError::new(&err))?;
+    let logger = options.logger(options.compress.log_filter());
+    compression::compress(input, &options.compress, logger.as_ref(), &options.progress)
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+/// Returns `{ data: Uint8Array, report }`, where `report` describes every
+/// chunk's candidate sizes, chosen chain, entropy and timing.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = compress_with_report)]
+pub fn compress_with_report_js(
+    input: &[u8],
+    options: &CompressorOptions,
+) -> Result<JsValue, JsError> {
+    let options = utils::parse_options(options).map_err(|err| JsError::new(&err))?;
+    let logger = options.logger(options.compress.log_filter());
+    let (data, report) = compression::compress_with_report(
+        input,
+        &options.compress,
+        logger.as_ref(),
+        &options.progress,
+    )
+    .map_err(|err| JsError::new(&err.to_string()))?;
+    Ok(utils::report_to_js(&data, &report))
+}
+
+/// Throws if the output would exceed `maxOutputSize` or `maxRatio`, or if
+/// cancelled as for `compress`.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn decompress(input: &[u8], options: &CompressorOptions) -> Result<Vec<u8>, JsError> {
+    let options = utils::parse_options(options).map_err(|err| JsError::new(&err))?;
+    let logger = options.logger(options.decompress.log_filter());
+    decompression::decompress(input, &options.decompress, logger.as_ref(), &options.progress)
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+/// Raw Snappy without the framing format, as stored by LevelDB. Only
+/// `level` and the logging options apply.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = compress_snappy_raw)]
+pub fn compress_snappy_raw_js(input: &[u8], options: &CompressorOptions) -> Result<Vec<u8>, JsError> {
+    let options = utils::parse_options(options).map_err(|err| JsError::new(&err))?;
+    let logger = options.logger(options.compress.log_filter());
+    Ok(compression::compress_snappy_raw(input, options.compress.level, logger.as_ref()))
+}
+
+/// Reads raw Snappy, which `decompress` cannot recognize. Throws as for
+/// `decompress`; malformed input is returned unchanged.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = decompress_snappy_raw)]
+pub fn decompress_snappy_raw_js(
+    input: &[u8],
+    options: &CompressorOptions,
+) -> Result<Vec<u8>, JsError> {
+    let options = utils::parse_options(options).map_err(|err| JsError::new(&err))?;
+    let logger = options.logger(options.decompress.log_filter());
+    decompression::decompress_snappy_raw(input, &options.decompress, logger.as_ref())
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+/// Native entry point for `compress`, for callers without a `JsValue`.
+/// Logs to stderr according to `options.log_level` and `options.verbose`.
+pub fn compress_with_options(input: &[u8], options: &CompressOptions) -> Vec<u8> {
+    compress_with_logger(input, options, utils::default_logger(options.log_filter()).as_ref())
+}
+
+/// `compress_with_options`, sending log messages to `logger` instead.
+pub fn compress_with_logger(input: &[u8], options: &CompressOptions, logger: &dyn Logger) -> Vec<u8> {
+    compression::compress(input, options, logger, &NoProgress).expect("NoProgress never cancels")
+}
+
+/// `compress_with_logger`, reporting to `progress` between chunks. Fails only
+/// with `Error::Cancelled`.
+pub fn compress_with_progress(
+    input: &[u8],
+    options: &CompressOptions,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+) -> Result<Vec<u8>, Error> {
+    compression::compress(input, options, logger, progress)
+}
+
+/// Native entry point for `compress_with_report`.
+pub fn compress_with_report(input: &[u8], options: &CompressOptions) -> (Vec<u8>, CompressionReport) {
+    let logger = utils::default_logger(options.log_filter());
+    compression::compress_with_report(input, options, logger.as_ref(), &NoProgress)
+        .expect("NoProgress never cancels")
+}
+
+/// Native entry point for `decompress`, for callers without a `JsValue`.
+/// Logs to stderr according to `options.log_level` and `options.verbose`.
+pub fn decompress_with_options(input: &[u8], options: &DecompressOptions) -> Result<Vec<u8>, Error> {
+    decompress_with_logger(input, options, utils::default_logger(options.log_filter()).as_ref())
+}
+
+/// `decompress_with_options`, sending log messages to `logger` instead.
+pub fn decompress_with_logger(
+    input: &[u8],
+    options: &DecompressOptions,
+    logger: &dyn Logger,
+) -> Result<Vec<u8>, Error> {
+    decompress_with_progress(input, options, logger, &NoProgress)
+}
+
+/// `decompress_with_logger`, reporting to `progress` between chunks.
+pub fn decompress_with_progress(
+    input: &[u8],
+    options: &DecompressOptions,
+    logger: &dyn Logger,
+    progress: &dyn Progress,
+) -> Result<Vec<u8>, Error> {
+    decompression::decompress(input, options, logger, progress)
+}
+
+/// Native entry point for `compress_snappy_raw`.
+pub fn compress_snappy_raw(input: &[u8], options: &CompressOptions) -> Vec<u8> {
+    let logger = utils::default_logger(options.log_filter());
+    compression::compress_snappy_raw(input, options.level, logger.as_ref())
+}
+
+/// Native entry point for `decompress_snappy_raw`.
+pub fn decompress_snappy_raw(input: &[u8], options: &DecompressOptions) -> Result<Vec<u8>, Error> {
+    let logger = utils::default_logger(options.log_filter());
+    decompression::decompress_snappy_raw(input, options, logger.as_ref())
 }
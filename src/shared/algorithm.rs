@@ -0,0 +1,97 @@
+use crate::constants::{
+    ALGO_BEST, ALGO_BWT, ALGO_CM, ALGO_DELTA, ALGO_FSE, ALGO_LZ4, ALGO_LZ_HUFFMAN, ALGO_NUMPACK, ALGO_RECORD,
+    ALGO_RLE, ALGO_SNAPPY,
+};
+use core::fmt;
+
+/// Method selected by the `algorithm` option. In JS this is the numeric
+/// `Algorithm` enum; the option also accepts the names `parse` understands.
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Algorithm {
+    /// Chunked compression, picking a chain of methods per chunk.
+    #[default]
+    Best = 0,
+    LzHuffman = 1,
+    Rle = 2,
+    Delta = 3,
+    Bwt = 4,
+    NumPack = 5,
+    Record = 6,
+    /// A standard LZ4 frame, for speed over ratio.
+    Lz4 = 7,
+    /// A Snappy framing-format stream, as read by other Snappy libraries.
+    Snappy = 8,
+    /// Table-based asymmetric numeral system (FSE) coding of the bytes alone.
+    Fse = 9,
+    /// Chunked, coding each chunk with context mixing: the best ratio, at a
+    /// small fraction of the speed.
+    Cm = 10,
+}
+
+impl Algorithm {
+    /// Every algorithm, in discriminant order.
+    pub const ALL: [Algorithm; 11] = [
+        Algorithm::Best,
+        Algorithm::LzHuffman,
+        Algorithm::Rle,
+        Algorithm::Delta,
+        Algorithm::Bwt,
+        Algorithm::NumPack,
+        Algorithm::Record,
+        Algorithm::Lz4,
+        Algorithm::Snappy,
+        Algorithm::Fse,
+        Algorithm::Cm,
+    ];
+
+    /// Canonical name, as used in logs and reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Best => ALGO_BEST,
+            Algorithm::LzHuffman => ALGO_LZ_HUFFMAN,
+            Algorithm::Rle => ALGO_RLE,
+            Algorithm::Delta => ALGO_DELTA,
+            Algorithm::Bwt => ALGO_BWT,
+            Algorithm::NumPack => ALGO_NUMPACK,
+            Algorithm::Record => ALGO_RECORD,
+            Algorithm::Lz4 => ALGO_LZ4,
+            Algorithm::Snappy => ALGO_SNAPPY,
+            Algorithm::Fse => ALGO_FSE,
+            Algorithm::Cm => ALGO_CM,
+        }
+    }
+
+    /// Parses a canonical name or one of its aliases ("auto" and "chunked"
+    /// for best, "lz" for LZ+Huffman, "context mixing" for CM), ignoring
+    /// case and any `+`, `-`, `_` or space, so "lz-huffman" and "BWT" are
+    /// accepted too.
+    pub fn parse(value: &str) -> Option<Algorithm> {
+        let mut key = [0u8; 16];
+        let mut len = 0;
+        for byte in value.bytes().filter(|byte| !b"+-_ ".contains(byte)) {
+            *key.get_mut(len)? = byte.to_ascii_lowercase();
+            len += 1;
+        }
+        match &key[..len] {
+            b"best" | b"auto" | b"chunked" => Some(Algorithm::Best),
+            b"lzhuffman" | b"lz" => Some(Algorithm::LzHuffman),
+            b"rle" => Some(Algorithm::Rle),
+            b"delta" => Some(Algorithm::Delta),
+            b"bwt" => Some(Algorithm::Bwt),
+            b"numpack" => Some(Algorithm::NumPack),
+            b"record" => Some(Algorithm::Record),
+            b"lz4" => Some(Algorithm::Lz4),
+            b"snappy" => Some(Algorithm::Snappy),
+            b"fse" => Some(Algorithm::Fse),
+            b"cm" | b"contextmixing" => Some(Algorithm::Cm),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
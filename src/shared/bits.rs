@@ -0,0 +1,104 @@
+// Bit streams for the entropy coders, packed LSB first.
+
+use alloc::vec::Vec;
+
+#[inline]
+pub fn highest_bit(value: u32) -> u32 {
+    31 - value.leading_zeros()
+}
+
+pub struct BitWriter<'a> {
+    out: &'a mut Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl<'a> BitWriter<'a> {
+    pub fn new(out: &'a mut Vec<u8>) -> Self {
+        BitWriter {
+            out,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    pub fn write(&mut self, value: u32, bits: u32) {
+        self.buffer |= (value as u64) << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Writes `value`, at least 1, as an Elias gamma code: as many zero bits
+    /// as it has bits after the highest, then the value from that bit down.
+    pub fn write_gamma(&mut self, value: u32) {
+        let bits = highest_bit(value);
+        self.write(0, bits);
+        self.write(1, 1);
+        self.write(value & ((1 << bits) - 1), bits);
+    }
+
+    /// Pads the last byte with zeros.
+    pub fn finish(self) {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+    }
+}
+
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    /// Position in bits.
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        BitReader { data, pos: 0 }
+    }
+
+    pub fn read(&mut self, bits: u32) -> Option<u32> {
+        let mut value = 0;
+        let mut done = 0;
+        while done < bits {
+            let byte = *self.data.get(self.pos / 8)? as u32;
+            let offset = (self.pos % 8) as u32;
+            let take = (8 - offset).min(bits - done);
+            value |= ((byte >> offset) & ((1 << take) - 1)) << done;
+            done += take;
+            self.pos += take as usize;
+        }
+        Some(value)
+    }
+
+    #[inline]
+    pub fn read_bit(&mut self) -> Option<u32> {
+        let byte = *self.data.get(self.pos / 8)?;
+        let bit = (byte >> (self.pos % 8)) & 1;
+        self.pos += 1;
+        Some(bit as u32)
+    }
+
+    pub fn read_gamma(&mut self) -> Option<u32> {
+        let mut bits = 0;
+        while self.read_bit()? == 0 {
+            bits += 1;
+            if bits > 31 {
+                return None;
+            }
+        }
+        Some(1 << bits | self.read(bits)?)
+    }
+
+    /// Whether everything but the zero padding of the last byte was read.
+    pub fn at_end(&self) -> bool {
+        let padding = self.data.len() * 8 - self.pos;
+        match self.data.last() {
+            Some(&last) if padding > 0 => padding < 8 && last >> (8 - padding) == 0,
+            _ => padding == 0,
+        }
+    }
+}
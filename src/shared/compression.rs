@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 #[derive(Debug)]
 pub enum CompressionResult {
     Compressed(Vec<u8>, u8),  // (data, flag)
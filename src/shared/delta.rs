@@ -0,0 +1,26 @@
+// Element helpers shared by the strided delta encoder and decoder.
+
+/// Element widths (in bytes) the delta transform can operate on.
+pub const DELTA_WIDTHS: [usize; 4] = [1, 2, 4, 8];
+
+/// Reads a little-endian integer of `bytes.len()` bytes (at most 8).
+#[inline]
+pub fn read_element(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0u64, |acc, &b| (acc << 8) | b as u64)
+}
+
+/// Writes the low `out.len()` bytes of `value` in little-endian order.
+#[inline]
+pub fn write_element(out: &mut [u8], value: u64) {
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = (value >> (8 * i)) as u8;
+    }
+}
+
+/// Returns true if `stride`/`width` describe a transform the decoder accepts.
+pub fn is_valid_layout(stride: usize, width: usize) -> bool {
+    DELTA_WIDTHS.contains(&width) && stride >= width && stride.is_multiple_of(width)
+}
@@ -0,0 +1,102 @@
+// Built-in static dictionary for LZ+Huffman. With the `dictionary` option,
+// matches may copy from these bytes as well as from the window, which helps
+// small web payloads that have too little history of their own.
+//
+// Streams refer to it by offset, so its contents are part of the format:
+// append to it if it must grow, never edit or reorder what is here.
+
+#[rustfmt::skip]
+pub const STATIC_DICTIONARY: &[u8] = concat!(
+    // JSON structure and literals
+    "{\"", "\":\"", "\",\"", "\":{\"", "\"},{\"", "\":[{\"", "\"}],\"", "\":[\"", "\"],\"",
+    "\":true,\"", "\":false,\"", "\":null,\"", "\":true}", "\":false}", "\":null}", "\":[]",
+    "\":{}", "\":0,\"", "\":1,\"", "\"}]}", "\"}}", "]}", "},{", "\\\"", "\\n", "\\u00", "\"id\":",
+    "\"name\":\"", "\"type\":\"", "\"value\":", "\"data\":", "\"key\":\"", "\"status\":\"",
+    "\"message\":\"", "\"error\":", "\"errors\":[", "\"code\":", "\"result\":", "\"results\":[",
+    "\"items\":[", "\"count\":", "\"total\":", "\"page\":", "\"per_page\":", "\"limit\":",
+    "\"offset\":", "\"next\":", "\"user\":{", "\"users\":[", "\"user_id\":", "\"username\":\"",
+    "\"email\":\"", "\"first_name\":\"", "\"last_name\":\"", "\"title\":\"", "\"description\":\"",
+    "\"content\":\"", "\"text\":\"", "\"url\":\"", "\"image\":\"", "\"avatar_url\":\"",
+    "\"price\":", "\"currency\":\"USD\"", "\"quantity\":", "\"created_at\":\"", "\"updated_at\":\"",
+    "\"deleted_at\":", "\"timestamp\":", "\"date\":\"", "\"version\":\"", "\"tags\":[",
+    "\"category\":\"", "\"enabled\":true", "\"success\":true", "\"success\":false",
+    "\"metadata\":{", "\"attributes\":{", "\"address\":{", "\"city\":\"", "\"country\":\"",
+    "\"phone\":\"", "\"latitude\":", "\"longitude\":", "\"token\":\"", "\"expires_in\":",
+    "\"access_token\":\"",
+    // ISO 8601 dates and times
+    "T00:00:00Z", "T00:00:00.000Z", ":00:00", ":00.000Z", ".000Z", "+00:00", "-01-01T", "2020-",
+    "2021-", "2022-", "2023-", "2024-", "2025-", "2026-", "2027-", "-01-", "-02-", "-03-", "-04-",
+    "-05-", "-06-", "-07-", "-08-", "-09-", "-10-", "-11-", "-12-", "January", "February", "March",
+    "April", "May", "June", "July", "August", "September", "October", "November", "December",
+    "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday", " GMT", " UTC",
+    // HTTP
+    "HTTP/1.1 200 OK\r\n", "HTTP/1.1 ", "HTTP/2", "GET ", "POST ", "PUT ", "DELETE ",
+    "Content-Type: ", "Content-Length: ", "Content-Encoding: ", "Cache-Control: ", "max-age=",
+    "no-cache", "no-store", "Accept: ", "Accept-Encoding: gzip, deflate, br",
+    "Accept-Language: en-US,en;q=0.9", "Authorization: Bearer ", "User-Agent: Mozilla/5.0 ",
+    "Set-Cookie: ", "; Path=/; HttpOnly; Secure", "Location: ", "Host: ", "Connection: keep-alive",
+    "ETag: ", "Last-Modified: ", "Date: ", "Vary: Accept-Encoding", "Access-Control-Allow-Origin: ",
+    "X-Requested-With", "application/json", "application/javascript", "application/xml",
+    "application/x-www-form-urlencoded", "multipart/form-data", "text/html; charset=utf-8",
+    "text/plain", "text/css", "image/png", "image/jpeg", "image/svg+xml", "charset=UTF-8",
+    "\r\n\r\n", "\r\n",
+    // URLs
+    "https://www.", "https://", "http://", ".com/", ".org/", ".net/", ".io/", "/api/v1/", "/api/",
+    "/index.html", "?utm_source=", "&utm_medium=", "&utm_campaign=", "?page=", "&limit=", "?q=",
+    "%20", "localhost:", "www.w3.org/2000/svg",
+    // HTML
+    "<!DOCTYPE html>", "<html lang=\"en\">", "<head>", "</head>", "<body>", "</body>", "</html>",
+    "<meta charset=\"utf-8\">",
+    "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">",
+    "<meta name=\"description\" content=\"", "<meta property=\"og:", "<title>", "</title>",
+    "<link rel=\"stylesheet\" href=\"", "<link rel=\"icon\" href=\"", "<script src=\"",
+    "<script type=\"text/javascript\">", "<script>", "</script>", "<style>", "</style>",
+    "<div class=\"", "<div id=\"", "</div>", "<span class=\"", "</span>", "<a href=\"", "</a>",
+    "<img src=\"", "\" alt=\"", "<p>", "</p>", "<br>", "<br />", "<ul>", "</ul>", "<li>", "</li>",
+    "<li class=\"", "<h1>", "</h1>", "<h2>", "</h2>", "<h3>", "</h3>",
+    "<button type=\"button\" class=\"", "</button>", "<input type=\"text\" name=\"",
+    "<input type=\"hidden\" name=\"", "<form action=\"", "</form>", "<label for=\"", "</label>",
+    "<table>", "</table>", "<tr>", "</tr>", "<td>", "</td>", "<th>", "</th>", "<nav>", "</nav>",
+    "<header>", "</header>", "<footer>", "</footer>", "<section", "</section>", "<svg ", "</svg>",
+    "<path d=\"", "&nbsp;", "&amp;", "&quot;", "&lt;", "&gt;", "\" class=\"", "\" id=\"",
+    "\" style=\"", "\" target=\"_blank\" rel=\"noopener noreferrer\"", " aria-label=\"",
+    " aria-hidden=\"true\"", " data-", " role=\"", " width=\"", " height=\"", "<!-- ", " -->",
+    // CSS
+    "display:flex;", "display:none;", "display:block;", "display:inline-block;", "display: flex;",
+    "display: none;", "position:absolute;", "position:relative;", "position: absolute;",
+    "position: relative;", "margin:0;", "padding:0;", "margin: 0;", "padding: 0;", "margin-top:",
+    "margin-bottom:", "margin-left:", "margin-right:", "padding-top:", "padding-bottom:",
+    "padding-left:", "padding-right:", "width:100%;", "height:100%;", "width: 100%;",
+    "height: 100%;", "font-family:", "font-size:", "font-weight:", "line-height:",
+    "text-align:center;", "text-decoration:none;", "color:#", "color: #", "background-color:",
+    "background:", "border:1px solid ", "border: 1px solid ", "border-radius:",
+    "box-sizing:border-box;", "box-shadow:", "align-items:center;", "justify-content:",
+    "flex-direction:", "cursor:pointer;", "overflow:hidden;", "transition:", "transform:",
+    "opacity:", "z-index:", "!important", "px;", "px ", "em;", "rem;", "%;", "rgba(0,0,0,",
+    "rgba(255,255,255,", "#fff;", "#000;", "@media (max-width:", "@media screen and (",
+    "sans-serif", "Helvetica", "Arial", "inherit", "solid", "none;", "auto;",
+    // JavaScript
+    "function(", "function ", "return ", "const ", "let ", "var ", " => ", "=>{", "undefined",
+    "null", "true", "false", "this.", "new ", "typeof ", "instanceof ",
+    "document.getElementById(\"", "document.querySelector(\"", "document.createElement(\"",
+    "window.", "addEventListener(\"", "removeEventListener(\"", "console.log(", "JSON.stringify(",
+    "JSON.parse(", "Object.keys(", "Array.isArray(", "Promise", "async ", "await ",
+    "export default ", "export const ", "import ", " from \"", "require(\"", "module.exports",
+    ".prototype.", ".length", ".push(", ".map(", ".filter(", ".forEach(", ".then(", ".catch(",
+    "setTimeout(", "fetch(\"", "throw new Error(\"", "if (", "} else {", "for (let i = 0; i < ",
+    "while (", "switch (", "case ", "break;", "try {", "} catch (e) {", "===", "!==", "&&", "||",
+    // English, with the spacing they usually appear with
+    " the ", " and ", " of ", " to ", " in ", " is ", " for ", " that ", " with ", " on ", " as ",
+    " are ", " this ", " be ", " by ", " from ", " at ", " or ", " an ", " was ", " have ", " not ",
+    " it ", " you ", " your ", " will ", " can ", " all ", " has ", " more ", " about ", " which ",
+    " their ", " there ", " when ", " what ", " been ", " would ", " other ", " into ", " only ",
+    " also ", " new ", " some ", " these ", " than ", " them ", " time ", " first ",
+    " information ", " please ", " account ", " service ", " available ", " search ", " page ",
+    " home ", " contact ", " privacy ", " policy ", " terms ", " product ", " products ", " price ",
+    " order ", " email ", " password ", " address ", " number ", " update ", " error ", " request ",
+    " response ", "Click here", "Learn more", "Read more", "Sign in", "Sign up", "Log in",
+    "Log out", "Terms of Service", "Privacy Policy", "All rights reserved", "Copyright ",
+    "Not Found", "Internal Server Error", "Bad Request", "Unauthorized", "Forbidden", "tion",
+    "ment", "ing ", "ed ", "ly ", "es ", "er ", "'s ", ". The ", ", and ", ". ", ", ",
+)
+.as_bytes();
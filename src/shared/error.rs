@@ -0,0 +1,29 @@
+use core::fmt;
+
+/// Why a call failed. Malformed streams are not errors; decoders log them and
+/// pass the data through.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// Decompressed output would exceed `max_output_size` bytes.
+    OutputTooLarge { limit: usize },
+    /// Decompressed output would exceed `max_ratio` times the input size.
+    RatioExceeded { limit: f64 },
+    /// The caller's `Progress` asked to stop.
+    Cancelled,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OutputTooLarge { limit } => {
+                write!(f, "decompressed output exceeds max_output_size of {} bytes", limit)
+            }
+            Error::RatioExceeded { limit } => {
+                write!(f, "decompressed output exceeds max_ratio of {}", limit)
+            }
+            Error::Cancelled => f.write_str("cancelled"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
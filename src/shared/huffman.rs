@@ -0,0 +1,83 @@
+// Alphabets of Huffman-coded LZ tokens, split as in DEFLATE. Literal bytes,
+// the end of the stream and match lengths share one alphabet; offsets have
+// their own. A length or offset is coded as the symbol of its bucket
+// followed by extra bits that place it within the bucket.
+//
+// Literal/length symbols: 0-255 literals, END_OF_STREAM, then 28 buckets for
+// lengths from MIN_MATCH_LEN. Offset symbols: one per entry of
+// `RecentOffsets`, which repeats that offset without extra bits, then 24
+// buckets for window offsets 1..=WINDOW_SIZE, then 32 for static dictionary
+// positions 0..=u16::MAX.
+
+use crate::shared::bits::highest_bit;
+use crate::shared::token::REPEAT_OFFSETS;
+
+pub const END_OF_STREAM: usize = 256;
+pub const FIRST_LENGTH_SYMBOL: usize = 257;
+pub const LITERAL_LENGTH_SYMBOLS: usize = FIRST_LENGTH_SYMBOL + 28;
+pub const FIRST_WINDOW_SYMBOL: usize = REPEAT_OFFSETS;
+pub const FIRST_DICTIONARY_SYMBOL: usize = FIRST_WINDOW_SYMBOL + 24;
+pub const OFFSET_SYMBOLS: usize = FIRST_DICTIONARY_SYMBOL + 32;
+pub const MAX_CODE_LEN: u32 = 15;
+
+const LENGTH_GROUP_LOG: u32 = 2; // Four buckets per extra bit, like DEFLATE lengths
+const OFFSET_GROUP_LOG: u32 = 1; // Two, like DEFLATE distances
+
+/// A bucket symbol and the extra bits after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bucket {
+    pub symbol: usize,
+    pub extra_bits: u32,
+    pub extra: u32,
+}
+
+/// `value - MIN_MATCH_LEN` for lengths, `offset - 1` for window offsets and
+/// the position itself for dictionary matches.
+fn bucket(value: u32, group_log: u32, first_symbol: usize) -> Bucket {
+    let extra_bits = highest_bit(value | 1).saturating_sub(group_log);
+    Bucket {
+        symbol: first_symbol + ((extra_bits << group_log) + (value >> extra_bits)) as usize,
+        extra_bits,
+        extra: value & ((1 << extra_bits) - 1),
+    }
+}
+
+/// Inverse of `bucket`: the smallest value in bucket `code` and its extra bits.
+fn bucket_base(code: u32, group_log: u32) -> (u32, u32) {
+    if code < 2 << group_log {
+        return (code, 0);
+    }
+    let extra_bits = (code >> group_log) - 1;
+    let base = ((code & ((1 << group_log) - 1)) + (1 << group_log)) << extra_bits;
+    (base, extra_bits)
+}
+
+pub fn length_bucket(length_from_min: u32) -> Bucket {
+    bucket(length_from_min, LENGTH_GROUP_LOG, FIRST_LENGTH_SYMBOL)
+}
+
+pub fn offset_bucket(offset: u32) -> Bucket {
+    bucket(offset - 1, OFFSET_GROUP_LOG, FIRST_WINDOW_SYMBOL)
+}
+
+pub fn dictionary_bucket(position: u32) -> Bucket {
+    bucket(position, OFFSET_GROUP_LOG, FIRST_DICTIONARY_SYMBOL)
+}
+
+/// Length above MIN_MATCH_LEN at the start of a length symbol's bucket, and
+/// its extra bits.
+pub fn length_base(symbol: usize) -> (u32, u32) {
+    bucket_base((symbol - FIRST_LENGTH_SYMBOL) as u32, LENGTH_GROUP_LOG)
+}
+
+/// Smallest offset, or dictionary position, of the bucket of an offset
+/// symbol from FIRST_WINDOW_SYMBOL on, and its extra bits.
+pub fn offset_base(symbol: usize) -> (u32, u32) {
+    if symbol < FIRST_DICTIONARY_SYMBOL {
+        let (base, extra_bits) =
+            bucket_base((symbol - FIRST_WINDOW_SYMBOL) as u32, OFFSET_GROUP_LOG);
+        (base + 1, extra_bits)
+    } else {
+        bucket_base((symbol - FIRST_DICTIONARY_SYMBOL) as u32, OFFSET_GROUP_LOG)
+    }
+}
@@ -1,2 +1,13 @@
 pub mod token;
+pub mod algorithm;
+pub mod bits;
 pub mod compression;
+pub mod crc32c;
+pub mod delta;
+pub mod dictionary;
+pub mod error;
+pub mod huffman;
+pub mod options;
+pub mod progress;
+pub mod varint;
+pub mod xxhash;
@@ -0,0 +1,117 @@
+use crate::compression::strategies::CHUNK_SIZE;
+use crate::constants::{
+    CHUNK_MODE_DEPENDENT, CHUNK_MODE_INDEPENDENT, DEFAULT_LEVEL, DEFAULT_MAX_OUTPUT_SIZE,
+};
+use crate::shared::algorithm::Algorithm;
+use crate::utils::{LevelFilter, LogLevel};
+
+/// Settings for a single `compress` call, parsed from the caller's options.
+#[derive(Debug, Clone)]
+pub struct CompressOptions {
+    pub algorithm: Algorithm,
+    pub log_level: LogLevel,
+    /// Also log performance messages, whatever `log_level` is.
+    pub verbose: bool,
+    /// Effort from `MIN_LEVEL` (fastest) to `MAX_LEVEL` (smallest output).
+    pub level: u8,
+    /// Bytes per chunk for chunked compression.
+    pub chunk_size: usize,
+    /// Whether chunks may copy from the chunk before them.
+    pub chunk_mode: ChunkMode,
+    /// Record width for the Record transform; detected per chunk when `None`.
+    pub record_width: Option<usize>,
+    /// Let LZ matches copy from the built-in dictionary of common web and
+    /// JSON tokens, which helps payloads of a few KB.
+    pub dictionary: bool,
+    /// Replace repeats of 64 bytes or more anywhere earlier in the input
+    /// with references before chunking, for the chunked algorithms.
+    pub long_range: bool,
+}
+
+impl CompressOptions {
+    pub fn log_filter(&self) -> LevelFilter {
+        LevelFilter {
+            level: self.log_level,
+            verbose: self.verbose,
+        }
+    }
+}
+
+impl Default for CompressOptions {
+    fn default() -> Self {
+        CompressOptions {
+            algorithm: Algorithm::Best,
+            log_level: LogLevel::None,
+            verbose: false,
+            level: DEFAULT_LEVEL,
+            chunk_size: CHUNK_SIZE,
+            chunk_mode: ChunkMode::Independent,
+            record_width: None,
+            dictionary: false,
+            long_range: false,
+        }
+    }
+}
+
+/// How the chunks of a chunked stream relate to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChunkMode {
+    /// Every chunk decodes on its own.
+    #[default]
+    Independent,
+    /// When LZ is a chunk's first stage, its window starts with the end of
+    /// the chunk before it, which pays off on data that repeats across
+    /// chunks, such as NDJSON. Chunks that copy from it decode only after
+    /// their predecessor.
+    Dependent,
+}
+
+impl ChunkMode {
+    pub const ALL: [ChunkMode; 2] = [ChunkMode::Independent, ChunkMode::Dependent];
+
+    /// Parses a name, ignoring case.
+    pub fn parse(value: &str) -> Option<ChunkMode> {
+        ChunkMode::ALL
+            .into_iter()
+            .find(|mode| mode.as_str().eq_ignore_ascii_case(value))
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChunkMode::Independent => CHUNK_MODE_INDEPENDENT,
+            ChunkMode::Dependent => CHUNK_MODE_DEPENDENT,
+        }
+    }
+}
+
+/// Settings for a single `decompress` call.
+#[derive(Debug, Clone)]
+pub struct DecompressOptions {
+    pub log_level: LogLevel,
+    /// Also log performance messages, whatever `log_level` is.
+    pub verbose: bool,
+    /// Largest decompressed size accepted, in bytes.
+    pub max_output_size: usize,
+    /// Largest accepted ratio of decompressed to compressed size; unlimited when `None`.
+    pub max_ratio: Option<f64>,
+}
+
+impl DecompressOptions {
+    pub fn log_filter(&self) -> LevelFilter {
+        LevelFilter {
+            level: self.log_level,
+            verbose: self.verbose,
+        }
+    }
+}
+
+impl Default for DecompressOptions {
+    fn default() -> Self {
+        DecompressOptions {
+            log_level: LogLevel::None,
+            verbose: false,
+            max_output_size: DEFAULT_MAX_OUTPUT_SIZE,
+            max_ratio: None,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::shared::error::Error;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Hooks for long `compress` and `decompress` calls. Chunked streams report
+/// after every chunk and check for cancellation before the next one; other
+/// methods only check once, before they start.
+pub trait Progress {
+    /// `done` of `total` bytes handled: input bytes when compressing, output
+    /// bytes (against the header's size) when decompressing.
+    fn report(&self, done: usize, total: usize);
+    /// True once the caller wants the call to stop with `Error::Cancelled`.
+    fn is_cancelled(&self) -> bool;
+}
+
+/// Reports nowhere and never cancels.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn report(&self, _done: usize, _total: usize) {}
+
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Native hooks: a progress closure and a flag another thread can set to
+/// cancel.
+#[derive(Clone, Copy, Default)]
+pub struct ProgressHooks<'a> {
+    pub on_progress: Option<&'a dyn Fn(usize, usize)>,
+    pub cancel: Option<&'a AtomicBool>,
+}
+
+impl Progress for ProgressHooks<'_> {
+    fn report(&self, done: usize, total: usize) {
+        if let Some(on_progress) = self.on_progress {
+            on_progress(done, total);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+}
+
+/// `Err(Error::Cancelled)` if `progress` asks to stop.
+pub(crate) fn check_cancelled(progress: &dyn Progress) -> Result<(), Error> {
+    if progress.is_cancelled() {
+        Err(Error::Cancelled)
+    } else {
+        Ok(())
+    }
+}
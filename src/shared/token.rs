@@ -1,5 +1,43 @@
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Token {
     Literal(u8),
     Match(u16, u16),
+    /// Position in `STATIC_DICTIONARY` and length.
+    DictMatch(u16, u16),
+}
+
+pub const REPEAT_OFFSETS: usize = 3;
+
+/// Offsets of the latest window matches, most recent first. The matcher, the
+/// Huffman encoder and its decoder all update it after every `Match`, so a
+/// match can name its offset by its place here instead of in full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecentOffsets([u16; REPEAT_OFFSETS]);
+
+impl Default for RecentOffsets {
+    /// Small offsets, which runs and short periods repeat.
+    fn default() -> Self {
+        RecentOffsets([1, 4, 8])
+    }
+}
+
+impl RecentOffsets {
+    pub fn get(&self, index: usize) -> u16 {
+        self.0[index]
+    }
+
+    pub fn offsets(&self) -> [u16; REPEAT_OFFSETS] {
+        self.0
+    }
+
+    pub fn position(&self, offset: u16) -> Option<usize> {
+        self.0.iter().position(|&recent| recent == offset)
+    }
+
+    /// Moves `offset` to the front, dropping the oldest if it is new.
+    pub fn push(&mut self, offset: u16) {
+        let index = self.position(offset).unwrap_or(REPEAT_OFFSETS - 1);
+        self.0.copy_within(..index, 1);
+        self.0[0] = offset;
+    }
 }
@@ -0,0 +1,128 @@
+// Dependent chunks: gains on NDJSON spread over many chunks, the per-chunk
+// marker, round trips at awkward chunk sizes, history that only reaches the
+// previous chunk, and no marker for chunks that don't copy from it.
+
+use super::rng::{gen_input, Rng};
+use crate::compression::strategies::compress_lz_with_history;
+use crate::constants::{
+    CHUNKED_FLAG, CHUNK_DEPENDS_ON_PREVIOUS, COMPRESSED_FLAG, DEFAULT_LEVEL, WINDOW_SIZE,
+};
+use crate::{
+    compress_with_options, decompress_with_options, inspect, ChunkMode, CompressOptions,
+    DecompressOptions, NoopLogger,
+};
+
+fn options(chunk_mode: ChunkMode, chunk_size: usize) -> CompressOptions {
+    CompressOptions {
+        chunk_mode,
+        chunk_size,
+        ..CompressOptions::default()
+    }
+}
+
+fn decompress(stream: &[u8]) -> Vec<u8> {
+    decompress_with_options(stream, &DecompressOptions::default()).unwrap()
+}
+
+/// Log events, one JSON object per line.
+fn ndjson(rng: &mut Rng, lines: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    for line in 0..lines {
+        out.extend_from_slice(
+            format!(
+                "{{\"ts\":\"2024-03-18T09:{:02}:{:02}Z\",\"level\":\"info\",\"service\":\"checkout\",\"event\":\"order_placed\",\"order_id\":{},\"items\":{}}}\n",
+                line / 60 % 60,
+                line % 60,
+                100_000 + rng.below(900_000),
+                rng.below(9) + 1
+            )
+            .as_bytes(),
+        );
+    }
+    out
+}
+
+#[test]
+fn ndjson_gains_from_dependent_chunks() {
+    let input = ndjson(&mut Rng::new(49), 400);
+    let independent = compress_with_options(&input, &options(ChunkMode::Independent, 2048));
+    let dependent = compress_with_options(&input, &options(ChunkMode::Dependent, 2048));
+    assert!(
+        dependent.len() < independent.len() * 4 / 5,
+        "{} bytes dependent, {} independent",
+        dependent.len(),
+        independent.len()
+    );
+    assert_eq!(decompress(&dependent), input);
+
+    // Only chunks after the first can depend on one
+    let chunks = inspect(&dependent).unwrap().chunks;
+    assert!(!chunks[0].depends_on_previous);
+    assert!(chunks[1..].iter().all(|chunk| chunk.depends_on_previous));
+    let chunks = inspect(&independent).unwrap().chunks;
+    assert!(chunks.iter().all(|chunk| !chunk.depends_on_previous));
+}
+
+#[test]
+fn dependent_chunks_round_trip() {
+    let mut rng = Rng::new(0xC4);
+    for case in 0..8 {
+        let input = gen_input(&mut rng, 4096);
+        for chunk_size in [1, 100, 3000, WINDOW_SIZE - 1] {
+            let compressed =
+                compress_with_options(&input, &options(ChunkMode::Dependent, chunk_size));
+            assert!(
+                decompress(&compressed) == input,
+                "case {}, chunks of {}",
+                case,
+                chunk_size
+            );
+        }
+    }
+}
+
+#[test]
+fn history_is_the_previous_chunk() {
+    // Two chunks that each start with a copy of the previous one's end
+    let mut rng = Rng::new(50);
+    let first = rng.bytes(400);
+    let second = [&first[100..], &rng.bytes(100)[..]].concat();
+    let (lz, used_history) =
+        compress_lz_with_history(&first, &second, DEFAULT_LEVEL, false, &NoopLogger);
+    assert!(used_history);
+    assert!(lz.len() < second.len() * 3 / 4);
+
+    let mut stream = vec![CHUNKED_FLAG];
+    stream.extend_from_slice(&2u32.to_le_bytes());
+    stream.extend_from_slice(&800u32.to_le_bytes());
+    stream.extend_from_slice(&400u32.to_le_bytes());
+    stream.push(0);
+    stream.extend_from_slice(&first);
+    stream.extend_from_slice(&(lz.len() as u32).to_le_bytes());
+    stream.extend_from_slice(&[1 | CHUNK_DEPENDS_ON_PREVIOUS, COMPRESSED_FLAG]);
+    stream.extend_from_slice(&lz);
+    assert_eq!(decompress(&stream), [&first[..], &second].concat());
+
+    // Without the marker, the copy reaches before the start and is cut short
+    let marker = 14 + first.len() + 4;
+    stream[marker] &= !CHUNK_DEPENDS_ON_PREVIOUS;
+    assert_ne!(decompress(&stream), [&first[..], &second].concat());
+}
+
+#[test]
+fn chunks_that_copy_nothing_back_are_independent() {
+    // The second chunk repeats itself but shares nothing with the first
+    let mut rng = Rng::new(0x49);
+    let first = rng.bytes(2048);
+    let second = rng.bytes(256).repeat(8);
+    let (_, used_history) =
+        compress_lz_with_history(&first, &second, DEFAULT_LEVEL, false, &NoopLogger);
+    assert!(!used_history);
+
+    let input = [first, second].concat();
+    let compressed = compress_with_options(&input, &options(ChunkMode::Dependent, 2048));
+    let chunks = inspect(&compressed).unwrap().chunks;
+    assert_eq!(chunks[1].methods.first(), Some(&COMPRESSED_FLAG));
+    assert!(chunks.iter().all(|chunk| !chunk.depends_on_previous));
+    assert_eq!(decompress(&compressed), input);
+}
@@ -0,0 +1,125 @@
+// Context mixing: its ratio on logs against the default chain, a pinned
+// stream guarding the model set, and rejection of truncated streams.
+
+use super::rng::Rng;
+use crate::constants::{CHUNKED_FLAG, CM_FLAG};
+use crate::entropy::cm;
+use crate::shared::varint::write_varint;
+use crate::{
+    compress_with_options, decompress_with_options, inspect, Algorithm, CompressOptions,
+    DecompressOptions,
+};
+
+const LEVELS: [&str; 4] = ["INFO", "INFO", "WARN", "ERROR"];
+const PATHS: [&str; 5] = [
+    "/api/v1/users",
+    "/api/v1/orders",
+    "/health",
+    "/login",
+    "/api/v1/items",
+];
+
+fn cm_options() -> CompressOptions {
+    CompressOptions {
+        algorithm: Algorithm::Cm,
+        ..CompressOptions::default()
+    }
+}
+
+fn decode(stream: &[u8]) -> Option<Vec<u8>> {
+    cm::decode(stream, |_| Ok(())).unwrap()
+}
+
+fn log_lines(rng: &mut Rng, lines: usize) -> Vec<u8> {
+    let mut log = String::new();
+    for line in 0..lines {
+        log.push_str(&format!(
+            "2024-03-18T09:{:02}:{:02}.{:03}Z {} [worker-{}] GET {} status={} duration_ms={} request_id={:08x}\n",
+            line / 60 % 60,
+            line % 60,
+            rng.below(1000),
+            LEVELS[rng.below(LEVELS.len())],
+            rng.below(8),
+            PATHS[rng.below(PATHS.len())],
+            [200, 200, 404, 500][rng.below(4)],
+            rng.below(900),
+            rng.next_u64() as u32
+        ));
+    }
+    log.into_bytes()
+}
+
+#[test]
+fn logs_compress_far_better_than_the_default_chain() {
+    let input = log_lines(&mut Rng::new(1), 800);
+    let best = compress_with_options(&input, &CompressOptions::default());
+    let compressed = compress_with_options(&input, &cm_options());
+    assert!(
+        compressed.len() < best.len() * 4 / 5,
+        "{} bytes with CM, {} with best",
+        compressed.len(),
+        best.len()
+    );
+
+    // Chunked, with every chunk coded by CM alone
+    assert_eq!(compressed[0], CHUNKED_FLAG);
+    let info = inspect(&compressed).unwrap();
+    assert!(info.chunks.len() > 1);
+    assert!(info.chunks.iter().all(|chunk| chunk.methods == [CM_FLAG]));
+
+    let decoded = decompress_with_options(&compressed, &DecompressOptions::default());
+    assert_eq!(decoded, Ok(input));
+}
+
+#[test]
+fn incompressible_chunks_are_stored() {
+    let mut rng = Rng::new(4);
+    let input = [rng.bytes(4096), log_lines(&mut rng, 40)].concat();
+    let options = CompressOptions {
+        chunk_size: 4096,
+        ..cm_options()
+    };
+    let compressed = compress_with_options(&input, &options);
+    let info = inspect(&compressed).unwrap();
+    assert_eq!(info.chunks[0].methods, []);
+    assert_eq!(info.chunks[1].methods, [CM_FLAG]);
+    let decoded = decompress_with_options(&compressed, &DecompressOptions::default());
+    assert_eq!(decoded, Ok(input));
+}
+
+#[test]
+fn model_set_is_pinned() {
+    // Any change to the models, counters, mixer or coder changes these bytes,
+    // and with them the format
+    let stream = cm::encode(b"context mixing, context mixing, context mixing");
+    assert_eq!(
+        stream,
+        [
+            46, 156, 183, 133, 109, 190, 97, 107, 64, 67, 76, 4, 124, 199, 143, 193, 253, 237, 160,
+            159, 61, 24
+        ]
+    );
+    assert_eq!(
+        decode(&stream).as_deref(),
+        Some(&b"context mixing, context mixing, context mixing"[..])
+    );
+}
+
+#[test]
+fn truncated_streams_are_not_decoded() {
+    let input = log_lines(&mut Rng::new(2), 20);
+    let valid = cm::encode(&input);
+    for len in [0, 1, 2, valid.len() / 2, valid.len() - 1] {
+        let truncated = &valid[..len];
+        assert_eq!(decode(truncated), None, "{} of {} bytes", len, valid.len());
+        let stream = [&[CM_FLAG], truncated].concat();
+        let decoded = decompress_with_options(&stream, &DecompressOptions::default());
+        assert_eq!(decoded.as_deref(), Ok(truncated));
+    }
+
+    // A length beyond what the bits encode
+    let mut longer = Vec::new();
+    write_varint(&mut longer, input.len() as u64 + 50);
+    longer.extend_from_slice(&valid[2..]);
+    assert_eq!(decode(&longer), None);
+}
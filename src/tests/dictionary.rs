@@ -0,0 +1,88 @@
+// Static dictionary: small web payloads shrink with it, streams that use it
+// are marked, and unmarked streams can't reach it.
+
+use crate::constants::{COMPRESSED_FLAG, LZ_DICTIONARY_MARKER};
+use crate::shared::dictionary::STATIC_DICTIONARY;
+use crate::{
+    compress_with_options, decompress_with_options, Algorithm, CompressOptions, DecompressOptions,
+};
+
+const RESPONSE: &[u8] = br#"{"data":{"user":{"id":1042,"username":"ada","email":"ada@example.com","created_at":"2024-03-18T09:12:44.000Z","updated_at":"2025-01-02T00:00:00.000Z"},"items":[{"id":1,"title":"Welcome","description":"Read more about your account","status":"active","enabled":true},{"id":2,"title":"Privacy Policy","url":"https://www.example.com/privacy","status":"archived","enabled":false}]},"success":true,"message":null}"#;
+
+fn options(algorithm: Algorithm, dictionary: bool) -> CompressOptions {
+    CompressOptions {
+        algorithm,
+        dictionary,
+        ..CompressOptions::default()
+    }
+}
+
+#[test]
+fn dictionary_shrinks_small_responses() {
+    for algorithm in [Algorithm::LzHuffman, Algorithm::Best] {
+        let plain = compress_with_options(RESPONSE, &options(algorithm, false));
+        let with_dictionary = compress_with_options(RESPONSE, &options(algorithm, true));
+        assert!(
+            with_dictionary.len() < plain.len() * 9 / 10,
+            "{}: {} bytes with the dictionary, {} without",
+            algorithm,
+            with_dictionary.len(),
+            plain.len()
+        );
+        let decoded = decompress_with_options(&with_dictionary, &DecompressOptions::default());
+        assert_eq!(decoded.as_deref(), Ok(RESPONSE), "{}", algorithm);
+    }
+}
+
+#[test]
+fn only_streams_using_the_dictionary_are_marked() {
+    let marked = compress_with_options(RESPONSE, &options(Algorithm::LzHuffman, true));
+    assert_eq!(marked[..2], [COMPRESSED_FLAG, LZ_DICTIONARY_MARKER]);
+
+    let plain = compress_with_options(RESPONSE, &options(Algorithm::LzHuffman, false));
+    assert_ne!(plain[1], LZ_DICTIONARY_MARKER);
+    // Nothing to gain from the dictionary, so nothing changes
+    let noise: Vec<u8> = (0..200u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+        .collect();
+    assert_eq!(
+        compress_with_options(&noise, &options(Algorithm::LzHuffman, true)),
+        compress_with_options(&noise, &options(Algorithm::LzHuffman, false))
+    );
+}
+
+#[test]
+fn dictionary_matches_need_the_marker() {
+    // A dictionary match for the first 8 bytes, behind the tree byte
+    let tokens = [0xFF, 2, 0, 0, 8, 0];
+    let expected = Ok(STATIC_DICTIONARY[..8].to_vec());
+
+    let marked = [&[COMPRESSED_FLAG, LZ_DICTIONARY_MARKER][..], &tokens].concat();
+    assert_eq!(
+        decompress_with_options(&marked, &DecompressOptions::default()),
+        expected
+    );
+
+    let unmarked = [&[COMPRESSED_FLAG][..], &tokens].concat();
+    assert_eq!(
+        decompress_with_options(&unmarked, &DecompressOptions::default()),
+        Ok(Vec::new())
+    );
+
+    // Matches must end inside the dictionary
+    let end = (STATIC_DICTIONARY.len() - 4) as u16;
+    let past_end = [COMPRESSED_FLAG, LZ_DICTIONARY_MARKER, 0xFF, 2]
+        .into_iter()
+        .chain(end.to_le_bytes())
+        .chain([8, 0])
+        .collect::<Vec<u8>>();
+    assert_eq!(
+        decompress_with_options(&past_end, &DecompressOptions::default()),
+        Ok(Vec::new())
+    );
+}
+
+#[test]
+fn dictionary_positions_fit_match_tokens() {
+    assert!(STATIC_DICTIONARY.len() <= u16::MAX as usize);
+}
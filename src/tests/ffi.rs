@@ -0,0 +1,222 @@
+// The C ABI: round trips through raw pointers, the compress bound, error
+// codes with their `goud_last_error` messages, and options structs shorter
+// than the current one.
+
+use super::rng::{gen_input, Rng};
+use crate::constants::NUMPACK_MARKER;
+use crate::ffi::*;
+use crate::Algorithm;
+use std::ffi::CStr;
+use std::ptr;
+
+fn compress(input: &[u8], opts: &GoudOptions, out_cap: usize) -> Result<Vec<u8>, i64> {
+    let mut out = vec![0u8; out_cap];
+    let written =
+        unsafe { goud_compress(input.as_ptr(), input.len(), opts, out.as_mut_ptr(), out_cap) };
+    if written < 0 {
+        return Err(written);
+    }
+    out.truncate(written as usize);
+    Ok(out)
+}
+
+fn decompress(input: &[u8], opts: &GoudOptions) -> Result<Vec<u8>, i64> {
+    let mut out = ptr::null_mut();
+    let len = unsafe { goud_decompress(input.as_ptr(), input.len(), opts, &mut out) };
+    if len < 0 {
+        assert!(out.is_null());
+        return Err(len);
+    }
+    let data = unsafe { std::slice::from_raw_parts(out, len as usize) }.to_vec();
+    unsafe { goud_free(out, len as usize) };
+    Ok(data)
+}
+
+fn last_error() -> Option<String> {
+    let message = goud_last_error();
+    (!message.is_null()).then(|| {
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    })
+}
+
+/// Inputs that expand the most under each method: single-digit numbers,
+/// escaped markers and incompressible bytes.
+fn worst_cases(rng: &mut Rng) -> Vec<Vec<u8>> {
+    let mut cases = Vec::new();
+    for len in [0, 1, 63, 64, 100, 255, 256, 1000, 5000] {
+        cases.push(b"1,".repeat(len).into_iter().take(len).collect());
+        cases.push(b"-1 ".repeat(len).into_iter().take(len).collect());
+        cases.push(vec![NUMPACK_MARKER; len]);
+        cases.push(vec![0xFF; len]);
+        cases.push((0..len).map(|i| (i * 7919 % 251) as u8).collect());
+        cases.push(rng.bytes(len));
+    }
+    cases
+}
+
+#[test]
+fn round_trips_within_bound() {
+    let mut rng = Rng::new(0xFF1);
+    let mut inputs = worst_cases(&mut rng);
+    inputs.extend((0..16).map(|_| gen_input(&mut rng, 4096)));
+
+    for algorithm in 0..Algorithm::ALL.len() as u32 {
+        let opts = GoudOptions {
+            algorithm,
+            ..GoudOptions::default()
+        };
+        for input in &inputs {
+            let bound = goud_compress_bound(input.len());
+            let compressed = compress(input, &opts, bound).unwrap_or_else(|code| {
+                panic!(
+                    "{} failed with {} on {} bytes",
+                    Algorithm::ALL[algorithm as usize],
+                    code,
+                    input.len()
+                )
+            });
+            assert_eq!(last_error(), None);
+            assert_eq!(decompress(&compressed, &opts).as_ref(), Ok(input));
+        }
+    }
+}
+
+#[test]
+fn null_options_use_defaults() {
+    let input = b"null options, null options, null options, null options!".repeat(20);
+    let mut out = vec![0u8; goud_compress_bound(input.len())];
+    let written = unsafe {
+        goud_compress(
+            input.as_ptr(),
+            input.len(),
+            ptr::null(),
+            out.as_mut_ptr(),
+            out.len(),
+        )
+    };
+    assert!(written > 0 && (written as usize) < input.len());
+
+    let mut decoded = ptr::null_mut();
+    let len = unsafe { goud_decompress(out.as_ptr(), written as usize, ptr::null(), &mut decoded) };
+    assert_eq!(len as usize, input.len());
+    unsafe { goud_free(decoded, len as usize) };
+
+    let empty = unsafe { goud_compress(ptr::null(), 0, ptr::null(), out.as_mut_ptr(), out.len()) };
+    assert_eq!(empty, 1);
+}
+
+#[test]
+fn errors_set_codes_and_messages() {
+    let input = b"0123456789".repeat(100);
+    let opts = GoudOptions::default();
+
+    assert_eq!(compress(&input, &opts, 4), Err(GOUD_ERROR_BUFFER_TOO_SMALL));
+    assert!(last_error().unwrap().contains("out_cap is 4"));
+
+    for bad in [
+        GoudOptions {
+            algorithm: Algorithm::ALL.len() as u32,
+            ..opts
+        },
+        GoudOptions { level: 10, ..opts },
+        GoudOptions {
+            record_width: 70_000,
+            ..opts
+        },
+        GoudOptions {
+            chunk_mode: 2,
+            ..opts
+        },
+        GoudOptions {
+            struct_size: 0,
+            ..opts
+        },
+    ] {
+        assert_eq!(
+            compress(&input, &bad, 4096),
+            Err(GOUD_ERROR_INVALID_OPTIONS)
+        );
+    }
+    let bad_ratio = GoudOptions {
+        max_ratio: f64::NAN,
+        ..opts
+    };
+    assert_eq!(
+        decompress(&[0xAA], &bad_ratio),
+        Err(GOUD_ERROR_INVALID_OPTIONS)
+    );
+
+    let compressed = compress(&input, &opts, 4096).unwrap();
+    let capped = GoudOptions {
+        max_output_size: 10,
+        ..opts
+    };
+    assert_eq!(
+        decompress(&compressed, &capped),
+        Err(GOUD_ERROR_OUTPUT_TOO_LARGE)
+    );
+    assert!(last_error().unwrap().contains("max_output_size"));
+    let ratio = GoudOptions {
+        max_ratio: 1.5,
+        ..opts
+    };
+    assert_eq!(
+        decompress(&compressed, &ratio),
+        Err(GOUD_ERROR_RATIO_EXCEEDED)
+    );
+
+    let null_src = unsafe { goud_compress(ptr::null(), 5, &opts, ptr::null_mut(), 0) };
+    assert_eq!(null_src, GOUD_ERROR_NULL_POINTER);
+    let null_out = unsafe { goud_decompress(input.as_ptr(), input.len(), &opts, ptr::null_mut()) };
+    assert_eq!(null_out, GOUD_ERROR_NULL_POINTER);
+
+    assert_eq!(decompress(&compressed, &opts).as_ref(), Ok(&input));
+    assert_eq!(last_error(), None);
+}
+
+/// `GoudOptions` with only the fields every caller must provide, followed
+/// by bytes that a read of the whole struct would take for the rest.
+#[repr(C)]
+struct WithoutDictionary {
+    struct_size: u32,
+    algorithm: u32,
+    level: u32,
+    chunk_size: u32,
+    record_width: u32,
+    max_output_size: u64,
+    max_ratio: f64,
+    beyond: [u32; 3],
+}
+
+#[test]
+fn shorter_options_structs_are_read_to_their_size() {
+    let input = b"field, field, field, shorter field, shorter field".repeat(50);
+    let short = WithoutDictionary {
+        struct_size: std::mem::offset_of!(WithoutDictionary, beyond) as u32,
+        algorithm: Algorithm::LzHuffman as u32,
+        level: 9,
+        chunk_size: 0,
+        record_width: 0,
+        max_output_size: 0,
+        max_ratio: 0.0,
+        // An invalid chunk_mode if read as one
+        beyond: [u32::MAX; 3],
+    };
+    let opts = &short as *const WithoutDictionary as *const GoudOptions;
+    let bound = goud_compress_bound(input.len());
+    let mut out = vec![0u8; bound];
+    let written =
+        unsafe { goud_compress(input.as_ptr(), input.len(), opts, out.as_mut_ptr(), bound) };
+    assert_eq!(last_error(), None);
+    out.truncate(written as usize);
+
+    let current = GoudOptions {
+        algorithm: Algorithm::LzHuffman as u32,
+        level: 9,
+        ..GoudOptions::default()
+    };
+    assert_eq!(compress(&input, &current, bound).as_ref(), Ok(&out));
+    assert_eq!(decompress(&out, &current).as_ref(), Ok(&input));
+}
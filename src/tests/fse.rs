@@ -0,0 +1,166 @@
+// FSE: round trips over skewed and flat distributions, its gain as the LZ
+// back end for short token streams and after BWT, and rejection of corrupt
+// tables and bit streams.
+
+use super::rng::Rng;
+use crate::compression::huffman::encode_tokens;
+use crate::compression::levels::level_params;
+use crate::compression::matcher::find_matches;
+use crate::compression::strategies::{compress_bwt, compress_fse, compress_lz};
+use crate::constants::{DEFAULT_LEVEL, FSE_FLAG, LZ_FSE_HEADER};
+use crate::entropy::fse;
+use crate::shared::varint::write_varint;
+use crate::{
+    compress_with_options, decompress_with_options, Algorithm, CompressOptions, DecompressOptions,
+    NoopLogger,
+};
+
+const WORDS: [&str; 12] = [
+    "the", "quick", "brown", "fox", "jumps", "over", "lazy", "dog", "while", "cat", "sleeps", "and",
+];
+
+fn decode(stream: &[u8]) -> Option<Vec<u8>> {
+    fse::decode(stream, |_| Ok(())).unwrap()
+}
+
+fn text(rng: &mut Rng, words: usize) -> Vec<u8> {
+    let words: Vec<&str> = (0..words).map(|_| WORDS[rng.below(WORDS.len())]).collect();
+    words.join(" ").into_bytes()
+}
+
+/// Bytes drawn so that each symbol is half as likely as the one before.
+fn skewed(rng: &mut Rng, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|_| (rng.next_u64() | 1 << 40).trailing_zeros() as u8)
+        .collect()
+}
+
+#[test]
+fn skewed_and_flat_distributions_round_trip() {
+    let mut rng = Rng::new(0xF5E);
+    let mut inputs = vec![
+        Vec::new(),
+        vec![9],
+        vec![b'a'; 5000],
+        (0..=255).collect(),
+        rng.bytes(70_000),
+    ];
+    for len in [2, 31, 300, 5000, 100_000] {
+        inputs.push(skewed(&mut rng, len));
+    }
+
+    for input in inputs {
+        let stream = fse::encode(&input);
+        assert_eq!(
+            decode(&stream),
+            Some(input.clone()),
+            "{} bytes",
+            input.len()
+        );
+    }
+}
+
+#[test]
+fn incompressible_data_is_stored() {
+    let input = Rng::new(3).bytes(300);
+    let stream = fse::encode(&input);
+    // [len: 2 bytes][0][bytes...]
+    assert_eq!(stream.len(), input.len() + 3);
+    assert_eq!(stream[2], 0);
+    assert_eq!(decode(&stream), Some(input));
+}
+
+#[test]
+fn skewed_data_takes_fractional_bits() {
+    // About 2 bits per symbol, where Huffman would need a whole bit for the
+    // most common one
+    let input = skewed(&mut Rng::new(7), 100_000);
+    let stream = compress_fse(&input, &NoopLogger);
+    assert!(
+        stream.len() < input.len() * 3 / 10,
+        "{} bytes",
+        stream.len()
+    );
+
+    let options = CompressOptions {
+        algorithm: Algorithm::Fse,
+        ..CompressOptions::default()
+    };
+    let compressed = compress_with_options(&input, &options);
+    assert_eq!(compressed[0], FSE_FLAG);
+    let decoded = decompress_with_options(&compressed, &DecompressOptions::default());
+    assert_eq!(decoded, Ok(input));
+}
+
+#[test]
+fn lz_codes_few_distinct_tokens_with_fse() {
+    // A handful of tokens, most of them one repeated match, is cheaper as
+    // FSE-coded bytes than behind two Huffman code tables
+    let input = b"goud compressor ".repeat(16);
+    let tokens = find_matches(&[], &input, &level_params(DEFAULT_LEVEL), false, &NoopLogger);
+    let plain = encode_tokens(&tokens);
+
+    let compressed = compress_lz(&input, DEFAULT_LEVEL, false, &NoopLogger);
+    assert_eq!(compressed[0], LZ_FSE_HEADER);
+    assert!(
+        compressed.len() < plain.len(),
+        "{} bytes with FSE, {} without",
+        compressed.len(),
+        plain.len()
+    );
+
+    let options = CompressOptions {
+        algorithm: Algorithm::LzHuffman,
+        ..CompressOptions::default()
+    };
+    let compressed = compress_with_options(&input, &options);
+    let decoded = decompress_with_options(&compressed, &DecompressOptions::default());
+    assert_eq!(decoded, Ok(input));
+}
+
+#[test]
+fn fse_after_bwt_shrinks_text() {
+    let bwt = compress_bwt(&text(&mut Rng::new(9), 2000), &NoopLogger);
+    let coded = compress_fse(&bwt, &NoopLogger);
+    assert!(
+        coded.len() < bwt.len() * 3 / 4,
+        "{} bytes after FSE, {} after BWT",
+        coded.len(),
+        bwt.len()
+    );
+}
+
+#[test]
+fn corrupt_streams_are_not_decoded() {
+    let input = skewed(&mut Rng::new(11), 2000);
+    // [len: 2 bytes][table_log][max_symbol][counts, then states...]
+    let valid = fse::encode(&input);
+
+    let mut corrupt_streams = Vec::new();
+    // Table log out of range
+    for table_log in [4, 13] {
+        let mut corrupt = valid.clone();
+        corrupt[2] = table_log;
+        corrupt_streams.push(corrupt);
+    }
+    // Corrupt counts
+    let mut corrupt = valid.clone();
+    corrupt[4] ^= 0x01;
+    corrupt_streams.push(corrupt);
+    // A truncated bit stream, and a longer recorded length
+    corrupt_streams.push(valid[..valid.len() - 1].to_vec());
+    let mut longer = Vec::new();
+    write_varint(&mut longer, input.len() as u64 + 1);
+    longer.extend_from_slice(&valid[2..]);
+    corrupt_streams.push(longer);
+    // A header without a table, and stored bytes of the wrong length
+    corrupt_streams.push(valid[..3].to_vec());
+    corrupt_streams.push([&valid[..2], &[0], &input[1..]].concat());
+
+    for corrupt in corrupt_streams {
+        assert_eq!(decode(&corrupt), None);
+        let stream = [&[FSE_FLAG], &corrupt[..]].concat();
+        let decoded = decompress_with_options(&stream, &DecompressOptions::default());
+        assert_eq!(decoded, Ok(corrupt));
+    }
+}
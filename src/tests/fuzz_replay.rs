@@ -0,0 +1,127 @@
+// Deterministic replay of the fuzz corpus in fuzz/corpus, plus truncated and
+// bit-flipped variants of real compressed streams, through the same entry
+// points as the cargo-fuzz targets.
+
+use super::alloc::peak_allocation;
+use super::rng::{gen_input, Rng};
+use crate::Algorithm;
+use crate::fuzzing::{decode_single, decompress, round_trip, DECODERS, MAX_OUTPUT_SIZE};
+use crate::{compress_with_options, CompressOptions, Error};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// Decoders may use a bounded multiple of what they read and write, never an
+// amount taken from an untrusted header.
+const ALLOCATION_FACTOR: usize = 32;
+const ALLOCATION_SLACK: usize = 1 << 20;
+const TIME_LIMIT: Duration = Duration::from_secs(10);
+
+fn corpus(target: &str) -> Vec<(String, Vec<u8>)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fuzz/corpus")
+        .join(target);
+    let mut entries: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap_or_else(|err| panic!("reading {}: {}", dir.display(), err))
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+    entries
+        .into_iter()
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            (name, std::fs::read(&path).unwrap())
+        })
+        .collect()
+}
+
+/// Compressed streams from every algorithm, truncated and with flipped bytes.
+fn mutated_streams() -> Vec<(String, Vec<u8>)> {
+    let mut rng = Rng::new(0xF022);
+    let mut streams = Vec::new();
+
+    for case in 0..8 {
+        let input = gen_input(&mut rng, 2048);
+        for algorithm in Algorithm::ALL {
+            let options = CompressOptions {
+                algorithm,
+                ..CompressOptions::default()
+            };
+            let compressed = compress_with_options(&input, &options);
+            let label = format!("{} case {}", algorithm, case);
+
+            for len in (0..compressed.len()).step_by(1 + compressed.len() / 32) {
+                streams.push((
+                    format!("{} truncated to {}", label, len),
+                    compressed[..len].to_vec(),
+                ));
+            }
+            for flip in 0..16 {
+                let mut mutated = compressed.clone();
+                if !mutated.is_empty() {
+                    let pos = rng.below(mutated.len());
+                    mutated[pos] ^= 1 << rng.below(8);
+                }
+                streams.push((format!("{} flip {}", label, flip), mutated));
+            }
+        }
+    }
+
+    streams
+}
+
+fn check_decoder(label: &str, input: &[u8], decode: impl FnOnce(&[u8]) -> Result<Vec<u8>, Error>) {
+    let start = Instant::now();
+    let (result, peak) = peak_allocation(|| decode(input));
+    let elapsed = start.elapsed();
+    // A decoder that hits the output cap may have used up to the cap first
+    let output = result.unwrap_or_else(|_| vec![0; MAX_OUTPUT_SIZE]);
+
+    let budget = ALLOCATION_FACTOR * (input.len() + output.len()) + ALLOCATION_SLACK;
+    assert!(
+        peak <= budget,
+        "{}: allocated {} bytes for {} in / {} out",
+        label,
+        peak,
+        input.len(),
+        output.len()
+    );
+    assert!(elapsed < TIME_LIMIT, "{}: took {:?}", label, elapsed);
+}
+
+#[test]
+fn decompress_corpus_replays() {
+    for (name, input) in corpus("decompress").into_iter().chain(mutated_streams()) {
+        check_decoder(&format!("decompress / {}", name), &input, decompress);
+    }
+}
+
+#[test]
+fn decoder_corpus_replays() {
+    let mut inputs = corpus("decoders");
+    // Every mutated stream through every individual decoder
+    for (name, stream) in mutated_streams().into_iter().step_by(7) {
+        for selector in 0..DECODERS.len() as u8 {
+            inputs.push((
+                format!("{} via decoder {}", name, selector),
+                [&[selector], stream.as_slice()].concat(),
+            ));
+        }
+    }
+
+    for (name, input) in inputs {
+        check_decoder(&format!("decoders / {}", name), &input, decode_single);
+    }
+}
+
+#[test]
+fn round_trip_corpus_replays() {
+    for (name, input) in corpus("round_trip") {
+        let start = Instant::now();
+        round_trip(&input);
+        assert!(
+            start.elapsed() < TIME_LIMIT,
+            "round_trip / {}: took too long",
+            name
+        );
+    }
+}
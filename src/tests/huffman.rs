@@ -0,0 +1,290 @@
+// Huffman-coded LZ tokens: bucket layout, split alphabets against a single
+// token alphabet, repeat offsets, code length limits, dictionary matches,
+// older streams and rejection of corrupt tables and bit streams.
+
+use super::rng::Rng;
+use crate::compression::huffman::{build_huffman_codes, encode_huffman, encode_tokens};
+use crate::compression::levels::level_params;
+use crate::compression::matcher::find_matches;
+use crate::compression::strategies::compress_lz;
+use crate::constants::{
+    COMPRESSED_FLAG, DEFAULT_LEVEL, LZ_DICTIONARY_MARKER, LZ_HUFFMAN_HEADER, MAX_MATCH_LEN,
+    MIN_MATCH_LEN, WINDOW_SIZE,
+};
+use crate::entropy::fse;
+use crate::shared::bits::BitWriter;
+use crate::shared::dictionary::STATIC_DICTIONARY;
+use crate::shared::huffman::{
+    dictionary_bucket, length_base, length_bucket, offset_base, offset_bucket, Bucket,
+    FIRST_DICTIONARY_SYMBOL, FIRST_LENGTH_SYMBOL, FIRST_WINDOW_SYMBOL, LITERAL_LENGTH_SYMBOLS,
+    MAX_CODE_LEN, OFFSET_SYMBOLS,
+};
+use crate::shared::token::{RecentOffsets, Token};
+use crate::{decompress_with_options, DecompressOptions, NoopLogger};
+
+fn decompress(stream: &[u8]) -> Vec<u8> {
+    decompress_with_options(stream, &DecompressOptions::default()).unwrap()
+}
+
+/// A bare Huffman-coded LZ stream of `tokens`.
+fn huffman_stream(tokens: &[Token], dictionary: bool) -> Vec<u8> {
+    let mut stream = vec![COMPRESSED_FLAG];
+    if dictionary {
+        stream.push(LZ_DICTIONARY_MARKER);
+    }
+    stream.push(LZ_HUFFMAN_HEADER);
+    stream.extend(encode_huffman(tokens, &build_huffman_codes(tokens)));
+    stream
+}
+
+/// JSON records whose numbers vary, so LZ leaves many literals.
+fn json_records(rng: &mut Rng, count: usize) -> Vec<u8> {
+    let mut json = b"[".to_vec();
+    for id in 0..count {
+        json.extend_from_slice(
+            format!(
+                r#"{{"id":{},"name":"sensor-{}","enabled":{},"readings":[{},{},{}],"unit":"C"}},"#,
+                id,
+                rng.below(1000),
+                rng.below(2) == 0,
+                rng.below(40),
+                rng.below(40),
+                rng.below(40)
+            )
+            .as_bytes(),
+        );
+    }
+    json.push(b']');
+    json
+}
+
+fn check_bucket(bucket: Bucket, value: u32, base: (u32, u32)) {
+    assert!(bucket.extra < 1 << bucket.extra_bits, "{}", value);
+    assert_eq!(base, (value - bucket.extra, bucket.extra_bits), "{}", value);
+}
+
+#[test]
+fn buckets_cover_every_length_and_offset() {
+    for length in MIN_MATCH_LEN as u32..=MAX_MATCH_LEN as u32 {
+        let bucket = length_bucket(length - MIN_MATCH_LEN as u32);
+        assert!((FIRST_LENGTH_SYMBOL..LITERAL_LENGTH_SYMBOLS).contains(&bucket.symbol));
+        let (base, extra_bits) = length_base(bucket.symbol);
+        check_bucket(bucket, length, (base + MIN_MATCH_LEN as u32, extra_bits));
+    }
+    for offset in 1..=WINDOW_SIZE as u32 {
+        let bucket = offset_bucket(offset);
+        assert!((FIRST_WINDOW_SYMBOL..FIRST_DICTIONARY_SYMBOL).contains(&bucket.symbol));
+        check_bucket(bucket, offset, offset_base(bucket.symbol));
+    }
+    for position in 0..=u16::MAX as u32 {
+        let bucket = dictionary_bucket(position);
+        assert!((FIRST_DICTIONARY_SYMBOL..OFFSET_SYMBOLS).contains(&bucket.symbol));
+        check_bucket(bucket, position, offset_base(bucket.symbol));
+    }
+    // The last buckets are the ones reaching the largest values
+    assert_eq!(length_bucket(255).symbol, LITERAL_LENGTH_SYMBOLS - 1);
+    assert_eq!(
+        offset_bucket(WINDOW_SIZE as u32).symbol,
+        FIRST_DICTIONARY_SYMBOL - 1
+    );
+    assert_eq!(
+        dictionary_bucket(u16::MAX as u32).symbol,
+        OFFSET_SYMBOLS - 1
+    );
+}
+
+#[test]
+fn json_matches_share_codes() {
+    // Nearly every (offset, length) pair is unique, but their buckets repeat
+    let input = json_records(&mut Rng::new(5), 100);
+    let tokens = find_matches(&[], &input, &level_params(DEFAULT_LEVEL), false, &NoopLogger);
+    let as_bytes = fse::encode(&encode_tokens(&tokens));
+
+    let compressed = compress_lz(&input, DEFAULT_LEVEL, false, &NoopLogger);
+    assert_eq!(compressed[0], LZ_HUFFMAN_HEADER);
+    assert!(
+        compressed.len() < as_bytes.len() * 3 / 4,
+        "{} bytes with Huffman, {} as FSE-coded token bytes",
+        compressed.len(),
+        as_bytes.len()
+    );
+    assert_eq!(
+        decompress(&[&[COMPRESSED_FLAG], &compressed[..]].concat()),
+        input
+    );
+}
+
+#[test]
+fn recent_offsets_move_to_front() {
+    let mut recent = RecentOffsets::default();
+    assert_eq!(recent.offsets(), [1, 4, 8]);
+    recent.push(57);
+    assert_eq!(recent.offsets(), [57, 1, 4]);
+    recent.push(4);
+    assert_eq!(recent.offsets(), [4, 57, 1]);
+    recent.push(4);
+    assert_eq!(recent.offsets(), [4, 57, 1]);
+    recent.push(1);
+    assert_eq!(recent.offsets(), [1, 4, 57]);
+    assert_eq!(recent.position(57), Some(2));
+    assert_eq!(recent.position(8), None);
+}
+
+#[test]
+fn records_repeat_their_offsets() {
+    // Records of one width with varying numbers: the matcher should keep
+    // returning to recent offsets rather than whichever is longest
+    let mut rng = Rng::new(8);
+    let mut input = b"[".to_vec();
+    for id in 0..500 {
+        input.extend_from_slice(
+            format!(
+                r#"{{"id":{:04},"temp":{}.{},"status":{}}},"#,
+                id,
+                rng.below(30) + 10,
+                rng.below(10),
+                rng.below(5)
+            )
+            .as_bytes(),
+        );
+    }
+    let tokens = find_matches(&[], &input, &level_params(DEFAULT_LEVEL), false, &NoopLogger);
+
+    let mut recent = RecentOffsets::default();
+    let (mut matches, mut repeats) = (0, 0);
+    for token in &tokens {
+        if let Token::Match(offset, _) = *token {
+            matches += 1;
+            repeats += recent.position(offset).is_some() as usize;
+            recent.push(offset);
+        }
+    }
+    // About a fifth would without preferring them
+    assert!(repeats * 3 > matches, "{} of {} matches", repeats, matches);
+
+    let compressed = compress_lz(&input, DEFAULT_LEVEL, false, &NoopLogger);
+    assert_eq!(
+        decompress(&[&[COMPRESSED_FLAG], &compressed[..]].concat()),
+        input
+    );
+}
+
+#[test]
+fn repeat_offsets_are_decoded() {
+    // The first match uses the initial history, the others the offsets of
+    // the matches before them, one of them now second in line
+    let tokens = [
+        Token::Literal(b'a'),
+        Token::Literal(b'b'),
+        Token::Literal(b'c'),
+        Token::Literal(b'd'),
+        Token::Match(4, 4),
+        Token::Literal(b'x'),
+        Token::Match(2, 3),
+        Token::Match(4, 3),
+        Token::Match(4, 4),
+    ];
+    assert_eq!(
+        decompress(&huffman_stream(&tokens, false)),
+        b"abcdabcdxdxdxdxdxdx"
+    );
+}
+
+#[test]
+fn code_lengths_are_limited() {
+    // Fibonacci counts make the optimal code as deep as it can be
+    let (mut a, mut b) = (1, 1);
+    let mut tokens = Vec::new();
+    for byte in 0..25 {
+        tokens.extend((0..a).map(|_| Token::Literal(byte)));
+        (a, b) = (b, a + b);
+    }
+    tokens.push(Token::Match(1, MIN_MATCH_LEN as u16));
+
+    let codes = build_huffman_codes(&tokens);
+    let deepest = codes.literal_lengths.lengths.iter().max().copied();
+    assert!(deepest <= Some(MAX_CODE_LEN as u8), "{:?}", deepest);
+
+    let mut expected: Vec<u8> = tokens[..tokens.len() - 1]
+        .iter()
+        .map(|token| match token {
+            Token::Literal(byte) => *byte,
+            _ => unreachable!(),
+        })
+        .collect();
+    expected.extend([24; MIN_MATCH_LEN]);
+    assert_eq!(decompress(&huffman_stream(&tokens, false)), expected);
+}
+
+#[test]
+fn dictionary_matches_are_coded() {
+    let end = STATIC_DICTIONARY.len() as u16;
+    let tokens = [
+        Token::DictMatch(0, 8),
+        Token::Literal(b' '),
+        Token::DictMatch(end - 40, 40),
+        Token::Match(41, MAX_MATCH_LEN as u16),
+    ];
+    let mut expected = STATIC_DICTIONARY[..8].to_vec();
+    expected.push(b' ');
+    expected.extend_from_slice(&STATIC_DICTIONARY[end as usize - 40..]);
+    for _ in 0..MAX_MATCH_LEN {
+        expected.push(expected[expected.len() - 41]);
+    }
+    assert_eq!(decompress(&huffman_stream(&tokens, true)), expected);
+
+    // Still only behind the marker
+    assert_eq!(
+        decompress(&huffman_stream(&tokens, false)),
+        Vec::<u8>::new()
+    );
+}
+
+#[test]
+fn older_streams_still_decode() {
+    // The byte-per-tag layout behind the placeholder tree byte: a literal
+    // and a match of 3 at offset 1
+    let stream = [COMPRESSED_FLAG, 0xFF, 0, b'a', 1, 1, 0, 3, 0];
+    assert_eq!(decompress(&stream), b"aaaa");
+}
+
+#[test]
+fn corrupt_streams_are_not_decoded() {
+    let tokens = find_matches(
+        &[],
+        &json_records(&mut Rng::new(6), 10),
+        &level_params(DEFAULT_LEVEL),
+        false,
+        &NoopLogger,
+    );
+    let valid = huffman_stream(&tokens, false);
+    let body = &valid[2..];
+
+    let mut corrupt_streams = Vec::new();
+    // Three codes of one bit
+    let mut oversubscribed = Vec::new();
+    let mut writer = BitWriter::new(&mut oversubscribed);
+    for _ in 0..3 {
+        writer.write(1, 4);
+    }
+    writer.write(0, 4);
+    writer.write_gamma((LITERAL_LENGTH_SYMBOLS - 4 + 1) as u32);
+    writer.finish();
+    corrupt_streams.push(oversubscribed);
+    // A run of unused symbols past the end of the alphabet
+    let mut overlong = Vec::new();
+    let mut writer = BitWriter::new(&mut overlong);
+    writer.write(0, 4);
+    writer.write_gamma(LITERAL_LENGTH_SYMBOLS as u32 + 1);
+    writer.finish();
+    corrupt_streams.push(overlong);
+    // Cut short, with bytes after the end, or without tables
+    corrupt_streams.push(body[..body.len() - 1].to_vec());
+    corrupt_streams.push([body, &[0x01]].concat());
+    corrupt_streams.push(Vec::new());
+
+    for corrupt in corrupt_streams {
+        let stream = [&[COMPRESSED_FLAG, LZ_HUFFMAN_HEADER], &corrupt[..]].concat();
+        assert_eq!(decompress(&stream), stream[1..]);
+    }
+}
@@ -0,0 +1,154 @@
+// Output caps: every decoder must stop with a specific error once the
+// decoded size passes `max_output_size` or `max_ratio`.
+
+use crate::compression::strategies::{compress_method, CHAIN_METHODS};
+use crate::constants::{CHUNKED_FLAG, CM_FLAG, FSE_FLAG, RLE_FLAG};
+use crate::decompression::decompress_method;
+use crate::decompression::limit::OutputLimit;
+use crate::shared::varint::write_varint;
+use crate::{
+    compress_snappy_raw, compress_with_options, decompress_snappy_raw, decompress_with_options,
+    Algorithm, CompressOptions, DecompressOptions, Error, NoopLogger,
+};
+
+// Algorithms whose whole stream has a decoder of its own, outside the chunk
+// chain
+const STREAM_ALGORITHMS: [Algorithm; 4] = [
+    Algorithm::Lz4,
+    Algorithm::Snappy,
+    Algorithm::Fse,
+    Algorithm::Cm,
+];
+
+fn options(max_output_size: usize, max_ratio: Option<f64>) -> DecompressOptions {
+    DecompressOptions {
+        max_output_size,
+        max_ratio,
+        ..DecompressOptions::default()
+    }
+}
+
+fn text(len: usize) -> Vec<u8> {
+    b"limits 12 34 5.6 "
+        .iter()
+        .copied()
+        .cycle()
+        .take(len)
+        .collect()
+}
+
+#[test]
+fn every_decoder_enforces_max_output_size() {
+    let input = text(4096);
+    for method in CHAIN_METHODS {
+        let encoded = compress_method(method, &input, &CompressOptions::default(), &NoopLogger);
+
+        let exact = OutputLimit::new(&options(input.len(), None), encoded.len());
+        let decoded = decompress_method(method, &encoded, &exact, &NoopLogger);
+        assert_eq!(
+            decoded.as_deref(),
+            Ok(input.as_slice()),
+            "method {:02X}",
+            method
+        );
+
+        let short = OutputLimit::new(&options(input.len() - 1, None), encoded.len());
+        assert_eq!(
+            decompress_method(method, &encoded, &short, &NoopLogger),
+            Err(Error::OutputTooLarge {
+                limit: input.len() - 1
+            }),
+            "method {:02X}",
+            method
+        );
+    }
+
+    type Decoder = fn(&[u8], &DecompressOptions) -> Result<Vec<u8>, Error>;
+    let mut streams: Vec<(String, Vec<u8>, Decoder)> = STREAM_ALGORITHMS
+        .iter()
+        .map(|&algorithm| {
+            let options = CompressOptions {
+                algorithm,
+                ..CompressOptions::default()
+            };
+            let encoded = compress_with_options(&input, &options);
+            (
+                algorithm.to_string(),
+                encoded,
+                decompress_with_options as Decoder,
+            )
+        })
+        .collect();
+    let raw = compress_snappy_raw(&input, &CompressOptions::default());
+    streams.push(("raw Snappy".to_string(), raw, decompress_snappy_raw));
+
+    for (name, encoded, decode) in streams {
+        let decoded = decode(&encoded, &options(input.len(), None));
+        assert_eq!(decoded.as_deref(), Ok(input.as_slice()), "{}", name);
+        assert_eq!(
+            decode(&encoded, &options(input.len() - 1, None)),
+            Err(Error::OutputTooLarge {
+                limit: input.len() - 1
+            }),
+            "{}",
+            name
+        );
+        let ratio = input.len() as f64 / encoded.len() as f64 / 2.0;
+        assert_eq!(
+            decode(&encoded, &options(usize::MAX, Some(ratio))),
+            Err(Error::RatioExceeded { limit: ratio }),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn forged_sizes_fail_before_allocating() {
+    let mut forged_streams = vec![vec![CHUNKED_FLAG, 1, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]];
+    // Recorded lengths, checked before the table or model is set up
+    for flag in [FSE_FLAG, CM_FLAG] {
+        let mut forged = vec![flag];
+        write_varint(&mut forged, u32::MAX as u64);
+        forged_streams.push(forged);
+    }
+    for forged in forged_streams {
+        assert_eq!(
+            decompress_with_options(&forged, &options(1 << 20, None)),
+            Err(Error::OutputTooLarge { limit: 1 << 20 }),
+            "flag {:02X}",
+            forged[0]
+        );
+    }
+}
+
+#[test]
+fn rle_bomb_stops_at_the_cap() {
+    // Each 3-byte run expands to 255 bytes
+    let mut bomb = vec![RLE_FLAG];
+    for _ in 0..10_000 {
+        bomb.extend_from_slice(&[0xFF, 0xFF, b'x']);
+    }
+    assert_eq!(
+        decompress_with_options(&bomb, &options(64 * 1024, None)),
+        Err(Error::OutputTooLarge { limit: 64 * 1024 })
+    );
+    assert_eq!(
+        decompress_with_options(&bomb, &options(usize::MAX, Some(10.0))),
+        Err(Error::RatioExceeded { limit: 10.0 })
+    );
+}
+
+#[test]
+fn max_ratio_applies_to_whole_stream() {
+    let input = vec![b'a'; 200_000];
+    let compressed = compress_with_options(&input, &CompressOptions::default());
+    let ratio = input.len() as f64 / compressed.len() as f64;
+
+    let decoded = decompress_with_options(&compressed, &options(usize::MAX, Some(ratio)));
+    assert_eq!(decoded.as_deref(), Ok(input.as_slice()));
+    assert_eq!(
+        decompress_with_options(&compressed, &options(usize::MAX, Some(ratio / 2.0))),
+        Err(Error::RatioExceeded { limit: ratio / 2.0 })
+    );
+}
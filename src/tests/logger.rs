@@ -0,0 +1,101 @@
+// Level filtering and the lazy formatting of `log_message!`.
+
+use crate::utils::{log_message, LevelFilter, LogLevel, Logger};
+use crate::{compress_with_logger, decompress_with_logger, CompressOptions, DecompressOptions};
+use std::cell::RefCell;
+use std::fmt;
+
+struct Recorder {
+    filter: LevelFilter,
+    messages: RefCell<Vec<(LogLevel, String)>>,
+}
+
+impl Recorder {
+    fn new(level: LogLevel, verbose: bool) -> Recorder {
+        Recorder {
+            filter: LevelFilter { level, verbose },
+            messages: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl Logger for Recorder {
+    fn enabled(&self, level: LogLevel) -> bool {
+        self.filter.allows(level)
+    }
+
+    fn log(&self, level: LogLevel, message: fmt::Arguments<'_>) {
+        self.messages
+            .borrow_mut()
+            .push((level, message.to_string()));
+    }
+}
+
+/// Counts how often it is formatted.
+struct Counted<'a>(&'a RefCell<usize>);
+
+impl fmt::Display for Counted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        *self.0.borrow_mut() += 1;
+        f.write_str("counted")
+    }
+}
+
+#[test]
+fn filter_levels() {
+    let info = LevelFilter {
+        level: LogLevel::Info,
+        verbose: false,
+    };
+    assert!(info.allows(LogLevel::Error) && info.allows(LogLevel::Info));
+    assert!(!info.allows(LogLevel::Debug) && !info.allows(LogLevel::Performance));
+    assert!(!info.allows(LogLevel::None));
+
+    let verbose = LevelFilter {
+        level: LogLevel::None,
+        verbose: true,
+    };
+    assert!(verbose.allows(LogLevel::Performance) && !verbose.allows(LogLevel::Error));
+    assert!(!verbose.is_off() && LevelFilter::default().is_off());
+
+    for name in ["none", "error", "info", "debug", "performance"] {
+        assert_eq!(LogLevel::parse(name).map(LogLevel::as_str), Some(name));
+    }
+    assert_eq!(LogLevel::parse("loud"), None);
+}
+
+#[test]
+fn disabled_levels_are_never_formatted() {
+    let formatted = RefCell::new(0);
+    let logger = Recorder::new(LogLevel::Error, false);
+
+    log_message!(&logger, LogLevel::Debug, "{}", Counted(&formatted));
+    log_message!(&logger, LogLevel::Performance, "{}", Counted(&formatted));
+    assert_eq!(*formatted.borrow(), 0);
+
+    log_message!(&logger, LogLevel::Error, "{}", Counted(&formatted));
+    assert_eq!(*formatted.borrow(), 1);
+    assert_eq!(
+        *logger.messages.borrow(),
+        [(LogLevel::Error, "counted".to_string())]
+    );
+}
+
+#[test]
+fn custom_logger_receives_codec_messages() {
+    let input = b"abcabcabcabcabcabcabcabcabcabc".repeat(100);
+    let logger = Recorder::new(LogLevel::Debug, true);
+    let compressed = compress_with_logger(&input, &CompressOptions::default(), &logger);
+    let decompressed =
+        decompress_with_logger(&compressed, &DecompressOptions::default(), &logger).unwrap();
+    assert_eq!(decompressed, input);
+
+    let messages = logger.messages.borrow();
+    assert!(!messages.is_empty());
+    assert!(messages
+        .iter()
+        .all(|(level, _)| logger.filter.allows(*level)));
+    assert!(messages
+        .iter()
+        .any(|(level, _)| *level == LogLevel::Performance));
+}
@@ -0,0 +1,164 @@
+// Long-range pre-pass: far repeats are stored once, near ones are left to
+// LZ, round trips through both chunked algorithms, and rejection of forged
+// headers and matches.
+
+use super::rng::{gen_input, Rng};
+use crate::compression::long_range::{find_long_matches, LongMatch};
+use crate::constants::{LONG_RANGE_FLAG, UNCOMPRESSED_FLAG, WINDOW_SIZE};
+use crate::shared::varint::write_varint;
+use crate::{
+    compress_with_options, decompress_with_options, inspect, Algorithm, CompressOptions,
+    DecompressOptions, Error,
+};
+
+fn options(algorithm: Algorithm, long_range: bool) -> CompressOptions {
+    CompressOptions {
+        algorithm,
+        long_range,
+        ..CompressOptions::default()
+    }
+}
+
+fn decompress(stream: &[u8]) -> Result<Vec<u8>, Error> {
+    decompress_with_options(stream, &DecompressOptions::default())
+}
+
+/// A long-range stream over a stored residual.
+fn stream(original_len: usize, matches: &[(usize, usize, usize)], residual: &[u8]) -> Vec<u8> {
+    let mut stream = vec![LONG_RANGE_FLAG];
+    write_varint(&mut stream, original_len as u64);
+    write_varint(&mut stream, matches.len() as u64);
+    for &(literals, offset, length) in matches {
+        write_varint(&mut stream, literals as u64);
+        write_varint(&mut stream, offset as u64);
+        write_varint(&mut stream, length as u64);
+    }
+    stream.push(UNCOMPRESSED_FLAG);
+    stream.extend_from_slice(residual);
+    stream
+}
+
+#[test]
+fn far_repeats_are_stored_once() {
+    // A 10 KB block that nothing else compresses, repeated after 50 KB of
+    // text
+    let mut rng = Rng::new(48);
+    let block = rng.bytes(10_000);
+    let mut input = block.clone();
+    while input.len() < 60_000 {
+        let line = format!("line {} value {}\n", input.len(), rng.below(1000));
+        input.extend_from_slice(line.as_bytes());
+    }
+    input.extend_from_slice(&block);
+
+    let plain = compress_with_options(&input, &options(Algorithm::Best, false));
+    let long_range = compress_with_options(&input, &options(Algorithm::Best, true));
+    assert_eq!(long_range[0], LONG_RANGE_FLAG);
+    assert!(
+        long_range.len() + 9_000 < plain.len(),
+        "{} bytes with the pre-pass, {} without",
+        long_range.len(),
+        plain.len()
+    );
+    assert_eq!(decompress(&long_range).as_deref(), Ok(&input[..]));
+
+    let info = inspect(&long_range).unwrap();
+    assert_eq!(info.original_size, Some(input.len()));
+    assert!(!info.chunks.is_empty());
+}
+
+#[test]
+fn repeats_are_extended_both_ways() {
+    // Three copies of a block: one match, reaching back to the start of the
+    // second copy and on through the third
+    let block = Rng::new(49).bytes(5_000);
+    let input = block.repeat(3);
+    let (matches, residual) = find_long_matches(&input);
+    assert_eq!(
+        matches,
+        [LongMatch {
+            literals: 5_000,
+            offset: 5_000,
+            length: 10_000
+        }]
+    );
+    assert_eq!(residual, block);
+
+    // Closer than the LZ window, so left for the chunks
+    let block = &block[..WINDOW_SIZE / 2];
+    let (matches, residual) = find_long_matches(&block.repeat(4));
+    assert!(matches.is_empty());
+    assert_eq!(residual, block.repeat(4));
+}
+
+#[test]
+fn long_range_round_trips() {
+    let mut rng = Rng::new(50);
+    for case in 0..4 {
+        // Generated pieces, some of them repeated far apart
+        let pieces: Vec<Vec<u8>> = (0..6).map(|_| gen_input(&mut rng, 6000)).collect();
+        let mut input = Vec::new();
+        for _ in 0..8 {
+            input.extend_from_slice(&pieces[rng.below(pieces.len())]);
+        }
+        for algorithm in [Algorithm::Best, Algorithm::Cm] {
+            let compressed = compress_with_options(&input, &options(algorithm, true));
+            assert_eq!(
+                decompress(&compressed).as_deref(),
+                Ok(&input[..]),
+                "case {}, {}",
+                case,
+                algorithm
+            );
+        }
+    }
+    // Other algorithms ignore the option
+    let input = Rng::new(51).bytes(6_000).repeat(2);
+    assert_eq!(
+        compress_with_options(&input, &options(Algorithm::LzHuffman, true)),
+        compress_with_options(&input, &options(Algorithm::LzHuffman, false))
+    );
+}
+
+#[test]
+fn matches_are_restored() {
+    // Overlapping, so each copy reads bytes it just wrote
+    let forged = stream(12, &[(4, 4, 8)], b"abcd");
+    assert_eq!(decompress(&forged).as_deref(), Ok(&b"abcdabcdabcd"[..]));
+    let forged = stream(14, &[(2, 2, 4), (1, 3, 6)], b"abxy");
+    assert_eq!(decompress(&forged).as_deref(), Ok(&b"abababxabxabxy"[..]));
+}
+
+#[test]
+fn forged_streams_are_not_decoded() {
+    let nested = [&[LONG_RANGE_FLAG, 8, 0][..], &stream(8, &[(4, 4, 4)], b"abcd")].concat();
+    let corrupt_streams = [
+        // Before the start, from nowhere, past the end and short of it
+        stream(12, &[(4, 5, 8)], b"abcd"),
+        stream(12, &[(4, 0, 8)], b"abcd"),
+        stream(10, &[(4, 4, 8)], b"abcd"),
+        stream(13, &[(4, 4, 8)], b"abcd"),
+        // More literals than the residual holds
+        stream(12, &[(5, 4, 7)], b"abcd"),
+        // Cut short, or wrapping another long-range stream
+        vec![LONG_RANGE_FLAG, 12, 1, 4, 4],
+        vec![LONG_RANGE_FLAG, 0x80],
+        nested,
+    ];
+    for corrupt in corrupt_streams {
+        assert_eq!(decompress(&corrupt).as_deref(), Ok(&corrupt[1..]));
+    }
+}
+
+#[test]
+fn forged_size_fails_before_decoding() {
+    let forged = stream(1 << 40, &[(4, 4, 1 << 39)], b"abcd");
+    let options = DecompressOptions {
+        max_output_size: 1 << 20,
+        ..DecompressOptions::default()
+    };
+    assert_eq!(
+        decompress_with_options(&forged, &options),
+        Err(Error::OutputTooLarge { limit: 1 << 20 })
+    );
+}
@@ -0,0 +1,94 @@
+// LZ4 frames: small inputs and block boundaries, interop with the reference
+// `lz4` tool, and rejection of corrupt frames.
+
+use crate::constants::{LZ4_BLOCK_SIZE, LZ4_FLAG};
+use crate::shared::xxhash::xxh32;
+use crate::{
+    compress_with_options, decompress_with_options, inspect, Algorithm, CompressOptions,
+    DecompressOptions,
+};
+
+fn lz4_options(level: u8) -> CompressOptions {
+    CompressOptions {
+        algorithm: Algorithm::Lz4,
+        level,
+        ..CompressOptions::default()
+    }
+}
+
+#[test]
+fn xxh32_matches_reference_values() {
+    assert_eq!(xxh32(b"", 0), 0x02CC_5D05);
+    assert_eq!(xxh32(b"a", 0), 0x550D_7456);
+    assert_eq!(xxh32(b"abc", 0), 0x32D1_53FF);
+    assert_eq!(
+        xxh32(b"Nobody inspects the spammish repetition", 0),
+        0xE229_3B2F
+    );
+}
+
+#[test]
+fn small_inputs_and_block_boundaries_round_trip() {
+    let mut large = b"lz4 block boundary 0123456789 "
+        .iter()
+        .copied()
+        .cycle()
+        .take(LZ4_BLOCK_SIZE + 1000)
+        .collect::<Vec<u8>>();
+    large[LZ4_BLOCK_SIZE - 3] = b'!';
+    let inputs = [Vec::new(), vec![7], b"thirteen byte".to_vec(), large];
+
+    for input in inputs {
+        let compressed = compress_with_options(&input, &lz4_options(1));
+        assert_eq!(compressed[0], LZ4_FLAG);
+        let info = inspect(&compressed).unwrap();
+        assert_eq!(info.original_size, Some(input.len()));
+        let decoded = decompress_with_options(&compressed, &DecompressOptions::default());
+        assert_eq!(
+            decoded.as_deref(),
+            Ok(input.as_slice()),
+            "{} bytes",
+            input.len()
+        );
+    }
+}
+
+#[test]
+fn reads_frames_from_the_reference_tool() {
+    let expected = include_bytes!("../../test/files/test.txt");
+    // `lz4 -9 --content-size` and `lz4 -BD -B100 -BX`: linked 100-byte
+    // blocks with block checksums, so matches cross block boundaries
+    let single = include_bytes!("../../test/lz4/test.txt.lz4");
+    let linked = include_bytes!("../../test/lz4/test.txt.linked.lz4");
+
+    for frame in [&single[..], &linked[..]] {
+        let decoded = decompress_with_options(frame, &DecompressOptions::default());
+        assert_eq!(decoded.as_deref(), Ok(&expected[..]));
+    }
+
+    // Concatenated frames, with a skippable frame between them
+    let mut stream = single.to_vec();
+    stream.extend_from_slice(&0x184D_2A53u32.to_le_bytes());
+    stream.extend_from_slice(&3u32.to_le_bytes());
+    stream.extend_from_slice(b"xyz");
+    stream.extend_from_slice(linked);
+    let decoded = decompress_with_options(&stream, &DecompressOptions::default()).unwrap();
+    assert_eq!(decoded, [&expected[..], &expected[..]].concat());
+}
+
+#[test]
+fn corrupt_frames_are_not_decoded() {
+    let input = include_bytes!("../../test/files/test.txt");
+    let compressed = compress_with_options(input, &lz4_options(6));
+
+    // Header checksum, block data, content checksum and a truncated frame
+    for position in [13, 30, compressed.len() - 1] {
+        let mut corrupt = compressed.clone();
+        corrupt[position] ^= 0x20;
+        let decoded = decompress_with_options(&corrupt, &DecompressOptions::default());
+        assert_eq!(decoded, Ok(corrupt), "byte {}", position);
+    }
+    let truncated = &compressed[..compressed.len() - 5];
+    let decoded = decompress_with_options(truncated, &DecompressOptions::default());
+    assert_eq!(decoded.as_deref(), Ok(truncated));
+}
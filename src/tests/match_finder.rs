@@ -0,0 +1,115 @@
+// Match finders: candidates are real matches in increasing length, binary
+// trees find the longest one and overlapping copies, how far ahead the top
+// level looks, and which levels use them.
+
+use super::rng::{gen_input, Rng};
+use crate::compression::binary_tree::BinaryTreeFinder;
+use crate::compression::levels::level_params;
+use crate::compression::matcher::{find_matches, Candidate, Finder, HashChainFinder, MatchFinder};
+use crate::constants::{DEFAULT_LEVEL, MAX_LEVEL, MAX_MATCH_LEN, MIN_MATCH_LEN, WINDOW_SIZE};
+use crate::shared::token::Token;
+use crate::NoopLogger;
+
+/// Every position's candidates from `finder`, checked against the data.
+fn candidates(mut finder: impl MatchFinder, data: &[u8]) -> Vec<Vec<Candidate>> {
+    (0..data.len())
+        .map(|pos| {
+            let mut out = Vec::new();
+            finder.find(data, pos, &mut out);
+            for (i, found) in out.iter().enumerate() {
+                assert!(found.length >= MIN_MATCH_LEN && found.length <= MAX_MATCH_LEN);
+                assert!(found.offset >= 1 && found.offset <= WINDOW_SIZE.min(pos));
+                assert!(i == 0 || found.length > out[i - 1].length);
+                let start = pos - found.offset;
+                assert_eq!(
+                    data[start..start + found.length],
+                    data[pos..pos + found.length],
+                    "pos {}, {:?}",
+                    pos,
+                    found
+                );
+            }
+            out
+        })
+        .collect()
+}
+
+/// Longest match at `pos` within the window, overlapping or not.
+fn brute_force_longest(data: &[u8], pos: usize) -> usize {
+    (1..=WINDOW_SIZE.min(pos))
+        .map(|offset| {
+            (0..MAX_MATCH_LEN.min(data.len() - pos))
+                .take_while(|&i| data[pos - offset + i] == data[pos + i])
+                .count()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn longest(candidates: &[Candidate]) -> usize {
+    candidates.last().map_or(0, |found| found.length)
+}
+
+#[test]
+fn binary_trees_find_the_longest_match() {
+    let mut rng = Rng::new(0xB7);
+    for case in 0..4 {
+        let data = gen_input(&mut rng, 3000);
+        let trees = candidates(BinaryTreeFinder::new(data.len(), WINDOW_SIZE), &data);
+        let chains = candidates(HashChainFinder::new(WINDOW_SIZE), &data);
+        for pos in 0..data.len() {
+            // Only 4-byte matches are sure to share a tree
+            let expected = brute_force_longest(&data, pos);
+            if expected > MIN_MATCH_LEN {
+                assert_eq!(longest(&trees[pos]), expected, "case {}, pos {}", case, pos);
+            }
+            // Hash chains never overlap `pos`, so they find no more
+            if longest(&chains[pos]) > MIN_MATCH_LEN {
+                assert!(longest(&trees[pos]) >= longest(&chains[pos]));
+            }
+            for found in &chains[pos] {
+                assert!(found.offset >= found.length);
+            }
+        }
+    }
+}
+
+#[test]
+fn binary_trees_cover_runs_with_overlapping_matches() {
+    let data = b"xyz".repeat(100);
+    let tokens = find_matches(&[], &data, &level_params(MAX_LEVEL), false, &NoopLogger);
+    assert_eq!(
+        tokens,
+        [
+            Token::Literal(b'x'),
+            Token::Literal(b'y'),
+            Token::Literal(b'z'),
+            Token::Match(3, MAX_MATCH_LEN as u16),
+            Token::Match(3, 42),
+        ]
+    );
+}
+
+#[test]
+fn top_level_defers_matches_two_positions() {
+    // At the second "abcd", "bcd" is no longer but "cdefghij" is
+    let data = b"abcd_cdefghij_abcdefghij";
+    let literals = |count| data[..count].iter().map(|&byte| Token::Literal(byte));
+
+    let tokens = find_matches(&[], data, &level_params(MAX_LEVEL), false, &NoopLogger);
+    let expected: Vec<_> = literals(16).chain([Token::Match(11, 8)]).collect();
+    assert_eq!(tokens, expected);
+
+    let tokens = find_matches(&[], data, &level_params(MAX_LEVEL - 1), false, &NoopLogger);
+    let expected: Vec<_> = literals(14)
+        .chain([Token::Match(14, 4), Token::Match(11, 6)])
+        .collect();
+    assert_eq!(tokens, expected);
+}
+
+#[test]
+fn top_levels_use_binary_trees() {
+    assert_eq!(level_params(DEFAULT_LEVEL).finder, Finder::HashChain);
+    assert_eq!(level_params(MAX_LEVEL - 1).finder, Finder::BinaryTree);
+    assert_eq!(level_params(MAX_LEVEL).finder, Finder::BinaryTree);
+}
@@ -0,0 +1,19 @@
+mod alloc;
+mod chunk_mode;
+mod cm;
+mod dictionary;
+mod ffi;
+mod fse;
+mod fuzz_replay;
+mod huffman;
+mod limits;
+mod logger;
+mod long_range;
+mod lz4;
+mod match_finder;
+mod options;
+mod progress;
+mod report;
+mod rng;
+mod roundtrip;
+mod snappy;
@@ -0,0 +1,146 @@
+// Option names and aliases, and the validation behind the JS options object.
+
+use crate::utils::validate::{apply_option, OptionValue};
+use crate::{Algorithm, ChunkMode, CompressOptions, DecompressOptions, LogLevel};
+
+fn apply(key: &str, value: OptionValue) -> Result<(CompressOptions, DecompressOptions), String> {
+    let mut compress = CompressOptions::default();
+    let mut decompress = DecompressOptions::default();
+    apply_option(&mut compress, &mut decompress, key, &value)?;
+    Ok((compress, decompress))
+}
+
+#[test]
+fn algorithm_names_and_aliases_parse() {
+    for algorithm in Algorithm::ALL {
+        assert_eq!(Algorithm::parse(algorithm.name()), Some(algorithm));
+    }
+    for (name, algorithm) in [
+        ("auto", Algorithm::Best),
+        ("Chunked", Algorithm::Best),
+        ("lz", Algorithm::LzHuffman),
+        ("lz-huffman", Algorithm::LzHuffman),
+        ("LZ_HUFFMAN", Algorithm::LzHuffman),
+        ("BWT", Algorithm::Bwt),
+        ("num pack", Algorithm::NumPack),
+    ] {
+        assert_eq!(Algorithm::parse(name), Some(algorithm), "{}", name);
+    }
+    for name in ["", "zip", "lzz", "a-name-longer-than-sixteen-bytes"] {
+        assert_eq!(Algorithm::parse(name), None, "{}", name);
+    }
+
+    for level in LogLevel::ALL {
+        assert_eq!(LogLevel::parse(level.as_str()), Some(level));
+        assert_eq!(LogLevel::parse(&level.as_str().to_uppercase()), Some(level));
+    }
+    assert_eq!(LogLevel::parse("verbose"), None);
+}
+
+#[test]
+fn valid_options_are_applied() {
+    let (compress, _) = apply("algorithm", OptionValue::Str("lz".into())).unwrap();
+    assert_eq!(compress.algorithm, Algorithm::LzHuffman);
+    let (compress, _) = apply("algorithm", OptionValue::Number(4.0)).unwrap();
+    assert_eq!(compress.algorithm, Algorithm::Bwt);
+    let (compress, _) = apply("level", OptionValue::Number(9.0)).unwrap();
+    assert_eq!(compress.level, 9);
+    let (compress, _) = apply("chunkMode", OptionValue::Str("Dependent".into())).unwrap();
+    assert_eq!(compress.chunk_mode, ChunkMode::Dependent);
+    let (compress, _) = apply("recordWidth", OptionValue::Number(12.0)).unwrap();
+    assert_eq!(compress.record_width, Some(12));
+    let (_, decompress) = apply("maxRatio", OptionValue::Number(50.0)).unwrap();
+    assert_eq!(decompress.max_ratio, Some(50.0));
+
+    let (compress, decompress) = apply("logLevel", OptionValue::Str("Debug".into())).unwrap();
+    assert_eq!(compress.log_level, LogLevel::Debug);
+    assert_eq!(decompress.log_level, LogLevel::Debug);
+    let (compress, _) = apply("logLevel", OptionValue::Number(1.0)).unwrap();
+    assert_eq!(compress.log_level, LogLevel::Error);
+
+    // Callbacks are checked by the JS layer and pass through here untouched
+    assert!(apply("onProgress", OptionValue::Other("function".into())).is_ok());
+}
+
+#[test]
+fn invalid_options_are_rejected() {
+    let cases = [
+        (
+            "algoritm",
+            OptionValue::Str("lz".into()),
+            "unknown option 'algoritm'",
+        ),
+        (
+            "algorithm",
+            OptionValue::Str("zip".into()),
+            "unknown algorithm 'zip'",
+        ),
+        ("algorithm", OptionValue::Number(11.0), "algorithm 11 is not"),
+        (
+            "algorithm",
+            OptionValue::Number(1.5),
+            "algorithm 1.5 is not",
+        ),
+        ("algorithm", OptionValue::Bool(true), "got boolean"),
+        ("level", OptionValue::Number(0.0), "from 1 to 9, got 0"),
+        ("level", OptionValue::Number(6.5), "from 1 to 9, got 6.5"),
+        (
+            "level",
+            OptionValue::Str("6".into()),
+            "must be an integer, got string",
+        ),
+        ("chunkSize", OptionValue::Number(0.0), "option 'chunkSize'"),
+        (
+            "recordWidth",
+            OptionValue::Number(70000.0),
+            "from 1 to 65535",
+        ),
+        (
+            "maxOutputSize",
+            OptionValue::Number(-1.0),
+            "option 'maxOutputSize'",
+        ),
+        (
+            "maxRatio",
+            OptionValue::Number(0.0),
+            "positive number, got 0",
+        ),
+        ("maxRatio", OptionValue::Number(f64::NAN), "positive number"),
+        (
+            "logLevel",
+            OptionValue::Str("loud".into()),
+            "unknown logLevel 'loud'",
+        ),
+        ("logLevel", OptionValue::Number(5.0), "logLevel 5 is not"),
+        (
+            "verbose",
+            OptionValue::Number(1.0),
+            "must be a boolean, got number",
+        ),
+        ("verbose", OptionValue::Other("object".into()), "got object"),
+        (
+            "dictionary",
+            OptionValue::Str("yes".into()),
+            "must be a boolean, got string",
+        ),
+        (
+            "chunkMode",
+            OptionValue::Str("linked".into()),
+            "unknown chunkMode 'linked'",
+        ),
+        (
+            "chunkMode",
+            OptionValue::Number(1.0),
+            "must be a string, got number",
+        ),
+        (
+            "longRange",
+            OptionValue::Number(1.0),
+            "must be a boolean, got number",
+        ),
+    ];
+    for (key, value, expected) in cases {
+        let err = apply(key, value).unwrap_err();
+        assert!(err.contains(expected), "{}: {}", key, err);
+    }
+}
@@ -0,0 +1,115 @@
+// Progress reports between chunks, and cancellation through `ProgressHooks`.
+
+use super::rng::{gen_input, Rng};
+use crate::{
+    compress_with_options, compress_with_progress, decompress_with_options,
+    decompress_with_progress, CompressOptions, DecompressOptions, Error, NoopLogger, ProgressHooks,
+};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const CHUNK_SIZE: usize = 1024;
+
+fn chunked_options() -> CompressOptions {
+    CompressOptions {
+        chunk_size: CHUNK_SIZE,
+        ..CompressOptions::default()
+    }
+}
+
+fn sample() -> Vec<u8> {
+    gen_input(&mut Rng::new(0x9806), 64 * 1024)
+        .into_iter()
+        .chain(b"progress ".repeat(1024))
+        .collect()
+}
+
+#[test]
+fn reports_once_per_chunk() {
+    let input = sample();
+    let calls = RefCell::new(Vec::new());
+    let on_progress = |done, total| calls.borrow_mut().push((done, total));
+    let hooks = ProgressHooks {
+        on_progress: Some(&on_progress),
+        cancel: None,
+    };
+
+    let compressed =
+        compress_with_progress(&input, &chunked_options(), &NoopLogger, &hooks).unwrap();
+    let expected: Vec<(usize, usize)> = (1..=input.len().div_ceil(CHUNK_SIZE))
+        .map(|chunk| ((chunk * CHUNK_SIZE).min(input.len()), input.len()))
+        .collect();
+    assert_eq!(*calls.borrow(), expected);
+
+    calls.borrow_mut().clear();
+    let decompressed = decompress_with_progress(
+        &compressed,
+        &DecompressOptions::default(),
+        &NoopLogger,
+        &hooks,
+    )
+    .unwrap();
+    assert_eq!(decompressed, input);
+    assert_eq!(*calls.borrow(), expected);
+}
+
+#[test]
+fn cancel_flag_stops_between_chunks() {
+    let input = sample();
+    let cancel = AtomicBool::new(false);
+    let calls = RefCell::new(0);
+    let on_progress = |_, _| {
+        *calls.borrow_mut() += 1;
+        if *calls.borrow() == 3 {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    };
+    let hooks = ProgressHooks {
+        on_progress: Some(&on_progress),
+        cancel: Some(&cancel),
+    };
+
+    let result = compress_with_progress(&input, &chunked_options(), &NoopLogger, &hooks);
+    assert_eq!(result, Err(Error::Cancelled));
+    assert_eq!(*calls.borrow(), 3);
+
+    let compressed = compress_with_options(&input, &chunked_options());
+    cancel.store(false, Ordering::Relaxed);
+    *calls.borrow_mut() = 0;
+    let result = decompress_with_progress(
+        &compressed,
+        &DecompressOptions::default(),
+        &NoopLogger,
+        &hooks,
+    );
+    assert_eq!(result, Err(Error::Cancelled));
+    assert_eq!(*calls.borrow(), 3);
+    assert_eq!(
+        decompress_with_options(&compressed, &DecompressOptions::default()).unwrap(),
+        input
+    );
+}
+
+#[test]
+fn cancelled_before_start_for_every_algorithm() {
+    let cancel = AtomicBool::new(true);
+    let hooks = ProgressHooks {
+        on_progress: None,
+        cancel: Some(&cancel),
+    };
+    for algorithm in crate::Algorithm::ALL {
+        let options = CompressOptions {
+            algorithm,
+            ..CompressOptions::default()
+        };
+        let result = compress_with_progress(&sample(), &options, &NoopLogger, &hooks);
+        assert_eq!(result, Err(Error::Cancelled), "{}", algorithm);
+    }
+    let result = decompress_with_progress(
+        &[0xAA, 1, 2],
+        &DecompressOptions::default(),
+        &NoopLogger,
+        &hooks,
+    );
+    assert_eq!(result, Err(Error::Cancelled));
+}
@@ -0,0 +1,77 @@
+// `compress_with_report` must describe the stream it returns.
+
+use super::rng::{gen_input, Rng};
+use crate::constants::{CHUNKED_FLAG, RLE_FLAG, UNCOMPRESSED_FLAG};
+use crate::{compress_with_options, compress_with_report, inspect, CompressOptions};
+
+#[test]
+fn report_matches_chunked_stream() {
+    let mut rng = Rng::new(0x4E90);
+    let options = CompressOptions {
+        chunk_size: 1024,
+        ..CompressOptions::default()
+    };
+
+    for case in 0..16 {
+        let input = gen_input(&mut rng, 8192);
+        let (output, report) = compress_with_report(&input, &options);
+        assert_eq!(
+            output,
+            compress_with_options(&input, &options),
+            "case {}",
+            case
+        );
+        assert_eq!(report.flag, output[0]);
+        assert_eq!(report.original_size, input.len());
+        assert_eq!(report.compressed_size, output.len());
+
+        if report.flag != CHUNKED_FLAG {
+            continue;
+        }
+        let info = inspect(&output).unwrap();
+        assert_eq!(report.chunks.len(), info.chunks.len(), "case {}", case);
+        for (chunk, stored) in report.chunks.iter().zip(&info.chunks) {
+            assert_eq!(chunk.chain, stored.methods);
+            assert_eq!(chunk.compressed_size, stored.compressed_size);
+            assert!((0.0..=8.0).contains(&chunk.entropy));
+
+            let chosen: Vec<u8> = chunk
+                .stages
+                .iter()
+                .filter_map(|stage| stage.chosen)
+                .collect();
+            assert_eq!(chosen, chunk.chain);
+            for stage in &chunk.stages {
+                let best = stage.candidates.iter().map(|c| c.score).min().unwrap();
+                if let Some(method) = stage.chosen {
+                    let picked = stage
+                        .candidates
+                        .iter()
+                        .find(|c| c.method == method)
+                        .unwrap();
+                    assert_eq!(picked.score, best);
+                }
+            }
+        }
+        assert_eq!(
+            report.chunks.iter().map(|c| c.original_size).sum::<usize>(),
+            input.len()
+        );
+    }
+}
+
+#[test]
+fn report_for_single_algorithm_and_small_input() {
+    let options = CompressOptions {
+        algorithm: crate::Algorithm::Rle,
+        ..CompressOptions::default()
+    };
+    let (_, report) = compress_with_report(&[b'x'; 500], &options);
+    assert_eq!(report.flag, RLE_FLAG);
+    assert!(report.chunks.is_empty());
+    assert!(report.ratio < 0.1);
+
+    let (output, report) = compress_with_report(b"tiny", &CompressOptions::default());
+    assert_eq!(report.flag, UNCOMPRESSED_FLAG);
+    assert_eq!(report.compressed_size, output.len());
+}
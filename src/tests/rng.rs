@@ -0,0 +1,92 @@
+// Deterministic input generation for the property tests, so the suite needs
+// no external crates and every failure can be replayed from its seed.
+
+const WORDS: [&str; 16] = [
+    "the", "quick", "brown", "fox", "jumps", "over", "lazy", "dog", "lorem", "ipsum", "\"id\":",
+    "\"name\":", "{", "}", ", ", "\n",
+];
+
+/// xorshift64* generator.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform value in `0..n`; `n` must be non-zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn byte(&mut self) -> u8 {
+        self.next_u64() as u8
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.byte()).collect()
+    }
+}
+
+/// Produces an input of at most `max_len` bytes in one of several shapes the
+/// codecs treat differently: noise, words, runs, numbers, records and a
+/// small alphabet.
+pub fn gen_input(rng: &mut Rng, max_len: usize) -> Vec<u8> {
+    let len = rng.below(max_len + 1);
+    let mut out = Vec::with_capacity(len + 32);
+
+    match rng.below(6) {
+        0 => out = rng.bytes(len),
+        1 => {
+            while out.len() < len {
+                out.extend_from_slice(WORDS[rng.below(WORDS.len())].as_bytes());
+                out.push(b' ');
+            }
+        }
+        2 => {
+            while out.len() < len {
+                let value = rng.byte();
+                let run = 1 + rng.below(300);
+                out.extend(std::iter::repeat_n(value, run));
+            }
+        }
+        3 => {
+            let mut value = rng.below(100_000) as i64 - 50_000;
+            while out.len() < len {
+                value += rng.below(200) as i64 - 100;
+                let number = match rng.below(3) {
+                    0 => format!("{},", value),
+                    1 => format!("{}.{:02},", value, rng.below(100)),
+                    _ => format!("{:05}\n", value.unsigned_abs()),
+                };
+                out.extend_from_slice(number.as_bytes());
+            }
+        }
+        4 => {
+            let width = 2 + rng.below(40);
+            let mut record = rng.bytes(width);
+            while out.len() < len {
+                let field = rng.below(width);
+                record[field] = record[field].wrapping_add(rng.below(4) as u8);
+                out.extend_from_slice(&record);
+            }
+        }
+        _ => {
+            let size = 1 + rng.below(4);
+            let alphabet = rng.bytes(size);
+            while out.len() < len {
+                out.push(alphabet[rng.below(alphabet.len())]);
+            }
+        }
+    }
+
+    out.truncate(len);
+    out
+}
@@ -0,0 +1,268 @@
+use super::rng::{gen_input, Rng};
+use crate::compression::strategies::{
+    compress_bwt, compress_chunked, compress_cm, compress_delta, compress_fse, compress_lz,
+    compress_method, compress_numpack, compress_record, compress_rle, CHAIN_METHODS, CHUNK_SIZE,
+    MAX_METHODS,
+};
+use crate::constants::{
+    BWT_FLAG, CM_FLAG, COMPRESSED_FLAG, DEFAULT_LEVEL, DELTA_FLAG, FSE_FLAG, MAX_LEVEL,
+    MIN_FILE_SIZE, MIN_LEVEL, NUMPACK_FLAG, NUMPACK_MARKER, RECORD_FLAG, RLE_FLAG,
+};
+use crate::decompression::limit::OutputLimit;
+use crate::decompression::{decompress_chunked, decompress_method};
+use crate::shared::compression::CompressionResult;
+use crate::{
+    compress_snappy_raw, compress_with_options, decompress_snappy_raw, decompress_with_options,
+    Algorithm, CompressOptions, DecompressOptions, NoProgress, NoopLogger,
+};
+
+const RANDOM_CASES: u64 = 64;
+const RANDOM_MAX_LEN: usize = 4096;
+
+fn edge_cases() -> Vec<(String, Vec<u8>)> {
+    let mut rng = Rng::new(0xED6E);
+    let mut cases = vec![
+        ("empty".to_string(), Vec::new()),
+        ("single byte".to_string(), vec![0x42]),
+        ("all 0xFF".to_string(), vec![0xFF; 5000]),
+        ("single repeated byte".to_string(), vec![b'a'; 5000]),
+        ("rle markers".to_string(), [0xFE, 0xFF, 0x00].repeat(700)),
+        ("numpack markers".to_string(), vec![NUMPACK_MARKER; 300]),
+    ];
+
+    for len in [MIN_FILE_SIZE - 1, MIN_FILE_SIZE, MIN_FILE_SIZE + 1] {
+        cases.push((format!("{} random bytes", len), rng.bytes(len)));
+        cases.push((format!("{} bytes of 0xFF", len), vec![0xFF; len]));
+    }
+
+    for chunks in [1, 2] {
+        let len = chunks * CHUNK_SIZE;
+        cases.push((format!("{} bytes of 0xFF", len), vec![0xFF; len]));
+        cases.push((
+            format!("{} bytes of text", len),
+            b"GoudCompressor round trip ".iter().copied().cycle().take(len).collect(),
+        ));
+        cases.push((format!("{} random bytes", len), rng.bytes(len)));
+    }
+
+    cases
+}
+
+fn random_cases(seed: u64) -> impl Iterator<Item = (String, Vec<u8>)> {
+    (0..RANDOM_CASES).map(move |case| {
+        let mut rng = Rng::new(seed ^ case);
+        (format!("seed {:#x} case {}", seed, case), gen_input(&mut rng, RANDOM_MAX_LEN))
+    })
+}
+
+fn all_cases(seed: u64) -> impl Iterator<Item = (String, Vec<u8>)> {
+    edge_cases().into_iter().chain(random_cases(seed))
+}
+
+fn unlimited(data: &[u8]) -> OutputLimit {
+    OutputLimit::new(&DecompressOptions::default(), data.len())
+}
+
+fn decode_method(method: u8, data: &[u8]) -> Vec<u8> {
+    decompress_method(method, data, &unlimited(data), &NoopLogger).unwrap()
+}
+
+fn decode_chunked(data: &[u8]) -> Vec<u8> {
+    decompress_chunked(data, &unlimited(data), &NoopLogger, &NoProgress).unwrap()
+}
+
+fn assert_round_trip(label: &str, input: &[u8], output: &[u8]) {
+    assert!(
+        input == output,
+        "{}: round trip of {} bytes produced {} bytes (first difference at {:?})",
+        label,
+        input.len(),
+        output.len(),
+        input.iter().zip(output).position(|(a, b)| a != b)
+    );
+}
+
+fn check_codec(name: &str, flag: u8, seed: u64, encode: impl Fn(&[u8]) -> Vec<u8>) {
+    for (label, input) in all_cases(seed) {
+        let decoded = decode_method(flag, &encode(&input));
+        assert_round_trip(&format!("{} / {}", name, label), &input, &decoded);
+    }
+}
+
+#[test]
+fn lz_round_trips() {
+    for level in MIN_LEVEL..=MAX_LEVEL {
+        check_codec("lz", COMPRESSED_FLAG, 0x11, |data| {
+            compress_lz(data, level, false, &NoopLogger)
+        });
+    }
+}
+
+#[test]
+fn lz_dictionary_round_trips() {
+    for level in [MIN_LEVEL, DEFAULT_LEVEL, MAX_LEVEL] {
+        check_codec("lz+dictionary", COMPRESSED_FLAG, 0x12, |data| {
+            compress_lz(data, level, true, &NoopLogger)
+        });
+    }
+}
+
+#[test]
+fn rle_round_trips() {
+    check_codec("rle", RLE_FLAG, 0x22, |data| compress_rle(data, &NoopLogger));
+}
+
+#[test]
+fn delta_round_trips() {
+    check_codec("delta", DELTA_FLAG, 0x33, |data| compress_delta(data, &NoopLogger));
+}
+
+#[test]
+fn bwt_round_trips() {
+    check_codec("bwt", BWT_FLAG, 0x44, |data| compress_bwt(data, &NoopLogger));
+}
+
+#[test]
+fn numpack_round_trips() {
+    check_codec("numpack", NUMPACK_FLAG, 0x55, |data| {
+        compress_numpack(data, &NoopLogger)
+    });
+}
+
+#[test]
+fn record_round_trips() {
+    for width in [None, Some(1), Some(3), Some(24), Some(u16::MAX as usize)] {
+        check_codec("record", RECORD_FLAG, 0x66, |data| {
+            compress_record(data, width, &NoopLogger)
+        });
+    }
+}
+
+#[test]
+fn fse_round_trips() {
+    check_codec("fse", FSE_FLAG, 0x77, |data| compress_fse(data, &NoopLogger));
+}
+
+#[test]
+fn cm_round_trips() {
+    check_codec("cm", CM_FLAG, 0x88, |data| compress_cm(data, &NoopLogger));
+}
+
+/// Every ordered selection of distinct methods `compress_chunk` could build.
+fn chain_permutations() -> Vec<Vec<u8>> {
+    let mut chains: Vec<Vec<u8>> = vec![Vec::new()];
+    let mut all = Vec::new();
+    for _ in 0..MAX_METHODS {
+        chains = chains
+            .iter()
+            .flat_map(|chain| {
+                CHAIN_METHODS
+                    .iter()
+                    .filter(|method| !chain.contains(method))
+                    .map(move |&method| [chain.as_slice(), &[method]].concat())
+            })
+            .collect();
+        all.extend(chains.iter().cloned());
+    }
+    all
+}
+
+#[test]
+fn every_chain_permutation_round_trips() {
+    let options = CompressOptions::default();
+    let mut rng = Rng::new(0x77);
+    let inputs: Vec<Vec<u8>> = (0..6).map(|_| gen_input(&mut rng, 1024)).collect();
+
+    for chain in chain_permutations() {
+        for input in &inputs {
+            let mut data = input.clone();
+            for &method in &chain {
+                data = compress_method(method, &data, &options, &NoopLogger);
+            }
+            for &method in chain.iter().rev() {
+                data = decode_method(method, &data);
+            }
+            assert_round_trip(&format!("chain {:02X?}", chain), input, &data);
+        }
+    }
+}
+
+#[test]
+fn chunked_round_trips() {
+    let options = CompressOptions::default();
+    for (label, input) in all_cases(0x88) {
+        let decoded = match compress_chunked(&input, &options, &NoopLogger, &NoProgress).unwrap().0 {
+            CompressionResult::Compressed(data, _) => decode_chunked(&data),
+            CompressionResult::Uncompressed(data) => data,
+        };
+        assert_round_trip(&format!("chunked / {}", label), &input, &decoded);
+    }
+}
+
+#[test]
+fn chunked_round_trips_at_every_level_and_chunk_size() {
+    for level in MIN_LEVEL..=MAX_LEVEL {
+        for chunk_size in [1, 100, 1024] {
+            let options = CompressOptions {
+                level,
+                chunk_size,
+                ..CompressOptions::default()
+            };
+            for (label, input) in random_cases(0xAA).take(8) {
+                let decoded = match compress_chunked(&input, &options, &NoopLogger, &NoProgress).unwrap().0 {
+                    CompressionResult::Compressed(data, _) => decode_chunked(&data),
+                    CompressionResult::Uncompressed(data) => data,
+                };
+                let label = format!("level {} chunk {} / {}", level, chunk_size, label);
+                assert_round_trip(&label, &input, &decoded);
+            }
+        }
+    }
+}
+
+#[test]
+fn end_to_end_round_trips_for_every_algorithm() {
+    for algorithm in Algorithm::ALL {
+        let options = CompressOptions {
+            algorithm,
+            ..CompressOptions::default()
+        };
+        for (label, input) in all_cases(0x99) {
+            let compressed = compress_with_options(&input, &options);
+            let decoded =
+                decompress_with_options(&compressed, &DecompressOptions::default()).unwrap();
+            assert_round_trip(&format!("{} / {}", algorithm, label), &input, &decoded);
+        }
+    }
+}
+
+#[test]
+fn lz4_and_snappy_round_trip_at_every_level() {
+    for level in MIN_LEVEL..=MAX_LEVEL {
+        for algorithm in [Algorithm::Lz4, Algorithm::Snappy] {
+            let options = CompressOptions {
+                algorithm,
+                level,
+                ..CompressOptions::default()
+            };
+            for (label, input) in random_cases(0xBB) {
+                let compressed = compress_with_options(&input, &options);
+                let decoded =
+                    decompress_with_options(&compressed, &DecompressOptions::default()).unwrap();
+                let label = format!("{} level {} / {}", algorithm, level, label);
+                assert_round_trip(&label, &input, &decoded);
+            }
+        }
+
+        let options = CompressOptions {
+            level,
+            ..CompressOptions::default()
+        };
+        for (label, input) in random_cases(0xBC) {
+            let raw = compress_snappy_raw(&input, &options);
+            let decoded = decompress_snappy_raw(&raw, &DecompressOptions::default()).unwrap();
+            let label = format!("raw Snappy level {} / {}", level, label);
+            assert_round_trip(&label, &input, &decoded);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+// Snappy: framed and raw block boundaries, hand-built streams covering every
+// element and chunk type, and rejection of corrupt streams.
+
+use crate::constants::{SNAPPY_BLOCK_SIZE, SNAPPY_STREAM_IDENTIFIER};
+use crate::shared::crc32c::{crc32c, masked_crc32c};
+use crate::{
+    compress_snappy_raw, compress_with_options, decompress_snappy_raw, decompress_with_options,
+    inspect, stream_name, Algorithm, CompressOptions, DecompressOptions,
+};
+
+fn snappy_options(level: u8) -> CompressOptions {
+    CompressOptions {
+        algorithm: Algorithm::Snappy,
+        level,
+        ..CompressOptions::default()
+    }
+}
+
+/// Raw Snappy for "abcd" * 6 + "dd" + "x" * 61: a literal, a 1-byte-offset
+/// copy overlapping its source, 2- and 4-byte-offset copies and a literal
+/// whose length follows the tag.
+fn hand_built_raw() -> (Vec<u8>, Vec<u8>) {
+    let mut raw = vec![87, 0x0C];
+    raw.extend_from_slice(b"abcd");
+    raw.extend_from_slice(&[0x11, 4, 0x2E, 12, 0, 0x07, 1, 0, 0, 0, 0xF0, 60]);
+    raw.extend_from_slice(&[b'x'; 61]);
+    let expected = [&b"abcd".repeat(6)[..], b"dd", &[b'x'; 61]].concat();
+    (raw, expected)
+}
+
+fn chunk(kind: u8, body: &[u8]) -> Vec<u8> {
+    let mut chunk = vec![kind];
+    chunk.extend_from_slice(&(body.len() as u32).to_le_bytes()[..3]);
+    chunk.extend_from_slice(body);
+    chunk
+}
+
+fn data_chunk(kind: u8, data: &[u8], decoded: &[u8]) -> Vec<u8> {
+    chunk(
+        kind,
+        &[&masked_crc32c(decoded).to_le_bytes()[..], data].concat(),
+    )
+}
+
+#[test]
+fn crc32c_matches_reference_values() {
+    assert_eq!(crc32c(b""), 0);
+    assert_eq!(crc32c(b"123456789"), 0xE306_9283);
+    assert_eq!(crc32c(&[0; 32]), 0x8A91_36AA);
+    assert_eq!(crc32c(&[0xFF; 32]), 0x62A8_AB43);
+}
+
+#[test]
+fn small_inputs_and_block_boundaries_round_trip() {
+    let mut large = b"snappy fragment boundary 0123456789 "
+        .iter()
+        .copied()
+        .cycle()
+        .take(3 * SNAPPY_BLOCK_SIZE + 1000)
+        .collect::<Vec<u8>>();
+    large[SNAPPY_BLOCK_SIZE - 2] = b'!';
+    let inputs = [Vec::new(), vec![7], b"abcd".to_vec(), large];
+
+    for input in inputs {
+        let compressed = compress_with_options(&input, &snappy_options(1));
+        assert_eq!(stream_name(&compressed), Some("Snappy"));
+        assert_eq!(inspect(&compressed).unwrap().original_size, None);
+        let decoded = decompress_with_options(&compressed, &DecompressOptions::default());
+        assert_eq!(
+            decoded.as_deref(),
+            Ok(input.as_slice()),
+            "{} bytes",
+            input.len()
+        );
+
+        let raw = compress_snappy_raw(&input, &snappy_options(9));
+        let decoded = decompress_snappy_raw(&raw, &DecompressOptions::default());
+        assert_eq!(
+            decoded.as_deref(),
+            Ok(input.as_slice()),
+            "raw {} bytes",
+            input.len()
+        );
+    }
+}
+
+#[test]
+fn reads_hand_built_streams() {
+    let (raw, expected) = hand_built_raw();
+    let decoded = decompress_snappy_raw(&raw, &DecompressOptions::default());
+    assert_eq!(decoded.as_deref(), Ok(expected.as_slice()));
+
+    // Padding, a skippable chunk and a repeated identifier between data chunks
+    let stream = [
+        SNAPPY_STREAM_IDENTIFIER.to_vec(),
+        data_chunk(0x00, &raw, &expected),
+        chunk(0xFE, &[0; 5]),
+        chunk(0x80, b"skip me"),
+        SNAPPY_STREAM_IDENTIFIER.to_vec(),
+        data_chunk(0x01, b"stored", b"stored"),
+    ]
+    .concat();
+    let decoded = decompress_with_options(&stream, &DecompressOptions::default());
+    assert_eq!(decoded, Ok([&expected[..], b"stored"].concat()));
+}
+
+#[test]
+fn corrupt_streams_are_not_decoded() {
+    let (raw, expected) = hand_built_raw();
+    let valid = [
+        SNAPPY_STREAM_IDENTIFIER.to_vec(),
+        data_chunk(0x00, &raw, &expected),
+    ]
+    .concat();
+
+    // Checksum, declared length, an element tag, a reserved unskippable chunk and
+    // a truncated chunk
+    let mut corrupt_streams = Vec::new();
+    for position in [14, 18, 24] {
+        let mut corrupt = valid.clone();
+        corrupt[position] ^= 0x01;
+        corrupt_streams.push(corrupt);
+    }
+    corrupt_streams.push([&valid[..], &chunk(0x02, b"")].concat());
+    corrupt_streams.push(valid[..valid.len() - 1].to_vec());
+
+    for corrupt in corrupt_streams {
+        let decoded = decompress_with_options(&corrupt, &DecompressOptions::default());
+        assert_eq!(decoded.as_ref(), Ok(&corrupt));
+    }
+
+    // Raw copies may not reach before the start, nor literals past the end of
+    // the address space
+    for raw in [
+        &[3, 0x04, b'a', b'b', 0x01, 3][..],
+        &[3, 0xFC, 0xFF, 0xFF, 0xFF, 0xFF, b'a'],
+    ] {
+        let decoded = decompress_snappy_raw(raw, &DecompressOptions::default());
+        assert_eq!(decoded.as_deref(), Ok(raw));
+    }
+}
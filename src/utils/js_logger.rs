@@ -0,0 +1,54 @@
+// Log backends for JS hosts: the browser console, or a caller-supplied
+// `onLog(level, message)` callback for routing logs into app telemetry.
+
+use crate::utils::logger::{LevelFilter, LogLevel, Logger};
+use js_sys::Function;
+use core::fmt;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console)]
+    fn log(s: &str);
+}
+
+/// Writes `[LEVEL] message` lines with `console.log`.
+#[cfg(target_arch = "wasm32")]
+pub struct ConsoleLogger {
+    pub filter: LevelFilter,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Logger for ConsoleLogger {
+    fn enabled(&self, level: LogLevel) -> bool {
+        self.filter.allows(level)
+    }
+
+    fn log(&self, level: LogLevel, message: fmt::Arguments<'_>) {
+        log(&format!("[{}] {}", level.as_str().to_uppercase(), message));
+    }
+}
+
+/// Calls `callback(level, message)` with the level as its option string.
+pub struct JsCallbackLogger {
+    pub filter: LevelFilter,
+    pub callback: Function,
+}
+
+impl Logger for JsCallbackLogger {
+    fn enabled(&self, level: LogLevel) -> bool {
+        self.filter.allows(level)
+    }
+
+    fn log(&self, level: LogLevel, message: fmt::Arguments<'_>) {
+        // A throwing callback must not abort compression
+        let _ = self.callback.call2(
+            &JsValue::NULL,
+            &JsValue::from_str(level.as_str()),
+            &JsValue::from_str(&message.to_string()),
+        );
+    }
+}
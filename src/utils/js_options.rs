@@ -0,0 +1,105 @@
+// Option parsing for the `#[wasm_bindgen]` entry points: reads the caller's
+// options object and checks every property against `validate`, so a typo
+// or an out-of-range value throws instead of quietly using the default.
+
+use super::js_logger::JsCallbackLogger;
+use super::js_progress::JsProgress;
+use super::validate::{apply_option, OptionValue};
+use super::{default_logger, LevelFilter, Logger};
+use crate::shared::options::{CompressOptions, DecompressOptions};
+use js_sys::{Function, Object, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Everything the options object configures, for either direction.
+pub struct JsOptions {
+    pub compress: CompressOptions,
+    pub decompress: DecompressOptions,
+    pub progress: JsProgress,
+    on_log: Option<Function>,
+}
+
+impl JsOptions {
+    /// The `onLog(level, message)` callback if one is given, else the
+    /// default backend for `filter`.
+    pub fn logger(&self, filter: LevelFilter) -> Box<dyn Logger> {
+        match &self.on_log {
+            Some(callback) if !filter.is_off() => Box::new(JsCallbackLogger {
+                filter,
+                callback: callback.clone(),
+            }),
+            _ => default_logger(filter),
+        }
+    }
+}
+
+/// Parses `options`, which may be `undefined` or `null` for the defaults.
+/// Properties set to `undefined` or `null` count as absent.
+pub fn parse_options(options: &JsValue) -> Result<JsOptions, String> {
+    let mut parsed = JsOptions {
+        compress: CompressOptions::default(),
+        decompress: DecompressOptions::default(),
+        progress: JsProgress {
+            on_progress: None,
+            signal: None,
+        },
+        on_log: None,
+    };
+    if options.is_undefined() || options.is_null() {
+        return Ok(parsed);
+    }
+    let object = options
+        .dyn_ref::<Object>()
+        .ok_or_else(|| format!("options must be an object, got {}", type_name(options)))?;
+
+    for key in Object::keys(object).iter() {
+        let key = key.as_string().unwrap_or_default();
+        let value = Reflect::get(options, &JsValue::from_str(&key)).unwrap_or(JsValue::UNDEFINED);
+        if value.is_undefined() || value.is_null() {
+            continue;
+        }
+        match key.as_str() {
+            "onLog" => parsed.on_log = Some(function(&key, value)?),
+            "onProgress" => parsed.progress.on_progress = Some(function(&key, value)?),
+            "signal" if value.is_object() => parsed.progress.signal = Some(value),
+            "signal" => {
+                return Err(format!(
+                    "option 'signal' must be an AbortSignal, got {}",
+                    type_name(&value)
+                ))
+            }
+            _ => apply_option(
+                &mut parsed.compress,
+                &mut parsed.decompress,
+                &key,
+                &option_value(&value),
+            )?,
+        }
+    }
+    Ok(parsed)
+}
+
+fn function(key: &str, value: JsValue) -> Result<Function, String> {
+    value.dyn_into::<Function>().map_err(|value| {
+        format!(
+            "option '{}' must be a function, got {}",
+            key,
+            type_name(&value)
+        )
+    })
+}
+
+fn option_value(value: &JsValue) -> OptionValue {
+    if let Some(text) = value.as_string() {
+        OptionValue::Str(text)
+    } else if let Some(number) = value.as_f64() {
+        OptionValue::Number(number)
+    } else if let Some(flag) = value.as_bool() {
+        OptionValue::Bool(flag)
+    } else {
+        OptionValue::Other(type_name(value))
+    }
+}
+
+fn type_name(value: &JsValue) -> String {
+    value.js_typeof().as_string().unwrap_or_default()
+}
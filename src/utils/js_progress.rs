@@ -0,0 +1,34 @@
+// Progress hooks for JS hosts: `onProgress(bytesDone, bytesTotal)` and an
+// AbortSignal-like `signal` whose `aborted` flag cancels the call. Both run
+// synchronously, so a signal can only be aborted from inside `onProgress`.
+
+use crate::shared::progress::Progress;
+use js_sys::{Function, Reflect};
+use wasm_bindgen::JsValue;
+
+pub struct JsProgress {
+    pub on_progress: Option<Function>,
+    pub signal: Option<JsValue>,
+}
+
+impl Progress for JsProgress {
+    fn report(&self, done: usize, total: usize) {
+        if let Some(callback) = &self.on_progress {
+            // A throwing callback must not abort compression
+            let _ = callback.call2(
+                &JsValue::NULL,
+                &JsValue::from_f64(done as f64),
+                &JsValue::from_f64(total as f64),
+            );
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.signal.as_ref().is_some_and(|signal| {
+            Reflect::get(signal, &JsValue::from_str("aborted"))
+                .ok()
+                .and_then(|aborted| aborted.as_bool())
+                .unwrap_or(false)
+        })
+    }
+}
@@ -0,0 +1,77 @@
+// Converts a `CompressionReport` into a plain JS object for `compress_with_report`.
+
+use crate::compression::report::{ChunkReport, CompressionReport, StageReport};
+use crate::decompression::inspect::{method_name, stream_name};
+use js_sys::{Array, Object, Reflect, Uint8Array};
+use wasm_bindgen::JsValue;
+
+pub fn report_to_js(data: &[u8], report: &CompressionReport) -> JsValue {
+    let chunks: Array = report.chunks.iter().map(chunk_to_js).collect();
+
+    let js_report = Object::new();
+    set(&js_report, "algorithm", report.algorithm.name().into());
+    let method = stream_name(data).map_or_else(|| method_js(report.flag), JsValue::from);
+    set(&js_report, "method", method);
+    set(&js_report, "originalSize", report.original_size.into());
+    set(&js_report, "compressedSize", report.compressed_size.into());
+    set(&js_report, "ratio", report.ratio.into());
+    set(&js_report, "timeMs", report.time_ms.into());
+    set(&js_report, "chunks", chunks.into());
+
+    let result = Object::new();
+    set(&result, "data", Uint8Array::from(data).into());
+    set(&result, "report", js_report.into());
+    result.into()
+}
+
+fn chunk_to_js(chunk: &ChunkReport) -> JsValue {
+    let chain: Array = chunk
+        .chain
+        .iter()
+        .map(|&method| method_js(method))
+        .collect();
+    let stages: Array = chunk.stages.iter().map(stage_to_js).collect();
+
+    let object = Object::new();
+    set(&object, "originalSize", chunk.original_size.into());
+    set(&object, "compressedSize", chunk.compressed_size.into());
+    set(&object, "ratio", chunk.ratio.into());
+    set(&object, "entropy", chunk.entropy.into());
+    set(&object, "timeMs", chunk.time_ms.into());
+    set(&object, "chain", chain.into());
+    set(&object, "stages", stages.into());
+    object.into()
+}
+
+fn stage_to_js(stage: &StageReport) -> JsValue {
+    let candidates: Array = stage
+        .candidates
+        .iter()
+        .map(|candidate| {
+            let object = Object::new();
+            set(&object, "method", method_js(candidate.method));
+            set(&object, "size", candidate.size.into());
+            set(&object, "score", candidate.score.into());
+            JsValue::from(object)
+        })
+        .collect();
+
+    let object = Object::new();
+    set(&object, "inputSize", stage.input_size.into());
+    set(&object, "candidates", candidates.into());
+    set(
+        &object,
+        "chosen",
+        stage.chosen.map_or(JsValue::NULL, method_js),
+    );
+    object.into()
+}
+
+fn method_js(flag: u8) -> JsValue {
+    method_name(flag).map_or_else(|| format!("{:02X}", flag).into(), JsValue::from)
+}
+
+fn set(object: &Object, key: &str, value: JsValue) {
+    // Setting a property on a fresh plain object cannot fail
+    let _ = Reflect::set(object, &JsValue::from_str(key), &value);
+}
@@ -1,36 +1,123 @@
 use crate::constants::{
     LOG_LEVEL_DEBUG, LOG_LEVEL_ERROR, LOG_LEVEL_INFO, LOG_LEVEL_NONE, LOG_LEVEL_PERFORMANCE,
 };
-use wasm_bindgen::prelude::*;
+use core::fmt;
 
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = console)]
-    pub fn log(s: &str);
-
-    #[wasm_bindgen(js_namespace = console, js_name = log)]
-    pub fn log_u32(a: u32);
-
-    #[wasm_bindgen(js_namespace = console, js_name = log)]
-    pub fn log_many(a: &str, b: &str);
+/// Log verbosity, from quietest to noisiest. In JS this is the numeric
+/// `LogLevel` enum; the `logLevel` option also accepts the names `parse`
+/// understands.
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LogLevel {
+    #[default]
+    None = 0,
+    Error = 1,
+    Info = 2,
+    Debug = 3,
+    Performance = 4,
 }
 
-pub fn log_message(level: &str, current_level: &str, message: &str, verbose: bool) {
-    let levels = vec![
-        LOG_LEVEL_NONE,
-        LOG_LEVEL_ERROR,
-        LOG_LEVEL_INFO,
-        LOG_LEVEL_DEBUG,
-        LOG_LEVEL_PERFORMANCE,
+impl LogLevel {
+    /// Every level, in discriminant order.
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::None,
+        LogLevel::Error,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Performance,
     ];
-    let current_index = levels.iter().position(|&l| l == current_level).unwrap_or(0);
-    let level_index = levels.iter().position(|&l| l == level).unwrap_or(0);
-
-    if level_index <= current_index {
-        if verbose || level != LOG_LEVEL_PERFORMANCE {
-            log(&format!("[{}] {}", level.to_uppercase(), message));
-        } else if level == LOG_LEVEL_PERFORMANCE {
-            log(&format!("[{}] {}", level.to_uppercase(), message));
+
+    /// Parses the `logLevel` option strings ("none", "error", "info", "debug",
+    /// "performance"), ignoring case.
+    pub fn parse(value: &str) -> Option<LogLevel> {
+        LogLevel::ALL
+            .into_iter()
+            .find(|level| level.as_str().eq_ignore_ascii_case(value))
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::None => LOG_LEVEL_NONE,
+            LogLevel::Error => LOG_LEVEL_ERROR,
+            LogLevel::Info => LOG_LEVEL_INFO,
+            LogLevel::Debug => LOG_LEVEL_DEBUG,
+            LogLevel::Performance => LOG_LEVEL_PERFORMANCE,
         }
     }
 }
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Destination for log messages. `log_message!` checks `enabled` before
+/// formatting, so a disabled level costs one call and no allocation.
+pub trait Logger {
+    fn enabled(&self, level: LogLevel) -> bool;
+    fn log(&self, level: LogLevel, message: fmt::Arguments<'_>);
+}
+
+/// Which messages a built-in backend writes: everything up to `level`, plus
+/// performance messages when `verbose` is set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LevelFilter {
+    pub level: LogLevel,
+    pub verbose: bool,
+}
+
+impl LevelFilter {
+    pub fn allows(&self, level: LogLevel) -> bool {
+        level != LogLevel::None
+            && (level <= self.level || (self.verbose && level == LogLevel::Performance))
+    }
+
+    /// True if no message can pass, so callers can skip logging entirely.
+    pub fn is_off(&self) -> bool {
+        self.level == LogLevel::None && !self.verbose
+    }
+}
+
+/// Discards everything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopLogger;
+
+impl Logger for NoopLogger {
+    fn enabled(&self, _level: LogLevel) -> bool {
+        false
+    }
+
+    fn log(&self, _level: LogLevel, _message: fmt::Arguments<'_>) {}
+}
+
+/// Writes `[LEVEL] message` lines to stderr.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StderrLogger {
+    pub filter: LevelFilter,
+}
+
+#[cfg(feature = "std")]
+impl Logger for StderrLogger {
+    fn enabled(&self, level: LogLevel) -> bool {
+        self.filter.allows(level)
+    }
+
+    fn log(&self, level: LogLevel, message: fmt::Arguments<'_>) {
+        eprintln!("[{}] {}", level.as_str().to_uppercase(), message);
+    }
+}
+
+/// Logs `$fmt, $args` at `$level` through `$logger`, formatting only if the
+/// logger accepts that level.
+macro_rules! log_message {
+    ($logger:expr, $level:expr, $($arg:tt)+) => {{
+        let logger: &dyn $crate::utils::Logger = $logger;
+        let level = $level;
+        if logger.enabled(level) {
+            logger.log(level, format_args!($($arg)+));
+        }
+    }};
+}
+pub(crate) use log_message;
@@ -1,12 +1,40 @@
 mod logger;
+mod timer;
 
-use js_sys::Reflect;
-pub use logger::log_message;
-use wasm_bindgen::JsValue;
+#[cfg(feature = "wasm")]
+mod js_logger;
+#[cfg(feature = "wasm")]
+mod js_options;
+#[cfg(feature = "wasm")]
+mod js_progress;
+#[cfg(feature = "wasm")]
+mod js_report;
+#[cfg(any(feature = "wasm", test))]
+pub(crate) mod validate;
 
-pub fn get_log_level(options: &JsValue) -> String {
-    Reflect::get(options, &JsValue::from_str("logLevel"))
-        .unwrap_or(JsValue::from_str("none"))
-        .as_string()
-        .unwrap_or("none".to_string())
+#[cfg(feature = "wasm")]
+pub use js_options::parse_options;
+#[cfg(feature = "wasm")]
+pub use js_report::report_to_js;
+pub(crate) use logger::log_message;
+#[cfg(feature = "std")]
+pub use logger::StderrLogger;
+pub use logger::{LevelFilter, LogLevel, Logger, NoopLogger};
+pub use timer::now_ms;
+
+use alloc::boxed::Box;
+
+/// No-op when `filter` lets nothing through; otherwise the console in a JS
+/// host and stderr elsewhere. Without `std` there is nowhere to write, so
+/// always a no-op.
+pub fn default_logger(filter: LevelFilter) -> Box<dyn Logger> {
+    if filter.is_off() {
+        return Box::new(NoopLogger);
+    }
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    return Box::new(js_logger::ConsoleLogger { filter });
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    return Box::new(StderrLogger { filter });
+    #[cfg(not(feature = "std"))]
+    return Box::new(NoopLogger);
 }
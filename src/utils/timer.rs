@@ -0,0 +1,27 @@
+// Wall-clock milliseconds for reports. `std::time` is unavailable in the
+// browser, so wasm builds read the JS clock instead, or report zero without
+// the `wasm` feature. Without `std` there is no clock, and times are
+// reported as zero.
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+pub fn now_ms() -> f64 {
+    0.0
+}
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub fn now_ms() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}
+
+#[cfg(all(not(feature = "std"), not(target_arch = "wasm32")))]
+pub fn now_ms() -> f64 {
+    0.0
+}
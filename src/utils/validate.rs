@@ -0,0 +1,166 @@
+// Validation of the JS options object, kept free of `JsValue` so it can be
+// tested natively. `js_options` reduces each property to an `OptionValue`
+// and applies it here; anything unknown or out of range is an error rather
+// than a silent fallback to the default.
+
+use crate::constants::{MAX_LEVEL, MIN_LEVEL};
+use crate::shared::algorithm::Algorithm;
+use crate::shared::options::{ChunkMode, CompressOptions, DecompressOptions};
+use crate::utils::LogLevel;
+use alloc::format;
+use alloc::string::String;
+
+/// Keys accepted by both `compress` and `decompress`, so one object can be
+/// passed to each; a function ignores the keys meant for the other.
+pub const OPTION_KEYS: [&str; 14] = [
+    "algorithm",
+    "level",
+    "chunkSize",
+    "chunkMode",
+    "recordWidth",
+    "dictionary",
+    "longRange",
+    "maxOutputSize",
+    "maxRatio",
+    "logLevel",
+    "verbose",
+    "onLog",
+    "onProgress",
+    "signal",
+];
+
+/// An option's value, reduced to what validation needs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionValue {
+    Str(String),
+    Number(f64),
+    Bool(bool),
+    /// Any other type, by its `typeof` name.
+    Other(String),
+}
+
+/// Aliases accepted for `algorithm` besides the canonical names.
+const ALGORITHM_HELP: &str =
+    "best (or auto, chunked), LZ+Huffman (or lz), RLE, Delta, bwt, NumPack, Record, LZ4, Snappy, FSE or CM";
+
+/// Applies the data option `key` to whichever options struct uses it.
+/// Callbacks and `signal` are checked by the caller, which needs the raw
+/// value.
+pub fn apply_option(
+    compress: &mut CompressOptions,
+    decompress: &mut DecompressOptions,
+    key: &str,
+    value: &OptionValue,
+) -> Result<(), String> {
+    match key {
+        "algorithm" => {
+            compress.algorithm = match value {
+                OptionValue::Str(name) => Algorithm::parse(name).ok_or_else(|| {
+                    format!("unknown algorithm '{}'; expected {}", name, ALGORITHM_HELP)
+                })?,
+                OptionValue::Number(index) => enum_index(&Algorithm::ALL, *index)
+                    .ok_or_else(|| format!("algorithm {} is not an Algorithm value", index))?,
+                other => return Err(wrong_type(key, "an Algorithm or its name", other)),
+            };
+        }
+        "level" => {
+            compress.level = integer(key, value, MIN_LEVEL as f64, MAX_LEVEL as f64)? as u8;
+        }
+        "chunkSize" => compress.chunk_size = integer(key, value, 1.0, u32::MAX as f64)? as usize,
+        "chunkMode" => {
+            compress.chunk_mode = match value {
+                OptionValue::Str(name) => ChunkMode::parse(name).ok_or_else(|| {
+                    format!(
+                        "unknown chunkMode '{}'; expected independent or dependent",
+                        name
+                    )
+                })?,
+                other => return Err(wrong_type(key, "a string", other)),
+            };
+        }
+        "recordWidth" => {
+            compress.record_width = Some(integer(key, value, 1.0, u16::MAX as f64)? as usize);
+        }
+        "dictionary" => match value {
+            OptionValue::Bool(dictionary) => compress.dictionary = *dictionary,
+            other => return Err(wrong_type(key, "a boolean", other)),
+        },
+        "longRange" => match value {
+            OptionValue::Bool(long_range) => compress.long_range = *long_range,
+            other => return Err(wrong_type(key, "a boolean", other)),
+        },
+        "maxOutputSize" => {
+            decompress.max_output_size = integer(key, value, 0.0, usize::MAX as f64)? as usize;
+        }
+        "maxRatio" => match value {
+            OptionValue::Number(ratio) if ratio.is_finite() && *ratio > 0.0 => {
+                decompress.max_ratio = Some(*ratio);
+            }
+            OptionValue::Number(ratio) => {
+                return Err(format!(
+                    "option 'maxRatio' must be a positive number, got {}",
+                    ratio
+                ))
+            }
+            other => return Err(wrong_type(key, "a number", other)),
+        },
+        "logLevel" => {
+            let level = match value {
+                OptionValue::Str(name) => LogLevel::parse(name).ok_or_else(|| {
+                    format!(
+                        "unknown logLevel '{}'; expected none, error, info, debug or performance",
+                        name
+                    )
+                })?,
+                OptionValue::Number(index) => enum_index(&LogLevel::ALL, *index)
+                    .ok_or_else(|| format!("logLevel {} is not a LogLevel value", index))?,
+                other => return Err(wrong_type(key, "a LogLevel or its name", other)),
+            };
+            compress.log_level = level;
+            decompress.log_level = level;
+        }
+        "verbose" => match value {
+            OptionValue::Bool(verbose) => {
+                compress.verbose = *verbose;
+                decompress.verbose = *verbose;
+            }
+            other => return Err(wrong_type(key, "a boolean", other)),
+        },
+        _ if OPTION_KEYS.contains(&key) => {}
+        _ => {
+            return Err(format!(
+                "unknown option '{}'; expected one of {}",
+                key,
+                OPTION_KEYS.join(", ")
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn integer(key: &str, value: &OptionValue, min: f64, max: f64) -> Result<f64, String> {
+    match value {
+        OptionValue::Number(n) if n.fract() == 0.0 && (min..=max).contains(n) => Ok(*n),
+        OptionValue::Number(n) => Err(format!(
+            "option '{}' must be an integer from {} to {}, got {}",
+            key, min, max, n
+        )),
+        other => Err(wrong_type(key, "an integer", other)),
+    }
+}
+
+fn enum_index<T: Copy>(all: &[T], index: f64) -> Option<T> {
+    (index.fract() == 0.0 && index >= 0.0)
+        .then(|| all.get(index as usize).copied())
+        .flatten()
+}
+
+fn wrong_type(key: &str, expected: &str, value: &OptionValue) -> String {
+    let found = match value {
+        OptionValue::Str(_) => "string",
+        OptionValue::Number(_) => "number",
+        OptionValue::Bool(_) => "boolean",
+        OptionValue::Other(type_name) => type_name,
+    };
+    format!("option '{}' must be {}, got {}", key, expected, found)
+}
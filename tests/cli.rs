@@ -0,0 +1,135 @@
+// End-to-end checks of the `goud` binary: file and stdin/stdout round trips,
+// keep/test/stats flags, option forms and argument errors.
+
+#![cfg(feature = "std")]
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+fn goud(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_goud"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start goud");
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("goud-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn sample() -> Vec<u8> {
+    (0..3_000)
+        .flat_map(|i: u32| format!("{{\"id\":{},\"name\":\"item {}\"}},", i, i % 37).into_bytes())
+        .collect()
+}
+
+#[test]
+fn stdin_round_trip() {
+    let input = sample();
+    let compressed = goud(&["-a", "lz+huffman", "-3"], &input);
+    assert!(compressed.status.success());
+    assert!(compressed.stdout.len() < input.len());
+
+    let decompressed = goud(&["-d"], &compressed.stdout);
+    assert!(decompressed.status.success());
+    assert!(decompressed.stdout == input);
+}
+
+#[test]
+fn file_round_trip_replaces_input_unless_kept() {
+    let dir = scratch_dir("file");
+    let path = dir.join("data.json");
+    let packed = dir.join("data.json.goud");
+    fs::write(&path, sample()).unwrap();
+
+    let kept = goud(&["-k", "--chunk-size", "8K", path.to_str().unwrap()], &[]);
+    assert!(kept.status.success(), "{}", String::from_utf8_lossy(&kept.stderr));
+    assert!(path.exists() && packed.exists());
+
+    let refused = goud(&[path.to_str().unwrap()], &[]);
+    assert!(!refused.status.success());
+
+    fs::remove_file(&path).unwrap();
+    let restored = goud(&["-d", packed.to_str().unwrap()], &[]);
+    assert!(restored.status.success());
+    assert!(!packed.exists());
+    assert!(fs::read(&path).unwrap() == sample());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_and_stats_leave_files_alone() {
+    let dir = scratch_dir("test");
+    let path = dir.join("data.json");
+    fs::write(&path, sample()).unwrap();
+
+    let checked = goud(&["-t", "--stats", path.to_str().unwrap()], &[]);
+    assert!(checked.status.success());
+    assert!(checked.stdout.is_empty());
+    let stats = String::from_utf8_lossy(&checked.stderr);
+    assert!(stats.contains("method: Chunked"), "{}", stats);
+    assert!(stats.contains("chunk 0:"), "{}", stats);
+    assert!(path.exists());
+    assert!(!dir.join("data.json.goud").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn corrupt_stream_fails_integrity_test() {
+    let mut compressed = goud(&[], &sample()).stdout;
+    compressed.truncate(compressed.len() / 2);
+    let checked = goud(&["-dt"], &compressed);
+    assert!(!checked.status.success());
+}
+
+#[test]
+fn option_values_may_be_attached() {
+    let input = sample();
+    let expected = goud(&["-a", "lz", "-l", "9"], &input).stdout;
+    for args in [
+        &["-alz", "-l9"][..],
+        &["--algorithm=lz", "--level=9"],
+        &["-a", "lz", "-9"],
+    ] {
+        let output = goud(args, &input);
+        assert!(output.status.success(), "{:?}", args);
+        assert!(output.stdout == expected, "{:?}", args);
+    }
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    for args in [
+        &["-a", "zip"][..],
+        &["--level", "0"],
+        &["-l0"],
+        &["--chunk-size", "0"],
+        &["--bogus"],
+    ] {
+        let output = goud(args, &[]);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+    }
+}
+
+#[test]
+fn output_limits_are_enforced() {
+    let compressed = goud(&[], &sample()).stdout;
+    let capped = goud(&["-d", "--max-output-size", "1K"], &compressed);
+    assert_eq!(capped.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&capped.stderr).contains("max_output_size"));
+
+    let ratio = goud(&["-d", "--max-ratio", "1.5"], &compressed);
+    assert!(String::from_utf8_lossy(&ratio.stderr).contains("max_ratio"));
+}